@@ -36,7 +36,7 @@ fn main() -> Result<(), xlsxwriter::XlsxError> {
         ),
     )?;
 
-    sheet1.set_selection(1, 0, 1, 2);
-    sheet1.set_tab_color(FormatColor::Cyan);
+    sheet1.set_selection(1, 0, 1, 2)?;
+    sheet1.set_tab_color(FormatColor::Cyan)?;
     workbook.close()
 }
@@ -1,13 +1,16 @@
 mod constants;
 mod series;
 mod structs;
+mod writer;
 
+use crate::error::XlsxErrorSource;
 use crate::format::FormatColor;
 use crate::XlsxError;
 
 pub use self::constants::*;
 pub use self::series::*;
 pub use self::structs::*;
+pub use self::writer::*;
 use super::Workbook;
 
 /// The Chart object represents an Excel chart. It provides functions for adding data series to the chart and for configuring the chart.
@@ -45,12 +48,21 @@ use super::Workbook;
 /// Add one or more data series to the chart which refers to data in the workbook using `Chart.add_series`.
 /// Configure the chart with the other available functions shown below.
 /// Insert the chart into a worksheet using `Worksheet.insert_chart`.
-pub struct Chart<'a> {
-    pub(crate) _workbook: &'a Workbook,
+pub struct Chart {
+    pub(crate) _workbook: Workbook,
     pub(crate) chart: *mut libxlsxwriter_sys::lxw_chart,
 }
 
-impl<'a> Chart<'a> {
+impl Chart {
+    /// Returns the libxlsxwriter chart, or an error if the workbook is already closed.
+    pub(crate) fn raw_chart(&self) -> Result<*mut libxlsxwriter_sys::lxw_chart, XlsxError> {
+        if self._workbook.is_closed() {
+            Err(XlsxErrorSource::WorkbookClosed.into())
+        } else {
+            Ok(self.chart)
+        }
+    }
+
     /// In Excel a chart **series** is a collection of information that defines which data is plotted such as the categories and values. It is also used to define the formatting for the data.
     ///
     /// For an libxlsxwriter chart object the `chart_add_series` function is used to set the categories and values of the series:
@@ -170,16 +182,20 @@ impl<'a> Chart<'a> {
         &mut self,
         categories: Option<&str>,
         values: Option<&str>,
-    ) -> Result<ChartSeries<'a>, XlsxError> {
+    ) -> Result<ChartSeries, XlsxError> {
         let series = unsafe {
             libxlsxwriter_sys::chart_add_series(
-                self.chart,
-                self._workbook.register_option_str(categories)?,
-                self._workbook.register_option_str(values)?,
+                self.raw_chart()?,
+                self._workbook
+                    .register_option_str(categories)
+                    .map_err(|e| e.with_method("add_series"))?,
+                self._workbook
+                    .register_option_str(values)
+                    .map_err(|e| e.with_method("add_series"))?,
             )
         };
         Ok(ChartSeries {
-            _workbook: self._workbook,
+            _workbook: self._workbook.handle(),
             chart_series: series,
         })
     }
@@ -190,8 +206,10 @@ impl<'a> Chart<'a> {
     pub fn add_title(&mut self, title: &str) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_title_set_name(
-                self.chart,
-                self._workbook.register_str(title)?,
+                self.raw_chart()?,
+                self._workbook
+                    .register_str(title)
+                    .map_err(|e| e.with_method("add_title"))?,
             );
         }
         Ok(())
@@ -200,13 +218,13 @@ impl<'a> Chart<'a> {
 
 /// Struct to represent an Excel chart data series.
 /// This struct is created using the `chart.add_series` function. It is used in functions that modify a chart series but the members of the struct aren't modified directly.
-pub struct ChartSeries<'a> {
-    pub(crate) _workbook: &'a Workbook,
+pub struct ChartSeries {
+    pub(crate) _workbook: Workbook,
     pub(crate) chart_series: *mut libxlsxwriter_sys::lxw_chart_series,
 }
 
 /// Struct to represent a chart pattern.
-#[derive(Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub struct ChartPattern {
     /// The pattern foreground color.
     pub fg_color: FormatColor,
@@ -217,7 +235,7 @@ pub struct ChartPattern {
 }
 
 /// Struct to represent a chart line.
-#[derive(Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub struct ChartLine {
     /// The chart font color.
     pub color: FormatColor,
@@ -232,7 +250,7 @@ pub struct ChartLine {
 }
 
 /// Struct to represent a chart fill.
-#[derive(Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct ChartFill {
     /// The chart font color.
     pub color: FormatColor,
@@ -266,7 +284,7 @@ pub enum ChartType {
     RadarFilled,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChartDashType {
     Solid,
     RoundDot,
@@ -278,7 +296,7 @@ pub enum ChartDashType {
     LongDashDotDot,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChartPatternType {
     /// None pattern.
     None,
@@ -380,7 +398,7 @@ pub enum ChartPatternType {
     SolidDiamond,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChartMarkerType {
     MarkerAutomatic,
     MarkerNone,
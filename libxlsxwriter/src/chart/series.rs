@@ -1,7 +1,25 @@
 use super::{ChartFill, ChartLine, ChartMarkerType, ChartPattern, ChartSeries};
+use crate::error::{ErrorContext, XlsxErrorSource};
+use crate::utility::CellRange;
 use crate::{convert_bool, WorksheetCol, WorksheetRow, XlsxError};
 
-impl<'a> ChartSeries<'a> {
+pub(super) fn series_sheet_name(range: &CellRange) -> Result<&str, XlsxError> {
+    range
+        .sheet_name
+        .as_deref()
+        .ok_or_else(|| XlsxErrorSource::InvalidCellReference(range.to_string()).into())
+}
+
+impl ChartSeries {
+    /// Returns the libxlsxwriter chart series, or an error if the workbook is already closed.
+    pub(crate) fn raw_series(&self) -> Result<*mut libxlsxwriter_sys::lxw_chart_series, XlsxError> {
+        if self._workbook.is_closed() {
+            Err(XlsxErrorSource::WorkbookClosed.into())
+        } else {
+            Ok(self.chart_series)
+        }
+    }
+
     /// The categories and values of a chart data series are generally set using the `chart_add_series` function and Excel range formulas like "=Sheet1!$A$2:$A$7".
     ///
     /// The `ChartSeries.set_categories()` function is an alternative method that is easier to generate programmatically. It requires that you set the categories and values parameters in `Chart.add_series` to `None` and then set them using row and column values in `ChartSeries.set_categories` and `ChartSeries.set_values`:
@@ -36,8 +54,15 @@ impl<'a> ChartSeries<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_series_set_categories(
-                self.chart_series,
-                self._workbook.register_str(sheet_name)?,
+                self.raw_series()?,
+                self._workbook.register_str(sheet_name).map_err(|e| {
+                    e.with_context(ErrorContext {
+                        sheet_name: Some(sheet_name.to_string()),
+                        first_cell: Some((first_row, first_column)),
+                        last_cell: Some((last_row, last_column)),
+                        method: Some("set_categories"),
+                    })
+                })?,
                 first_row,
                 first_column,
                 last_row,
@@ -60,8 +85,15 @@ impl<'a> ChartSeries<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_series_set_values(
-                self.chart_series,
-                self._workbook.register_str(sheet_name)?,
+                self.raw_series()?,
+                self._workbook.register_str(sheet_name).map_err(|e| {
+                    e.with_context(ErrorContext {
+                        sheet_name: Some(sheet_name.to_string()),
+                        first_cell: Some((first_row, first_column)),
+                        last_cell: Some((last_row, last_column)),
+                        method: Some("set_values"),
+                    })
+                })?,
                 first_row,
                 first_column,
                 last_row,
@@ -71,6 +103,50 @@ impl<'a> ChartSeries<'a> {
         Ok(())
     }
 
+    /// Same as [`ChartSeries::set_categories`], with the range given as a [`CellRange`] which must have a sheet name.
+    /// The sheet name is quoted as required:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// use xlsxwriter::utility::CellRange;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-chart_series-set_values_ref-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(Some("Sales 2024"))?;
+    /// # for i in 0..5 {
+    /// #     worksheet.write_string(i, 0, &format!("value {}", i + 1), None)?;
+    /// #     worksheet.write_number(i, 1, (i*10 + 2).into(), None)?;
+    /// # }
+    /// # let mut chart = workbook.add_chart(ChartType::Column);
+    /// let mut series = chart.add_series(None, None)?;
+    /// series.set_categories_ref(&"'Sales 2024'!A1:A5".parse()?)?;
+    /// series.set_values_ref(&CellRange::new(0, 1, 4, 1).with_sheet_name("Sales 2024"))?;
+    /// # worksheet.insert_chart(1, 3, &chart)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn set_categories_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let sheet_name = series_sheet_name(range)?;
+        self.set_categories(
+            sheet_name,
+            range.first.row,
+            range.first.col,
+            range.last.row,
+            range.last.col,
+        )
+    }
+
+    /// Same as [`ChartSeries::set_values`], with the range given as a [`CellRange`] which must have a sheet name.
+    /// See [`ChartSeries::set_categories_ref`].
+    pub fn set_values_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let sheet_name = series_sheet_name(range)?;
+        self.set_values(
+            sheet_name,
+            range.first.row,
+            range.first.col,
+            range.last.row,
+            range.last.col,
+        )
+    }
+
     /// This function is used to set the name for a chart data series. The series name in Excel is displayed in the chart legend and in the formula bar. The name property is optional and if it isn't supplied it will default to `Series 1..n`.
     ///
     /// ```rust
@@ -126,8 +202,10 @@ impl<'a> ChartSeries<'a> {
     pub fn set_name(&mut self, name: &str) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_series_set_name(
-                self.chart_series,
-                self._workbook.register_str(name)?,
+                self.raw_series()?,
+                self._workbook
+                    .register_str(name)
+                    .map_err(|e| e.with_method("set_name"))?,
             );
         }
         Ok(())
@@ -166,8 +244,15 @@ impl<'a> ChartSeries<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_series_set_name_range(
-                self.chart_series,
-                self._workbook.register_str(sheet_name)?,
+                self.raw_series()?,
+                self._workbook.register_str(sheet_name).map_err(|e| {
+                    e.with_context(ErrorContext {
+                        sheet_name: Some(sheet_name.to_string()),
+                        first_cell: Some((row, column)),
+                        last_cell: Some((row, column)),
+                        method: Some("set_name_range"),
+                    })
+                })?,
                 row,
                 column,
             );
@@ -188,9 +273,9 @@ impl<'a> ChartSeries<'a> {
     /// let mut series3 = chart.add_series(None, Some("=Sheet1!$C$2:$C$6"))?;
     /// let mut chart_line = ChartLine::new();
     /// chart_line.color = FormatColor::Red;
-    /// series1.set_line(&chart_line);
-    /// series2.set_line(&chart_line);
-    /// series3.set_line(&chart_line);
+    /// series1.set_line(&chart_line)?;
+    /// series2.set_line(&chart_line)?;
+    /// series3.set_line(&chart_line)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -207,10 +292,11 @@ impl<'a> ChartSeries<'a> {
     /// # }
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-chart_series-set_line-1.png)
-    pub fn set_line(&mut self, line: &ChartLine) {
+    pub fn set_line(&mut self, line: &ChartLine) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::chart_series_set_line(self.chart_series, &mut line.value());
+            libxlsxwriter_sys::chart_series_set_line(self.raw_series()?, &mut line.value());
         }
+        Ok(())
     }
 
     /// Set the fill properties of a chart series:
@@ -230,9 +316,9 @@ impl<'a> ChartSeries<'a> {
     /// chart_fill_2.color = FormatColor::Yellow;
     /// let mut chart_fill_3 = ChartFill::new();
     /// chart_fill_3.color = FormatColor::Green;
-    /// series1.set_fill(&chart_fill_1);
-    /// series2.set_fill(&chart_fill_2);
-    /// series3.set_fill(&chart_fill_3);
+    /// series1.set_fill(&chart_fill_1)?;
+    /// series2.set_fill(&chart_fill_2)?;
+    /// series3.set_fill(&chart_fill_3)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -249,10 +335,11 @@ impl<'a> ChartSeries<'a> {
     /// # }
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-chart_series-set_fill-1.png)
-    pub fn set_fill(&mut self, fill: &ChartFill) {
+    pub fn set_fill(&mut self, fill: &ChartFill) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::chart_series_set_fill(self.chart_series, &mut fill.value());
+            libxlsxwriter_sys::chart_series_set_fill(self.raw_series()?, &mut fill.value());
         }
+        Ok(())
     }
 
     /// Invert the fill color for negative values. Usually only applicable to column and bar charts.
@@ -275,12 +362,12 @@ impl<'a> ChartSeries<'a> {
     /// # chart_fill_2.color = FormatColor::Yellow;
     /// # let mut chart_fill_3 = ChartFill::new();
     /// # chart_fill_3.color = FormatColor::Green;
-    /// # series1.set_fill(&chart_fill_1);
-    /// series1.set_invert_if_negative();
-    /// # series2.set_fill(&chart_fill_2);
-    /// # series2.set_invert_if_negative();
-    /// # series3.set_fill(&chart_fill_3);
-    /// # series3.set_invert_if_negative();
+    /// # series1.set_fill(&chart_fill_1)?;
+    /// series1.set_invert_if_negative()?;
+    /// # series2.set_fill(&chart_fill_2)?;
+    /// # series2.set_invert_if_negative()?;
+    /// # series3.set_fill(&chart_fill_3)?;
+    /// # series3.set_invert_if_negative()?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -297,10 +384,11 @@ impl<'a> ChartSeries<'a> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_invert_if_negative(&mut self) {
+    pub fn set_invert_if_negative(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::chart_series_set_invert_if_negative(self.chart_series);
+            libxlsxwriter_sys::chart_series_set_invert_if_negative(self.raw_series()?);
         }
+        Ok(())
     }
 
     /// Set the pattern properties of a chart series:
@@ -316,9 +404,9 @@ impl<'a> ChartSeries<'a> {
     /// # series1.set_name("=Sheet1!$A$1");
     /// # series2.set_name("=Sheet1!$B$1");
     /// let pattern1 = ChartPattern::new(FormatColor::Custom(0x804000), FormatColor::Custom(0xC68C53), ChartPatternType::Shingle);
-    /// series1.set_pattern(&pattern1);
+    /// series1.set_pattern(&pattern1)?;
     /// let pattern2 = ChartPattern::new(FormatColor::Custom(0xB30000), FormatColor::Custom(0xFF6666), ChartPatternType::HorizontalBrick);
-    /// series2.set_pattern(&pattern2);
+    /// series2.set_pattern(&pattern2)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -334,10 +422,11 @@ impl<'a> ChartSeries<'a> {
     /// # }
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-chart_series-set_pattern-1.png)
-    pub fn set_pattern(&mut self, pattern: &ChartPattern) {
+    pub fn set_pattern(&mut self, pattern: &ChartPattern) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::chart_series_set_pattern(self.chart_series, &mut pattern.value());
+            libxlsxwriter_sys::chart_series_set_pattern(self.raw_series()?, &mut pattern.value());
         }
+        Ok(())
     }
 
     /// In Excel a chart marker is used to distinguish data points in a plotted series. In general only Line and Scatter and Radar chart types use markers.
@@ -350,7 +439,7 @@ impl<'a> ChartSeries<'a> {
     /// # let mut chart = workbook.add_chart(ChartType::Line);
     /// # let mut series1 = chart.add_series(None, Some("=Sheet1!$A$2:$A$6"))?;
     /// # series1.set_name("=Sheet1!$A$1");
-    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond);
+    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -364,10 +453,11 @@ impl<'a> ChartSeries<'a> {
     /// # }
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-chart_series-set_marker_type-1.png)
-    pub fn set_marker_type(&mut self, maker_type: ChartMarkerType) {
+    pub fn set_marker_type(&mut self, maker_type: ChartMarkerType) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::chart_series_set_marker_type(self.chart_series, maker_type.value());
+            libxlsxwriter_sys::chart_series_set_marker_type(self.raw_series()?, maker_type.value());
         }
+        Ok(())
     }
 
     /// This function is used to specify the size of the series marker.
@@ -380,8 +470,8 @@ impl<'a> ChartSeries<'a> {
     /// # let mut chart = workbook.add_chart(ChartType::Line);
     /// # let mut series1 = chart.add_series(None, Some("=Sheet1!$A$2:$A$6"))?;
     /// # series1.set_name("=Sheet1!$A$1");
-    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond);
-    /// series1.set_marker_size(10);
+    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond)?;
+    /// series1.set_marker_size(10)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -394,8 +484,9 @@ impl<'a> ChartSeries<'a> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_marker_size(&mut self, maker_size: u8) {
-        unsafe { libxlsxwriter_sys::chart_series_set_marker_size(self.chart_series, maker_size) }
+    pub fn set_marker_size(&mut self, maker_size: u8) -> Result<(), XlsxError> {
+        unsafe { libxlsxwriter_sys::chart_series_set_marker_size(self.raw_series()?, maker_size) }
+        Ok(())
     }
 
     /// Set the line/border properties of a chart marker.
@@ -408,11 +499,11 @@ impl<'a> ChartSeries<'a> {
     /// # let mut chart = workbook.add_chart(ChartType::Line);
     /// # let mut series1 = chart.add_series(None, Some("=Sheet1!$A$2:$A$6"))?;
     /// # series1.set_name("=Sheet1!$A$1");
-    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond);
+    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond)?;
     /// let mut marker_line = ChartLine::new();
     /// marker_line.color = FormatColor::Red;
-    /// series1.set_marker_line(&marker_line);
-    /// series1.set_marker_size(10);
+    /// series1.set_marker_line(&marker_line)?;
+    /// series1.set_marker_size(10)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -425,13 +516,14 @@ impl<'a> ChartSeries<'a> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_marker_line(&mut self, chart_line: &ChartLine) {
+    pub fn set_marker_line(&mut self, chart_line: &ChartLine) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_series_set_marker_line(
-                self.chart_series,
+                self.raw_series()?,
                 &mut chart_line.value(),
             );
         }
+        Ok(())
     }
 
     /// Set the line/border properties of a chart marker.
@@ -444,14 +536,14 @@ impl<'a> ChartSeries<'a> {
     /// # let mut chart = workbook.add_chart(ChartType::Line);
     /// # let mut series1 = chart.add_series(None, Some("=Sheet1!$A$2:$A$6"))?;
     /// # series1.set_name("=Sheet1!$A$1");
-    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond);
+    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond)?;
     /// let mut marker_line = ChartLine::new();
     /// marker_line.color = FormatColor::Red;
-    /// series1.set_marker_line(&marker_line);
+    /// series1.set_marker_line(&marker_line)?;
     /// let mut marker_fill = ChartFill::new();
     /// marker_fill.color = FormatColor::Yellow;
-    /// series1.set_marker_fill(&marker_fill);
-    /// series1.set_marker_size(10);
+    /// series1.set_marker_fill(&marker_fill)?;
+    /// series1.set_marker_size(10)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -464,13 +556,14 @@ impl<'a> ChartSeries<'a> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_marker_fill(&mut self, chart_fill: &ChartFill) {
+    pub fn set_marker_fill(&mut self, chart_fill: &ChartFill) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::chart_series_set_marker_fill(
-                self.chart_series,
+                self.raw_series()?,
                 &mut chart_fill.value(),
             );
         }
+        Ok(())
     }
 
     // TODO: chart_series_set_points
@@ -485,7 +578,7 @@ impl<'a> ChartSeries<'a> {
     /// # let mut chart = workbook.add_chart(ChartType::Line);
     /// # let mut series1 = chart.add_series(None, Some("=Sheet1!$A$2:$A$6"))?;
     /// # series1.set_name("=Sheet1!$A$1");
-    /// series1.set_smooth(true);
+    /// series1.set_smooth(true)?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -499,10 +592,11 @@ impl<'a> ChartSeries<'a> {
     /// # }
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-chart_series-set_smooth-1.png)
-    pub fn set_smooth(&mut self, smooth: bool) {
+    pub fn set_smooth(&mut self, smooth: bool) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::chart_series_set_smooth(self.chart_series, convert_bool(smooth));
+            libxlsxwriter_sys::chart_series_set_smooth(self.raw_series()?, convert_bool(smooth));
         }
+        Ok(())
     }
 
     /// This function is used to turn on data labels for a chart series. Data labels indicate the values of the plotted data points.
@@ -515,8 +609,8 @@ impl<'a> ChartSeries<'a> {
     /// # let mut chart = workbook.add_chart(ChartType::Line);
     /// # let mut series1 = chart.add_series(None, Some("=Sheet1!$A$2:$A$6"))?;
     /// # series1.set_name("=Sheet1!$A$1");
-    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond);
-    /// series1.set_labels();
+    /// series1.set_marker_type(ChartMarkerType::MarkerDiamond)?;
+    /// series1.set_labels()?;
     /// # worksheet.insert_chart(1, 3, &chart)?;
     /// # workbook.close()
     /// # }
@@ -530,7 +624,8 @@ impl<'a> ChartSeries<'a> {
     /// # }
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-chart_series-set_smooth-1.png)
-    pub fn set_labels(&mut self) {
-        unsafe { libxlsxwriter_sys::chart_series_set_labels(self.chart_series) }
+    pub fn set_labels(&mut self) -> Result<(), XlsxError> {
+        unsafe { libxlsxwriter_sys::chart_series_set_labels(self.raw_series()?) }
+        Ok(())
     }
 }
@@ -0,0 +1,290 @@
+use super::series::series_sheet_name;
+use super::{Chart, ChartFill, ChartLine, ChartMarkerType, ChartPattern, ChartSeries};
+use crate::utility::CellRange;
+use crate::{WorksheetCol, WorksheetRow, XlsxError};
+
+/// An operation on a chart series. The variants correspond to the [`ChartSeries`] methods of the same name.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChartSeriesOp {
+    /// [`ChartSeries::set_categories`].
+    Categories {
+        sheet_name: String,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    },
+    /// [`ChartSeries::set_values`].
+    Values {
+        sheet_name: String,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    },
+    /// [`ChartSeries::set_name`].
+    Name { name: String },
+    /// [`ChartSeries::set_name_range`].
+    NameRange {
+        sheet_name: String,
+        row: WorksheetRow,
+        col: WorksheetCol,
+    },
+    /// [`ChartSeries::set_line`].
+    Line(ChartLine),
+    /// [`ChartSeries::set_fill`].
+    Fill(ChartFill),
+    /// [`ChartSeries::set_invert_if_negative`].
+    InvertIfNegative,
+    /// [`ChartSeries::set_pattern`].
+    Pattern(ChartPattern),
+    /// [`ChartSeries::set_marker_type`].
+    MarkerType(ChartMarkerType),
+    /// [`ChartSeries::set_marker_size`].
+    MarkerSize(u8),
+    /// [`ChartSeries::set_marker_line`].
+    MarkerLine(ChartLine),
+    /// [`ChartSeries::set_marker_fill`].
+    MarkerFill(ChartFill),
+    /// [`ChartSeries::set_smooth`].
+    Smooth(bool),
+    /// [`ChartSeries::set_labels`].
+    Labels,
+}
+
+/// An operation on a chart, as recorded by a [`MockChart`](crate::mock::MockChart).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ChartOp {
+    /// [`Chart::add_series`].
+    AddSeries {
+        categories: Option<String>,
+        values: Option<String>,
+    },
+    /// [`Chart::add_title`].
+    Title { title: String },
+    /// An operation on a series. `series` is the index of the series in the order the series were added.
+    Series { series: usize, op: ChartSeriesOp },
+}
+
+/// Chart operations shared by [`Chart`] and [`MockChart`](crate::mock::MockChart).
+/// Charts are created by [`WorkbookWriter::add_chart`](crate::worksheet::WorkbookWriter::add_chart).
+pub trait ChartWriter {
+    /// Series returned by [`ChartWriter::add_series`].
+    type Series: ChartSeriesWriter;
+
+    /// See [`Chart::add_series`].
+    fn add_series(
+        &mut self,
+        categories: Option<&str>,
+        values: Option<&str>,
+    ) -> Result<Self::Series, XlsxError>;
+
+    /// See [`Chart::add_title`].
+    fn add_title(&mut self, title: &str) -> Result<(), XlsxError>;
+}
+
+/// Chart series operations shared by [`ChartSeries`] and [`MockChartSeries`](crate::mock::MockChartSeries).
+/// The methods take the same arguments as the [`ChartSeries`] methods of the same name.
+pub trait ChartSeriesWriter {
+    /// Perform `op`. A [`ChartSeries`] sets it, and a [`MockChartSeries`](crate::mock::MockChartSeries) records it.
+    fn write_series_op(&mut self, op: ChartSeriesOp) -> Result<(), XlsxError>;
+
+    /// See [`ChartSeries::set_categories`].
+    fn set_categories(
+        &mut self,
+        sheet_name: &str,
+        first_row: WorksheetRow,
+        first_column: WorksheetCol,
+        last_row: WorksheetRow,
+        last_column: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Categories {
+            sheet_name: sheet_name.to_string(),
+            first_row,
+            first_col: first_column,
+            last_row,
+            last_col: last_column,
+        })
+    }
+
+    /// See [`ChartSeries::set_values`].
+    fn set_values(
+        &mut self,
+        sheet_name: &str,
+        first_row: WorksheetRow,
+        first_column: WorksheetCol,
+        last_row: WorksheetRow,
+        last_column: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Values {
+            sheet_name: sheet_name.to_string(),
+            first_row,
+            first_col: first_column,
+            last_row,
+            last_col: last_column,
+        })
+    }
+
+    /// See [`ChartSeries::set_categories_ref`].
+    fn set_categories_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let sheet_name = series_sheet_name(range)?;
+        self.set_categories(
+            sheet_name,
+            range.first.row,
+            range.first.col,
+            range.last.row,
+            range.last.col,
+        )
+    }
+
+    /// See [`ChartSeries::set_values_ref`].
+    fn set_values_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let sheet_name = series_sheet_name(range)?;
+        self.set_values(
+            sheet_name,
+            range.first.row,
+            range.first.col,
+            range.last.row,
+            range.last.col,
+        )
+    }
+
+    /// See [`ChartSeries::set_name`].
+    fn set_name(&mut self, name: &str) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Name {
+            name: name.to_string(),
+        })
+    }
+
+    /// See [`ChartSeries::set_name_range`].
+    fn set_name_range(
+        &mut self,
+        sheet_name: &str,
+        row: WorksheetRow,
+        column: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::NameRange {
+            sheet_name: sheet_name.to_string(),
+            row,
+            col: column,
+        })
+    }
+
+    /// See [`ChartSeries::set_line`].
+    fn set_line(&mut self, line: &ChartLine) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Line(*line))
+    }
+
+    /// See [`ChartSeries::set_fill`].
+    fn set_fill(&mut self, fill: &ChartFill) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Fill(fill.clone()))
+    }
+
+    /// See [`ChartSeries::set_invert_if_negative`].
+    fn set_invert_if_negative(&mut self) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::InvertIfNegative)
+    }
+
+    /// See [`ChartSeries::set_pattern`].
+    fn set_pattern(&mut self, pattern: &ChartPattern) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Pattern(*pattern))
+    }
+
+    /// See [`ChartSeries::set_marker_type`].
+    fn set_marker_type(&mut self, maker_type: ChartMarkerType) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::MarkerType(maker_type))
+    }
+
+    /// See [`ChartSeries::set_marker_size`].
+    fn set_marker_size(&mut self, maker_size: u8) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::MarkerSize(maker_size))
+    }
+
+    /// See [`ChartSeries::set_marker_line`].
+    fn set_marker_line(&mut self, chart_line: &ChartLine) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::MarkerLine(*chart_line))
+    }
+
+    /// See [`ChartSeries::set_marker_fill`].
+    fn set_marker_fill(&mut self, chart_fill: &ChartFill) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::MarkerFill(chart_fill.clone()))
+    }
+
+    /// See [`ChartSeries::set_smooth`].
+    fn set_smooth(&mut self, smooth: bool) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Smooth(smooth))
+    }
+
+    /// See [`ChartSeries::set_labels`].
+    fn set_labels(&mut self) -> Result<(), XlsxError> {
+        self.write_series_op(ChartSeriesOp::Labels)
+    }
+}
+
+impl ChartWriter for Chart {
+    type Series = ChartSeries;
+
+    fn add_series(
+        &mut self,
+        categories: Option<&str>,
+        values: Option<&str>,
+    ) -> Result<ChartSeries, XlsxError> {
+        Chart::add_series(self, categories, values)
+    }
+
+    fn add_title(&mut self, title: &str) -> Result<(), XlsxError> {
+        Chart::add_title(self, title)
+    }
+}
+
+impl ChartSeriesWriter for ChartSeries {
+    fn write_series_op(&mut self, op: ChartSeriesOp) -> Result<(), XlsxError> {
+        match op {
+            ChartSeriesOp::Categories {
+                sheet_name,
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            } => ChartSeries::set_categories(
+                self,
+                &sheet_name,
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            ),
+            ChartSeriesOp::Values {
+                sheet_name,
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            } => {
+                ChartSeries::set_values(self, &sheet_name, first_row, first_col, last_row, last_col)
+            }
+            ChartSeriesOp::Name { name } => ChartSeries::set_name(self, &name),
+            ChartSeriesOp::NameRange {
+                sheet_name,
+                row,
+                col,
+            } => ChartSeries::set_name_range(self, &sheet_name, row, col),
+            ChartSeriesOp::Line(line) => ChartSeries::set_line(self, &line),
+            ChartSeriesOp::Fill(fill) => ChartSeries::set_fill(self, &fill),
+            ChartSeriesOp::InvertIfNegative => ChartSeries::set_invert_if_negative(self),
+            ChartSeriesOp::Pattern(pattern) => ChartSeries::set_pattern(self, &pattern),
+            ChartSeriesOp::MarkerType(marker_type) => {
+                ChartSeries::set_marker_type(self, marker_type)
+            }
+            ChartSeriesOp::MarkerSize(marker_size) => {
+                ChartSeries::set_marker_size(self, marker_size)
+            }
+            ChartSeriesOp::MarkerLine(line) => ChartSeries::set_marker_line(self, &line),
+            ChartSeriesOp::MarkerFill(fill) => ChartSeries::set_marker_fill(self, &fill),
+            ChartSeriesOp::Smooth(smooth) => ChartSeries::set_smooth(self, smooth),
+            ChartSeriesOp::Labels => ChartSeries::set_labels(self),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::error::XlsxErrorSource;
+use crate::utility::{self, CellRange, CellRef};
+use crate::{WorksheetCol, WorksheetRow, XlsxError};
+
+/// Maximum number of characters in a defined name.
+const MAX_NAME_LENGTH: usize = 255;
+
+/// A defined name, which is added to a workbook with [`Workbook::add_defined_name`](crate::Workbook::add_defined_name).
+///
+/// A defined name is global by default, and can be made local to a worksheet with [`DefinedName::local`].
+/// ```rust
+/// # use xlsxwriter::prelude::*;
+/// # fn main() -> Result<(), XlsxError> {
+/// let workbook = Workbook::new("test-workbook-add_defined_name.xlsx")?;
+/// let mut worksheet = workbook.add_worksheet(Some("Q3 Sales"))?;
+/// workbook.add_defined_name(&DefinedName::new("Exchange_rate", "=0.95"))?;
+/// // ='Q3 Sales'!$A$1:$A$10
+/// workbook.add_defined_name(&DefinedName::range_at("Sales", "Q3 Sales", 0, 0, 9, 0))?;
+/// // Local to the sheet 'Q3 Sales' and hidden in Excel's Name Manager
+/// workbook.add_defined_name(&DefinedName::new("Rate", "=1.1").local("Q3 Sales").hidden(true))?;
+/// assert!(workbook.add_defined_name(&DefinedName::new("A1", "=1")).is_err());
+/// worksheet.write_formula(0, 1, "=SUM(Sales)*Exchange_rate*Rate", None)?;
+/// # workbook.close()
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DefinedName {
+    pub(crate) name: String,
+    pub(crate) sheet_name: Option<String>,
+    pub(crate) formula: String,
+    pub(crate) hidden: bool,
+}
+
+impl DefinedName {
+    /// A global name referring to `formula`, such as `=0.95` or `=Sheet1!$A$1`.
+    pub fn new(name: &str, formula: &str) -> Self {
+        DefinedName {
+            name: name.to_string(),
+            sheet_name: None,
+            formula: formula.to_string(),
+            hidden: false,
+        }
+    }
+
+    /// A global name referring to `range`. The range should have a sheet name.
+    pub fn range(name: &str, range: &CellRange) -> Self {
+        DefinedName::new(name, &range.to_formula())
+    }
+
+    /// A global name referring to the absolute range from `first_row`, `first_col` to `last_row`, `last_col` on `sheet_name`.
+    pub fn range_at(
+        name: &str,
+        sheet_name: &str,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    ) -> Self {
+        DefinedName::range(
+            name,
+            &CellRange::new(first_row, first_col, last_row, last_col)
+                .absolute()
+                .with_sheet_name(sheet_name),
+        )
+    }
+
+    /// Make the name local to the worksheet `sheet_name`, such as `'Q3 Sales'!Rate`.
+    pub fn local(mut self, sheet_name: &str) -> Self {
+        self.sheet_name = Some(sheet_name.to_string());
+        self
+    }
+
+    /// Hide the name from Excel's Name Manager.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Check the rules of Excel for a defined name: 1 to 255 characters, starting with a letter, `_` or `\`,
+    /// followed by letters, digits, `_` and `.`, and not a cell reference such as `A1` or `R1C1`.
+    pub(crate) fn validate(&self) -> Result<(), XlsxError> {
+        let name = &self.name;
+        let mut chars = name.chars();
+        let valid = match chars.next() {
+            Some(first) => {
+                (first.is_alphabetic() || first == '_' || first == '\\')
+                    && chars.all(|x| x.is_alphanumeric() || x == '_' || x == '.')
+            }
+            None => false,
+        };
+        if !valid
+            || name.chars().count() > MAX_NAME_LENGTH
+            || name.parse::<CellRef>().is_ok()
+            || utility::is_r1c1_reference(name)
+        {
+            return Err(XlsxErrorSource::InvalidDefinedName(name.to_string()).into());
+        }
+        Ok(())
+    }
+
+    /// The name in the form libxlsxwriter takes, such as `'Q3 Sales'!Rate` for a local name.
+    pub(crate) fn qualified_name(&self) -> String {
+        match &self.sheet_name {
+            // libxlsxwriter removes the quotes, but doesn't unescape doubled quotes
+            Some(sheet_name) => format!("'{}'!{}", sheet_name, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Add `hidden="1"` to the `<definedName>` elements of `names` in the workbook part `xml`.
+pub(crate) fn hide_defined_names(xml: &str, names: &[DefinedName]) -> String {
+    let sheet_names = sheet_names(xml);
+    let mut xml = xml.to_string();
+    for name in names {
+        let mut start_tag = format!(r#"<definedName name="{}""#, escape_attribute(&name.name));
+        if let Some(sheet_name) = &name.sheet_name {
+            let escaped = escape_attribute(sheet_name);
+            match sheet_names.iter().position(|x| *x == escaped) {
+                Some(index) => start_tag.push_str(&format!(r#" localSheetId="{}""#, index)),
+                None => continue,
+            }
+        }
+        start_tag.push('>');
+        if let Some(start) = xml.find(&start_tag) {
+            xml.insert_str(start + start_tag.len() - 1, r#" hidden="1""#);
+        }
+    }
+    xml
+}
+
+/// Escaped names of the sheets in the workbook part, in the order of `localSheetId`.
+fn sheet_names(xml: &str) -> Vec<String> {
+    let sheets = match (xml.find("<sheets>"), xml.find("</sheets>")) {
+        (Some(start), Some(end)) => &xml[start..end],
+        _ => return Vec::new(),
+    };
+    sheets
+        .split("<sheet ")
+        .skip(1)
+        .filter_map(|x| {
+            let value = &x[x.find(r#"name=""#)? + 6..];
+            Some(value[..value.find('"')?].to_string())
+        })
+        .collect()
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        for name in ["Rate", "_rate", "\\rate", "Tax.2024", "税率", "AB_1"] {
+            assert!(DefinedName::new(name, "=1").validate().is_ok(), "{}", name);
+        }
+        let long = "a".repeat(256);
+        for name in [
+            "",
+            "A1",
+            "$A$1",
+            "R1C1",
+            "r",
+            "C",
+            "1st",
+            "my rate",
+            "a-b",
+            long.as_str(),
+        ] {
+            assert!(DefinedName::new(name, "=1").validate().is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_hide_defined_names() {
+        let xml = concat!(
+            r#"<workbook><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/>"#,
+            r#"<sheet name="Q&amp;A" sheetId="2" r:id="rId2"/></sheets><definedNames>"#,
+            r#"<definedName name="Rate" localSheetId="1">1.1</definedName>"#,
+            r#"<definedName name="Rate">1</definedName>"#,
+            r#"<definedName name="Total">2</definedName></definedNames></workbook>"#
+        );
+        let names = [
+            DefinedName::new("Rate", "=1.1").local("Q&A"),
+            DefinedName::new("Total", "=2"),
+            DefinedName::new("Missing", "=3"),
+        ];
+        assert_eq!(
+            hide_defined_names(xml, &names),
+            xml.replace(r#"localSheetId="1">"#, r#"localSheetId="1" hidden="1">"#)
+                .replace(r#""Total">"#, r#""Total" hidden="1">"#)
+        );
+    }
+}
@@ -1,4 +1,7 @@
 use super::XlsxError;
+use crate::limits;
+use crate::utility::{self, CellRef};
+use crate::worksheet::{WorksheetCol, WorksheetRow};
 use std::error::Error;
 use std::ffi;
 use std::fmt::{self, Display};
@@ -9,26 +12,291 @@ pub(crate) enum XlsxErrorSource {
     NumberOfColumnsIsNotMatched,
     Unknown,
     NulError(std::ffi::NulError),
+    Io(String),
+    NonFiniteNumber(f64),
+    InvalidCellReference(String),
+    InvalidDefinedName(String),
+    UnknownWorksheet(String),
+    WorkbookClosed,
+    WorksheetInUse(String),
+    Cancelled,
+    MissingTemplateValue(String),
+    #[cfg(feature = "arrow")]
+    Arrow(String),
+    #[cfg(feature = "rust_decimal")]
+    InexactDecimal(rust_decimal::Decimal),
+}
+
+/// A list specifying general categories of [`XlsxError`].
+///
+/// Most of the kinds correspond to an error code of libxlsxwriter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum XlsxErrorKind {
+    /// Memory error, failed to malloc() required memory.
+    MemoryMallocFailed,
+    /// Error creating output xlsx file. Usually a permissions error.
+    CreatingXlsxFile,
+    /// Error encountered when creating a tmpfile during file assembly.
+    CreatingTmpfile,
+    /// Error reading a tmpfile.
+    ReadingTmpfile,
+    /// Zip generic error ZIP_ERRNO while creating the xlsx file.
+    ZipFileOperation,
+    /// Zip error ZIP_PARAMERROR while creating the xlsx file.
+    ZipParameterError,
+    /// Zip error ZIP_BADZIPFILE (use_zip64 option may be required).
+    ZipBadZipFile,
+    /// Zip error ZIP_INTERNALERROR while creating the xlsx file.
+    ZipInternalError,
+    /// File error or unknown zip error when adding sub file to xlsx file.
+    ZipFileAdd,
+    /// Unknown zip error when closing xlsx file.
+    ZipClose,
+    /// Feature is not currently supported in this configuration.
+    FeatureNotSupported,
+    /// NULL function parameter ignored.
+    NullParameterIgnored,
+    /// Function parameter validation error.
+    ParameterValidation,
+    /// Function string parameter is empty.
+    ParameterIsEmpty,
+    /// Worksheet name exceeds Excel's limit of 31 characters.
+    SheetNameLengthExceeded,
+    /// Worksheet name cannot contain invalid characters: `[ ] : * ? / \`
+    InvalidSheetNameCharacter,
+    /// Worksheet name cannot start or end with an apostrophe.
+    SheetNameStartEndApostrophe,
+    /// Worksheet name is already in use.
+    SheetNameAlreadyUsed,
+    /// Parameter exceeds Excel's limit of 32 characters.
+    StringLengthExceeded32,
+    /// Parameter exceeds Excel's limit of 128 characters.
+    StringLengthExceeded128,
+    /// Parameter exceeds Excel's limit of 255 characters.
+    StringLengthExceeded255,
+    /// String exceeds Excel's limit of 32,767 characters.
+    MaxStringLengthExceeded,
+    /// Error finding internal string index.
+    SharedStringIndexNotFound,
+    /// Worksheet row or column index out of range.
+    WorksheetIndexOutOfRange,
+    /// Maximum hyperlink length (2079) exceeded.
+    MaxUrlLengthExceeded,
+    /// Maximum number of worksheet URLs (65530) exceeded.
+    MaxNumberUrlsExceeded,
+    /// Couldn't read image dimensions or DPI.
+    ImageDimensions,
+    /// Number of columns in table options is not equal to the table size.
+    NumberOfColumnsIsNotMatched,
+    /// String contains a null byte.
+    NulError,
+    /// IO error while reading or writing the xlsx file.
+    Io,
+    /// NaN or infinite number cannot be written.
+    NonFiniteNumber,
+    /// Cell reference or range in A1 notation cannot be parsed, or refers to another sheet.
+    InvalidCellReference,
+    /// Defined name doesn't follow the naming rules of Excel.
+    InvalidDefinedName,
+    /// No worksheet has the given name.
+    UnknownWorksheet,
+    /// Worksheet or chart is used after [`Workbook::close`](crate::Workbook::close).
+    WorkbookClosed,
+    /// Another [`Worksheet`](crate::Worksheet) object for the worksheet is alive.
+    WorksheetInUse,
+    /// Closing the workbook was cancelled with a [`CancellationToken`](crate::workbook::CancellationToken).
+    Cancelled,
+    /// A placeholder of a [`SheetTemplate`](crate::worksheet::SheetTemplate) has no value in
+    /// [`ReplayOptions`](crate::worksheet::ReplayOptions).
+    MissingTemplateValue,
+    /// Arrow data cannot be written.
+    #[cfg(feature = "arrow")]
+    Arrow,
+    /// Decimal cannot be represented exactly as a number.
+    #[cfg(feature = "rust_decimal")]
+    InexactDecimal,
+    /// Unknown error.
+    Unknown,
+    /// Error code of libxlsxwriter unknown to this version of xlsxwriter.
+    Other(u32),
+}
+
+impl From<libxlsxwriter_sys::lxw_error> for XlsxErrorKind {
+    // lxw_error is a signed integer on some platforms
+    #[allow(clippy::unnecessary_cast)]
+    fn from(error: libxlsxwriter_sys::lxw_error) -> Self {
+        #[allow(non_upper_case_globals)]
+        match error {
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_MEMORY_MALLOC_FAILED => {
+                XlsxErrorKind::MemoryMallocFailed
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_CREATING_XLSX_FILE => {
+                XlsxErrorKind::CreatingXlsxFile
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_CREATING_TMPFILE => {
+                XlsxErrorKind::CreatingTmpfile
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_READING_TMPFILE => XlsxErrorKind::ReadingTmpfile,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_ZIP_FILE_OPERATION => {
+                XlsxErrorKind::ZipFileOperation
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_ZIP_PARAMETER_ERROR => {
+                XlsxErrorKind::ZipParameterError
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_ZIP_BAD_ZIP_FILE => XlsxErrorKind::ZipBadZipFile,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_ZIP_INTERNAL_ERROR => {
+                XlsxErrorKind::ZipInternalError
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_ZIP_FILE_ADD => XlsxErrorKind::ZipFileAdd,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_ZIP_CLOSE => XlsxErrorKind::ZipClose,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_FEATURE_NOT_SUPPORTED => {
+                XlsxErrorKind::FeatureNotSupported
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_NULL_PARAMETER_IGNORED => {
+                XlsxErrorKind::NullParameterIgnored
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_PARAMETER_VALIDATION => {
+                XlsxErrorKind::ParameterValidation
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_PARAMETER_IS_EMPTY => {
+                XlsxErrorKind::ParameterIsEmpty
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_LENGTH_EXCEEDED => {
+                XlsxErrorKind::SheetNameLengthExceeded
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_INVALID_SHEETNAME_CHARACTER => {
+                XlsxErrorKind::InvalidSheetNameCharacter
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_START_END_APOSTROPHE => {
+                XlsxErrorKind::SheetNameStartEndApostrophe
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_ALREADY_USED => {
+                XlsxErrorKind::SheetNameAlreadyUsed
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_32_STRING_LENGTH_EXCEEDED => {
+                XlsxErrorKind::StringLengthExceeded32
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_128_STRING_LENGTH_EXCEEDED => {
+                XlsxErrorKind::StringLengthExceeded128
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_255_STRING_LENGTH_EXCEEDED => {
+                XlsxErrorKind::StringLengthExceeded255
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_MAX_STRING_LENGTH_EXCEEDED => {
+                XlsxErrorKind::MaxStringLengthExceeded
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_SHARED_STRING_INDEX_NOT_FOUND => {
+                XlsxErrorKind::SharedStringIndexNotFound
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_WORKSHEET_INDEX_OUT_OF_RANGE => {
+                XlsxErrorKind::WorksheetIndexOutOfRange
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_WORKSHEET_MAX_URL_LENGTH_EXCEEDED => {
+                XlsxErrorKind::MaxUrlLengthExceeded
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_WORKSHEET_MAX_NUMBER_URLS_EXCEEDED => {
+                XlsxErrorKind::MaxNumberUrlsExceeded
+            }
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_IMAGE_DIMENSIONS => {
+                XlsxErrorKind::ImageDimensions
+            }
+            other => XlsxErrorKind::Other(other as u32),
+        }
+    }
+}
+
+/// Where an error happened. It is filled by worksheet methods.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct ErrorContext {
+    pub(crate) sheet_name: Option<String>,
+    pub(crate) first_cell: Option<(WorksheetRow, WorksheetCol)>,
+    pub(crate) last_cell: Option<(WorksheetRow, WorksheetCol)>,
+    pub(crate) method: Option<&'static str>,
 }
 
 impl Error for XlsxError {}
 
 impl XlsxError {
     pub(crate) fn new(error: libxlsxwriter_sys::lxw_error) -> XlsxError {
-        XlsxError {
-            source: XlsxErrorSource::LibXlsxWriter(error),
-        }
+        XlsxErrorSource::LibXlsxWriter(error).into()
     }
 
     pub(crate) fn unknown_error() -> XlsxError {
-        XlsxError {
-            source: XlsxErrorSource::Unknown,
+        XlsxErrorSource::Unknown.into()
+    }
+
+    /// Returns the kind of this error.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-error-kind-1.xlsx")?;
+    /// let mut worksheet = workbook.add_worksheet(Some("Q3"))?;
+    /// let error = worksheet.write_string(2_000_000, 2, "text", None).unwrap_err();
+    /// assert_eq!(error.kind(), XlsxErrorKind::WorksheetIndexOutOfRange);
+    /// assert_eq!(error.sheet_name(), Some("Q3"));
+    /// assert_eq!(error.cell(), Some((2_000_000, 2)));
+    /// assert_eq!(error.method(), Some("write_string"));
+    /// assert!(error.to_string().starts_with("Sheet 'Q3'!C2000001: "));
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn kind(&self) -> XlsxErrorKind {
+        match &self.source {
+            XlsxErrorSource::LibXlsxWriter(error) => (*error).into(),
+            XlsxErrorSource::NumberOfColumnsIsNotMatched => {
+                XlsxErrorKind::NumberOfColumnsIsNotMatched
+            }
+            XlsxErrorSource::Unknown => XlsxErrorKind::Unknown,
+            XlsxErrorSource::NulError(_) => XlsxErrorKind::NulError,
+            XlsxErrorSource::Io(_) => XlsxErrorKind::Io,
+            XlsxErrorSource::NonFiniteNumber(_) => XlsxErrorKind::NonFiniteNumber,
+            XlsxErrorSource::InvalidCellReference(_) => XlsxErrorKind::InvalidCellReference,
+            XlsxErrorSource::InvalidDefinedName(_) => XlsxErrorKind::InvalidDefinedName,
+            XlsxErrorSource::UnknownWorksheet(_) => XlsxErrorKind::UnknownWorksheet,
+            XlsxErrorSource::WorkbookClosed => XlsxErrorKind::WorkbookClosed,
+            XlsxErrorSource::WorksheetInUse(_) => XlsxErrorKind::WorksheetInUse,
+            XlsxErrorSource::Cancelled => XlsxErrorKind::Cancelled,
+            XlsxErrorSource::MissingTemplateValue(_) => XlsxErrorKind::MissingTemplateValue,
+            #[cfg(feature = "arrow")]
+            XlsxErrorSource::Arrow(_) => XlsxErrorKind::Arrow,
+            #[cfg(feature = "rust_decimal")]
+            XlsxErrorSource::InexactDecimal(_) => XlsxErrorKind::InexactDecimal,
         }
     }
-}
 
-impl Display for XlsxError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// Returns the name of the worksheet where the error happened.
+    pub fn sheet_name(&self) -> Option<&str> {
+        self.context.sheet_name.as_deref()
+    }
+
+    /// Returns the row and the column where the error happened.
+    /// For an error of a function writing to a range, the first row and column of the range are returned.
+    /// For an error of a function on whole rows or columns, such as [`Worksheet::set_row`](crate::Worksheet::set_row),
+    /// the first cell of the rows or columns is returned.
+    pub fn cell(&self) -> Option<(WorksheetRow, WorksheetCol)> {
+        self.context.first_cell
+    }
+
+    /// Returns the name of the method which returned the error, such as `write_string`.
+    pub fn method(&self) -> Option<&'static str> {
+        self.context.method
+    }
+
+    pub(crate) fn with_context(mut self, context: ErrorContext) -> XlsxError {
+        self.context = context;
+        self
+    }
+
+    /// Attaches `method` to an error which does not happen on a worksheet, such as an error of a workbook or a chart.
+    pub(crate) fn with_method(self, method: &'static str) -> XlsxError {
+        self.with_context(ErrorContext {
+            method: Some(method),
+            ..ErrorContext::default()
+        })
+    }
+
+    fn fmt_message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.source {
             XlsxErrorSource::Unknown => {
                 write!(f, "Unknown Error")
@@ -42,6 +310,45 @@ impl Display for XlsxError {
             XlsxErrorSource::NulError(e) => {
                 write!(f, "Null bytes in string: {}", e)
             }
+            XlsxErrorSource::Io(e) => {
+                write!(f, "IO error: {}", e)
+            }
+            XlsxErrorSource::NonFiniteNumber(number) => {
+                write!(f, "Excel doesn't support {} as a number value", number)
+            }
+            XlsxErrorSource::InvalidCellReference(reference) => {
+                write!(f, "Invalid cell reference: {}", reference)
+            }
+            XlsxErrorSource::InvalidDefinedName(name) => {
+                write!(f, "Invalid defined name: {}", name)
+            }
+            XlsxErrorSource::UnknownWorksheet(name) => {
+                write!(f, "Worksheet not found: {}", name)
+            }
+            XlsxErrorSource::WorkbookClosed => {
+                write!(f, "Workbook is already closed")
+            }
+            XlsxErrorSource::WorksheetInUse(name) => {
+                write!(f, "Worksheet is already in use: {}", name)
+            }
+            XlsxErrorSource::Cancelled => {
+                write!(f, "Closing the workbook was cancelled")
+            }
+            XlsxErrorSource::MissingTemplateValue(key) => {
+                write!(f, "No value for template placeholder: {}", key)
+            }
+            #[cfg(feature = "arrow")]
+            XlsxErrorSource::Arrow(e) => {
+                write!(f, "Arrow error: {}", e)
+            }
+            #[cfg(feature = "rust_decimal")]
+            XlsxErrorSource::InexactDecimal(value) => {
+                write!(
+                    f,
+                    "Decimal cannot be represented exactly as a number: {}",
+                    value
+                )
+            }
             XlsxErrorSource::LibXlsxWriter(error) => unsafe {
                 match ffi::CStr::from_ptr(libxlsxwriter_sys::lxw_strerror(*error)).to_str() {
                     Ok(error_text) => write!(f, "{}", error_text),
@@ -52,10 +359,70 @@ impl Display for XlsxError {
     }
 }
 
-impl From<std::ffi::NulError> for XlsxError {
-    fn from(e: std::ffi::NulError) -> Self {
+fn fmt_cell(f: &mut fmt::Formatter<'_>, (row, col): (WorksheetRow, WorksheetCol)) -> fmt::Result {
+    write!(f, "{}", CellRef::new(row, col))
+}
+
+/// Writes whole rows as `2:3` and whole columns as `B:C`, like Excel does.
+fn fmt_range(
+    f: &mut fmt::Formatter<'_>,
+    first_cell: (WorksheetRow, WorksheetCol),
+    last_cell: (WorksheetRow, WorksheetCol),
+) -> fmt::Result {
+    if first_cell.1 == 0 && last_cell.1 == limits::LAST_COL {
+        write!(
+            f,
+            "{}:{}",
+            u64::from(first_cell.0) + 1,
+            u64::from(last_cell.0) + 1
+        )
+    } else if first_cell.0 == 0 && last_cell.0 == limits::LAST_ROW {
+        write!(
+            f,
+            "{}:{}",
+            utility::col_to_name(first_cell.1),
+            utility::col_to_name(last_cell.1)
+        )
+    } else {
+        fmt_cell(f, first_cell)?;
+        if last_cell != first_cell {
+            write!(f, ":")?;
+            fmt_cell(f, last_cell)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for XlsxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let context = &self.context;
+        if let Some(sheet_name) = &context.sheet_name {
+            write!(f, "Sheet '{}'", sheet_name.replace('\'', "''"))?;
+            if let Some(first_cell) = context.first_cell {
+                write!(f, "!")?;
+                fmt_range(f, first_cell, context.last_cell.unwrap_or(first_cell))?;
+            }
+            write!(f, ": ")?;
+        }
+        self.fmt_message(f)?;
+        if let Some(method) = context.method {
+            write!(f, " (in {})", method)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<XlsxErrorSource> for XlsxError {
+    fn from(source: XlsxErrorSource) -> Self {
         XlsxError {
-            source: XlsxErrorSource::NulError(e),
+            source,
+            context: ErrorContext::default(),
         }
     }
 }
+
+impl From<std::ffi::NulError> for XlsxError {
+    fn from(e: std::ffi::NulError) -> Self {
+        XlsxErrorSource::NulError(e).into()
+    }
+}
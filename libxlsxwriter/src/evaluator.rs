@@ -0,0 +1,1009 @@
+//! A small formula evaluator which computes the cached results of formulas.
+//!
+//! libxlsxwriter doesn't calculate formulas and stores `0` as the result of [`Worksheet::write_formula`](crate::Worksheet::write_formula).
+//! Excel recalculates formulas when a file is opened, but viewers and readers which don't recalculate show the cached `0`.
+//! When [`Workbook::enable_formula_evaluator`](crate::Workbook::enable_formula_evaluator) is called, the workbook keeps the values written to
+//! the worksheets and formulas written with [`Worksheet::write_formula`](crate::Worksheet::write_formula) are evaluated with them.
+//! The result is stored as the cached value in the same way as [`Worksheet::write_formula_num`](crate::Worksheet::write_formula_num)
+//! and [`Worksheet::write_formula_str`](crate::Worksheet::write_formula_str).
+//!
+//! The evaluator supports:
+//! * numbers, strings, `TRUE`/`FALSE`, error values and references to cells and ranges, including other sheets such as `'Sheet 2'!A1:B3`
+//! * arithmetic `+ - * / ^ %`, concatenation `&` and comparisons `= <> < > <= >=`
+//! * the functions `SUM`, `AVERAGE`, `MIN`, `MAX`, `COUNT`, `IF`, `ROUND`, `CONCATENATE` and `CONCAT`
+//!
+//! A formula using anything else gets the fallback value of [`EvaluatorOptions`].
+//! Only cells written before the formula are known to the evaluator, so formulas should be written after the cells they refer to.
+//! A formula referring to a single cell which is not written yet gets the fallback value, while a cell written with
+//! [`Worksheet::write_blank`](crate::Worksheet::write_blank) is empty as in Excel. Cells of a range which are not written are skipped
+//! as empty cells.
+//!
+//! ```rust
+//! # use xlsxwriter::prelude::*;
+//! # fn main() -> Result<(), XlsxError> {
+//! let workbook = Workbook::new("test-evaluator.xlsx")?;
+//! workbook.enable_formula_evaluator(EvaluatorOptions::default());
+//! let mut worksheet = workbook.add_worksheet(None)?;
+//! worksheet.write_number(0, 0, 1.5, None)?;
+//! worksheet.write_number(1, 0, 2.5, None)?;
+//! // Cached result is 4
+//! worksheet.write_formula(2, 0, "=SUM(A1:A2)", None)?;
+//! // Cached result is "total: 4"
+//! worksheet.write_formula(3, 0, "=\"total: \" & A3", None)?;
+//! # workbook.close()
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::limits;
+use crate::utility::{self, CellRef};
+use crate::worksheet::{CellError, WorksheetCol, WorksheetRow};
+
+/// Value of a cell known to the evaluator, or the result of a formula.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum FormulaValue {
+    Number(f64),
+    String(String),
+    Boolean(bool),
+    Error(CellError),
+    /// An empty cell. As a formula result, the formula is written with an empty string result.
+    Empty,
+}
+
+/// Options of the formula evaluator.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct EvaluatorOptions {
+    /// Cached result of a formula which the evaluator cannot compute. Default is `Number(0.)`, the result libxlsxwriter stores.
+    pub fallback: FormulaValue,
+}
+
+impl Default for EvaluatorOptions {
+    fn default() -> Self {
+        EvaluatorOptions {
+            fallback: FormulaValue::Number(0.),
+        }
+    }
+}
+
+/// Values written to the worksheets of a workbook.
+pub(crate) struct Evaluator {
+    options: EvaluatorOptions,
+    values: HashMap<(String, WorksheetRow, WorksheetCol), FormulaValue>,
+}
+
+impl Evaluator {
+    pub(crate) fn new(options: EvaluatorOptions) -> Self {
+        Evaluator {
+            options,
+            values: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn set_value(
+        &mut self,
+        sheet_name: &str,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        value: FormulaValue,
+    ) {
+        // A blank cell is kept as `Empty` to tell it from a cell which is not written yet
+        self.values
+            .insert((sheet_name.to_lowercase(), row, col), value);
+    }
+
+    /// Evaluate `formula` on the sheet `sheet_name`, or returns the fallback value if the formula is not supported.
+    pub(crate) fn evaluate(&self, sheet_name: &str, formula: &str) -> FormulaValue {
+        let sheet_name = sheet_name.to_lowercase();
+        let formula = formula.strip_prefix('=').unwrap_or(formula);
+        tokenize(formula)
+            .and_then(|tokens| {
+                Parser {
+                    tokens,
+                    pos: 0,
+                    depth: 0,
+                }
+                .parse()
+            })
+            .and_then(|expr| self.eval(&sheet_name, &expr))
+            .map(|value| match value {
+                // A reference to an empty cell results in 0
+                FormulaValue::Empty => FormulaValue::Number(0.),
+                value => value,
+            })
+            .unwrap_or_else(|_| self.options.fallback.clone())
+    }
+
+    /// Value of the cell, or `None` if the cell is not written yet.
+    fn cell_value(
+        &self,
+        sheet_name: &str,
+        row: WorksheetRow,
+        col: WorksheetCol,
+    ) -> Option<FormulaValue> {
+        self.values
+            .get(&(sheet_name.to_string(), row, col))
+            .cloned()
+    }
+
+    /// Values of non-empty cells in the range, in row major order.
+    fn range_values(&self, sheet_name: &str, range: &Range) -> Vec<FormulaValue> {
+        let cells = u64::from(range.last_row - range.first_row + 1)
+            * u64::from(range.last_col - range.first_col + 1);
+        if cells <= self.values.len() as u64 {
+            let mut values = Vec::new();
+            for row in range.first_row..=range.last_row {
+                for col in range.first_col..=range.last_col {
+                    match self.values.get(&(sheet_name.to_string(), row, col)) {
+                        None | Some(FormulaValue::Empty) => (),
+                        Some(value) => values.push(value.clone()),
+                    }
+                }
+            }
+            values
+        } else {
+            let mut values: Vec<_> = self
+                .values
+                .iter()
+                .filter(|((sheet, row, col), value)| {
+                    **value != FormulaValue::Empty
+                        && sheet == sheet_name
+                        && (range.first_row..=range.last_row).contains(row)
+                        && (range.first_col..=range.last_col).contains(col)
+                })
+                .map(|((_, row, col), value)| (*row, *col, value.clone()))
+                .collect();
+            values.sort_by_key(|(row, col, _)| (*row, *col));
+            values.into_iter().map(|(_, _, value)| value).collect()
+        }
+    }
+
+    fn eval(&self, sheet_name: &str, expr: &Expr) -> Result<FormulaValue, Unsupported> {
+        match expr {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Reference(sheet, range) => {
+                if range.first_row != range.last_row || range.first_col != range.last_col {
+                    // Implicit intersection of a range is not supported
+                    return Err(Unsupported);
+                }
+                // A cell which is not written yet may be written after the formula
+                self.cell_value(
+                    sheet.as_deref().unwrap_or(sheet_name),
+                    range.first_row,
+                    range.first_col,
+                )
+                .ok_or(Unsupported)
+            }
+            Expr::Negate(expr) => {
+                let value = self.eval(sheet_name, expr)?;
+                Ok(number_result(to_number(&value).map(|x| -x)))
+            }
+            Expr::Percent(expr) => {
+                let value = self.eval(sheet_name, expr)?;
+                Ok(number_result(to_number(&value).map(|x| x / 100.)))
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(sheet_name, left)?;
+                let right = self.eval(sheet_name, right)?;
+                Ok(binary(*op, &left, &right))
+            }
+            Expr::Call(name, args) => self.call(sheet_name, name, args),
+        }
+    }
+
+    /// Numbers in the arguments of an aggregate function. Numbers in references are used, and text and booleans in references are ignored.
+    /// Other arguments are converted to numbers.
+    fn numbers(
+        &self,
+        sheet_name: &str,
+        args: &[Expr],
+    ) -> Result<Result<Vec<f64>, CellError>, Unsupported> {
+        let mut numbers = Vec::new();
+        for arg in args {
+            match arg {
+                Expr::Reference(sheet, range) => {
+                    for value in self.range_values(sheet.as_deref().unwrap_or(sheet_name), range) {
+                        match value {
+                            FormulaValue::Number(x) => numbers.push(x),
+                            FormulaValue::Error(e) => return Ok(Err(e)),
+                            _ => (),
+                        }
+                    }
+                }
+                arg => match to_number(&self.eval(sheet_name, arg)?) {
+                    Ok(x) => numbers.push(x),
+                    Err(e) => return Ok(Err(e)),
+                },
+            }
+        }
+        Ok(Ok(numbers))
+    }
+
+    fn call(
+        &self,
+        sheet_name: &str,
+        name: &str,
+        args: &[Expr],
+    ) -> Result<FormulaValue, Unsupported> {
+        let name = name.to_ascii_uppercase();
+        let name = name.strip_prefix("_XLFN.").unwrap_or(&name);
+        let aggregate = |f: fn(&[f64]) -> FormulaValue| -> Result<FormulaValue, Unsupported> {
+            Ok(match self.numbers(sheet_name, args)? {
+                Ok(numbers) => f(&numbers),
+                Err(e) => FormulaValue::Error(e),
+            })
+        };
+        match (name, args.len()) {
+            ("SUM", 1..) => aggregate(|x| FormulaValue::Number(x.iter().sum())),
+            ("AVERAGE", 1..) => aggregate(|x| {
+                if x.is_empty() {
+                    FormulaValue::Error(CellError::Div0)
+                } else {
+                    FormulaValue::Number(x.iter().sum::<f64>() / x.len() as f64)
+                }
+            }),
+            ("MIN", 1..) => aggregate(|x| {
+                FormulaValue::Number(x.iter().copied().reduce(f64::min).unwrap_or(0.))
+            }),
+            ("MAX", 1..) => aggregate(|x| {
+                FormulaValue::Number(x.iter().copied().reduce(f64::max).unwrap_or(0.))
+            }),
+            ("COUNT", 1..) => {
+                let mut count = 0;
+                for arg in args {
+                    match arg {
+                        Expr::Reference(sheet, range) => {
+                            count += self
+                                .range_values(sheet.as_deref().unwrap_or(sheet_name), range)
+                                .iter()
+                                .filter(|x| matches!(x, FormulaValue::Number(_)))
+                                .count();
+                        }
+                        arg => {
+                            if to_number(&self.eval(sheet_name, arg)?).is_ok() {
+                                count += 1;
+                            }
+                        }
+                    }
+                }
+                Ok(FormulaValue::Number(count as f64))
+            }
+            ("IF", 2..=3) => {
+                let condition = match to_boolean(&self.eval(sheet_name, &args[0])?) {
+                    Ok(condition) => condition,
+                    Err(e) => return Ok(FormulaValue::Error(e)),
+                };
+                if condition {
+                    self.eval(sheet_name, &args[1])
+                } else if let Some(arg) = args.get(2) {
+                    self.eval(sheet_name, arg)
+                } else {
+                    Ok(FormulaValue::Boolean(false))
+                }
+            }
+            ("ROUND", 2) => {
+                let number = to_number(&self.eval(sheet_name, &args[0])?);
+                let digits = to_number(&self.eval(sheet_name, &args[1])?);
+                Ok(number_result(number.and_then(|number| {
+                    Ok(round(number, digits?.trunc() as i32))
+                })))
+            }
+            ("CONCATENATE", 1..) | ("CONCAT", 1..) => {
+                let mut text = String::new();
+                for arg in args {
+                    let values = match arg {
+                        Expr::Reference(sheet, range) if name == "CONCAT" => {
+                            self.range_values(sheet.as_deref().unwrap_or(sheet_name), range)
+                        }
+                        arg => vec![self.eval(sheet_name, arg)?],
+                    };
+                    for value in values {
+                        match to_text(&value) {
+                            Ok(x) => text.push_str(&x),
+                            Err(e) => return Ok(FormulaValue::Error(e)),
+                        }
+                    }
+                }
+                Ok(FormulaValue::String(text))
+            }
+            _ => Err(Unsupported),
+        }
+    }
+}
+
+/// The formula uses something the evaluator doesn't support.
+struct Unsupported;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Concat,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Range {
+    first_row: WorksheetRow,
+    first_col: WorksheetCol,
+    last_row: WorksheetRow,
+    last_col: WorksheetCol,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Value(FormulaValue),
+    /// Reference with the lower case sheet name.
+    Reference(Option<String>, Range),
+    Negate(Box<Expr>),
+    Percent(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Error(CellError),
+    /// Name, cell reference or function name.
+    Name(String),
+    /// Quoted sheet name.
+    SheetName(String),
+    Operator(&'static str),
+}
+
+const OPERATORS: &[&str] = &[
+    "<>", "<=", ">=", "+", "-", "*", "/", "^", "&", "=", "<", ">", "%", "(", ")", ",", ":", "!",
+];
+
+const ERRORS: &[CellError] = &[
+    CellError::Null,
+    CellError::Div0,
+    CellError::Value,
+    CellError::Ref,
+    CellError::Name,
+    CellError::Num,
+    CellError::NA,
+];
+
+fn tokenize(formula: &str) -> Result<Vec<Token>, Unsupported> {
+    let mut tokens = Vec::new();
+    let mut rest = formula;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c == '"' || c == '\'' {
+            // Quotes in a string are escaped by doubling them
+            let mut text = String::new();
+            let mut chars = rest.char_indices().skip(1).peekable();
+            let mut end = None;
+            while let Some((i, x)) = chars.next() {
+                if x == c {
+                    if chars.peek().map(|&(_, y)| y) == Some(c) {
+                        chars.next();
+                    } else {
+                        end = Some(i + 1);
+                        break;
+                    }
+                }
+                text.push(x);
+            }
+            rest = &rest[end.ok_or(Unsupported)?..];
+            tokens.push(if c == '"' {
+                Token::String(text)
+            } else {
+                Token::SheetName(text)
+            });
+        } else if c == '#' {
+            let error = ERRORS
+                .iter()
+                .find(|x| rest.to_ascii_uppercase().starts_with(x.as_str()))
+                .ok_or(Unsupported)?;
+            rest = &rest[error.as_str().len()..];
+            tokens.push(Token::Error(*error));
+        } else if c.is_ascii_digit()
+            || (c == '.' && rest[1..].starts_with(|x: char| x.is_ascii_digit()))
+        {
+            let mut end = rest
+                .find(|x: char| !x.is_ascii_digit() && x != '.')
+                .unwrap_or(rest.len());
+            if rest[end..].starts_with(['e', 'E']) {
+                let exponent = &rest[end + 1..];
+                let sign = usize::from(exponent.starts_with(['+', '-']));
+                let digits = exponent[sign..]
+                    .find(|x: char| !x.is_ascii_digit())
+                    .unwrap_or(exponent.len() - sign);
+                if digits > 0 {
+                    end += 1 + sign + digits;
+                }
+            }
+            let number = rest[..end].parse().map_err(|_| Unsupported)?;
+            rest = &rest[end..];
+            tokens.push(Token::Number(number));
+        } else if c.is_alphanumeric() || c == '_' || c == '$' || c == '\\' {
+            let end = rest
+                .find(|x: char| {
+                    !(x.is_alphanumeric() || x == '_' || x == '$' || x == '.' || x == '\\')
+                })
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let operator = OPERATORS
+                .iter()
+                .find(|x| rest.starts_with(*x))
+                .ok_or(Unsupported)?;
+            rest = &rest[operator.len()..];
+            tokens.push(Token::Operator(operator));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse a column name such as `$AB`.
+fn parse_col(name: &str) -> Option<WorksheetCol> {
+    utility::name_to_col(name.strip_prefix('$').unwrap_or(name)).ok()
+}
+
+/// Parse a cell reference such as `$A$1`.
+fn parse_cell(name: &str) -> Option<(WorksheetRow, WorksheetCol)> {
+    name.parse::<CellRef>()
+        .ok()
+        .map(|cell| (cell.row, cell.col))
+}
+
+/// Maximum nesting of parentheses, function calls and signs in a formula. Excel allows 64 nested levels of functions.
+/// A formula nested deeper is not supported, rather than overflowing the stack.
+const MAX_NESTING_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn parse(mut self) -> Result<Expr, Unsupported> {
+        let expr = self.comparison()?;
+        if self.pos == self.tokens.len() {
+            Ok(expr)
+        } else {
+            Err(Unsupported)
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_operator(&self, operators: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Operator(x)) if operators.contains(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, operator: &str) -> Result<(), Unsupported> {
+        if self.peek_operator(&[operator]).is_some() {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(Unsupported)
+        }
+    }
+
+    /// Parse with `parse` one nesting level deeper.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Expr, Unsupported>,
+    ) -> Result<Expr, Unsupported> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(Unsupported);
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[(&str, BinaryOp)],
+        operand: fn(&mut Self) -> Result<Expr, Unsupported>,
+    ) -> Result<Expr, Unsupported> {
+        let mut left = operand(self)?;
+        while let Some(operator) =
+            self.peek_operator(&operators.iter().map(|x| x.0).collect::<Vec<_>>())
+        {
+            self.pos += 1;
+            let op = operators.iter().find(|x| x.0 == operator).unwrap().1;
+            left = Expr::Binary(op, Box::new(left), Box::new(operand(self)?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, Unsupported> {
+        self.binary(
+            &[
+                ("=", BinaryOp::Equal),
+                ("<>", BinaryOp::NotEqual),
+                ("<", BinaryOp::Less),
+                ("<=", BinaryOp::LessEqual),
+                (">", BinaryOp::Greater),
+                (">=", BinaryOp::GreaterEqual),
+            ],
+            Self::concat,
+        )
+    }
+
+    fn concat(&mut self) -> Result<Expr, Unsupported> {
+        self.binary(&[("&", BinaryOp::Concat)], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr, Unsupported> {
+        self.binary(
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+            Self::multiplicative,
+        )
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, Unsupported> {
+        self.binary(
+            &[("*", BinaryOp::Multiply), ("/", BinaryOp::Divide)],
+            Self::power,
+        )
+    }
+
+    fn power(&mut self) -> Result<Expr, Unsupported> {
+        self.binary(&[("^", BinaryOp::Power)], Self::unary)
+    }
+
+    /// Negation binds tighter than `^` in Excel, so `-2^2` is 4.
+    fn unary(&mut self) -> Result<Expr, Unsupported> {
+        match self.peek_operator(&["-", "+"]) {
+            Some(operator) => {
+                self.pos += 1;
+                let expr = self.nested(Self::unary)?;
+                Ok(if operator == "-" {
+                    Expr::Negate(Box::new(expr))
+                } else {
+                    expr
+                })
+            }
+            None => self.percent(),
+        }
+    }
+
+    fn percent(&mut self) -> Result<Expr, Unsupported> {
+        let mut expr = self.primary()?;
+        while self.peek_operator(&["%"]).is_some() {
+            self.pos += 1;
+            expr = Expr::Percent(Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, Unsupported> {
+        let token = self.peek().cloned().ok_or(Unsupported)?;
+        self.pos += 1;
+        match token {
+            Token::Number(x) => Ok(Expr::Value(FormulaValue::Number(x))),
+            Token::String(x) => Ok(Expr::Value(FormulaValue::String(x))),
+            Token::Error(x) => Ok(Expr::Value(FormulaValue::Error(x))),
+            Token::Operator("(") => {
+                let expr = self.nested(Self::comparison)?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::SheetName(sheet) => {
+                self.expect("!")?;
+                self.reference(Some(sheet.to_lowercase()))
+            }
+            Token::Name(name) => {
+                if self.peek_operator(&["("]).is_some() {
+                    self.pos += 1;
+                    self.call(name)
+                } else if self.peek_operator(&["!"]).is_some() {
+                    self.pos += 1;
+                    self.reference(Some(name.to_lowercase()))
+                } else if name.eq_ignore_ascii_case("TRUE") {
+                    Ok(Expr::Value(FormulaValue::Boolean(true)))
+                } else if name.eq_ignore_ascii_case("FALSE") {
+                    Ok(Expr::Value(FormulaValue::Boolean(false)))
+                } else {
+                    self.pos -= 1;
+                    self.reference(None)
+                }
+            }
+            Token::Operator(_) => Err(Unsupported),
+        }
+    }
+
+    fn call(&mut self, name: String) -> Result<Expr, Unsupported> {
+        let mut args = Vec::new();
+        if self.peek_operator(&[")"]).is_none() {
+            loop {
+                args.push(self.nested(Self::comparison)?);
+                if self.peek_operator(&[","]).is_some() {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect(")")?;
+        Ok(Expr::Call(name, args))
+    }
+
+    /// Parse a cell reference, a range such as `A1:B3`, or a column range such as `A:C`.
+    fn reference(&mut self, sheet: Option<String>) -> Result<Expr, Unsupported> {
+        let first = match self.peek() {
+            Some(Token::Name(x)) => x.clone(),
+            _ => return Err(Unsupported),
+        };
+        self.pos += 1;
+        let last = if self.peek_operator(&[":"]).is_some() {
+            self.pos += 1;
+            match self.peek() {
+                Some(Token::Name(x)) => {
+                    let last = x.clone();
+                    self.pos += 1;
+                    Some(last)
+                }
+                // A number token for a row range such as 1:3 is not supported
+                _ => return Err(Unsupported),
+            }
+        } else {
+            None
+        };
+        let range = match (parse_cell(&first), &last) {
+            (Some((first_row, first_col)), None) => Range {
+                first_row,
+                first_col,
+                last_row: first_row,
+                last_col: first_col,
+            },
+            (Some((first_row, first_col)), Some(last)) => {
+                let (last_row, last_col) = parse_cell(last).ok_or(Unsupported)?;
+                Range {
+                    first_row: first_row.min(last_row),
+                    first_col: first_col.min(last_col),
+                    last_row: first_row.max(last_row),
+                    last_col: first_col.max(last_col),
+                }
+            }
+            (None, Some(last)) => {
+                let first_col = parse_col(&first).ok_or(Unsupported)?;
+                let last_col = parse_col(last).ok_or(Unsupported)?;
+                Range {
+                    first_row: 0,
+                    first_col: first_col.min(last_col),
+                    last_row: limits::MAX_ROWS - 1,
+                    last_col: first_col.max(last_col),
+                }
+            }
+            // Defined names are not supported
+            (None, None) => return Err(Unsupported),
+        };
+        Ok(Expr::Reference(sheet, range))
+    }
+}
+
+fn number_result(number: Result<f64, CellError>) -> FormulaValue {
+    match number {
+        Ok(x) if x.is_finite() => FormulaValue::Number(x),
+        Ok(_) => FormulaValue::Error(CellError::Num),
+        Err(e) => FormulaValue::Error(e),
+    }
+}
+
+/// Round `number` to `digits` decimal places, half away from zero, as Excel does. The number is first taken to
+/// 15 significant digits, so `ROUND(1.005, 2)` is 1.01 although the binary value of 1.005 is slightly less.
+fn round(number: f64, digits: i32) -> f64 {
+    if number == 0. || !number.is_finite() {
+        return number;
+    }
+    let text = format!("{:.14e}", number.abs());
+    let (mantissa, exponent) = text.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    // The number is 0.ddd... * 10^(exponent + 1) with 15 digits ddd...
+    let significand: u64 = mantissa.replace('.', "").parse().unwrap();
+    let kept = exponent.saturating_add(1).saturating_add(digits);
+    if kept >= 15 {
+        return text.parse::<f64>().unwrap().copysign(number);
+    }
+    let rounded = if kept < 0 {
+        0
+    } else {
+        let divisor = 10u64.pow((15 - kept) as u32);
+        let quotient = significand / divisor;
+        if (significand % divisor) * 2 >= divisor {
+            quotient + 1
+        } else {
+            quotient
+        }
+    };
+    format!("{}e{}", rounded, -digits)
+        .parse::<f64>()
+        .unwrap()
+        .copysign(number)
+}
+
+fn to_number(value: &FormulaValue) -> Result<f64, CellError> {
+    match value {
+        FormulaValue::Number(x) => Ok(*x),
+        FormulaValue::String(x) => x.trim().parse().map_err(|_| CellError::Value),
+        FormulaValue::Boolean(x) => Ok(if *x { 1. } else { 0. }),
+        FormulaValue::Error(e) => Err(*e),
+        FormulaValue::Empty => Ok(0.),
+    }
+}
+
+fn to_boolean(value: &FormulaValue) -> Result<bool, CellError> {
+    match value {
+        FormulaValue::Number(x) => Ok(*x != 0.),
+        FormulaValue::String(x) if x.eq_ignore_ascii_case("TRUE") => Ok(true),
+        FormulaValue::String(x) if x.eq_ignore_ascii_case("FALSE") => Ok(false),
+        FormulaValue::String(_) => Err(CellError::Value),
+        FormulaValue::Boolean(x) => Ok(*x),
+        FormulaValue::Error(e) => Err(*e),
+        FormulaValue::Empty => Ok(false),
+    }
+}
+
+fn to_text(value: &FormulaValue) -> Result<String, CellError> {
+    match value {
+        FormulaValue::Number(x) => Ok(number_to_text(*x)),
+        FormulaValue::String(x) => Ok(x.clone()),
+        FormulaValue::Boolean(x) => Ok(if *x { "TRUE" } else { "FALSE" }.to_string()),
+        FormulaValue::Error(e) => Err(*e),
+        FormulaValue::Empty => Ok(String::new()),
+    }
+}
+
+/// Format a number with up to 15 significant digits as Excel does in the general format.
+fn number_to_text(number: f64) -> String {
+    if number == 0. {
+        return "0".to_string();
+    }
+    let magnitude = number.abs().log10().floor() as i32;
+    if !(-10..21).contains(&magnitude) {
+        let text = format!("{:.14E}", number);
+        let (mantissa, exponent) = text.split_once('E').unwrap();
+        let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+        let exponent: i32 = exponent.parse().unwrap();
+        return format!(
+            "{}E{}{:02}",
+            mantissa,
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        );
+    }
+    let decimals = (14 - magnitude).max(0) as usize;
+    let text = format!("{:.*}", decimals, number);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// Order of values of different types in comparisons: numbers, then text, then booleans.
+fn compare(left: &FormulaValue, right: &FormulaValue) -> Ordering {
+    fn normalize(value: &FormulaValue, other: &FormulaValue) -> FormulaValue {
+        match (value, other) {
+            (FormulaValue::Empty, FormulaValue::String(_)) => FormulaValue::String(String::new()),
+            (FormulaValue::Empty, FormulaValue::Boolean(_)) => FormulaValue::Boolean(false),
+            (FormulaValue::Empty, _) => FormulaValue::Number(0.),
+            (value, _) => value.clone(),
+        }
+    }
+    match (normalize(left, right), normalize(right, left)) {
+        (FormulaValue::Number(x), FormulaValue::Number(y)) => {
+            x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+        }
+        (FormulaValue::String(x), FormulaValue::String(y)) => {
+            x.to_lowercase().cmp(&y.to_lowercase())
+        }
+        (FormulaValue::Boolean(x), FormulaValue::Boolean(y)) => x.cmp(&y),
+        (FormulaValue::Number(_), _) | (FormulaValue::String(_), FormulaValue::Boolean(_)) => {
+            Ordering::Less
+        }
+        _ => Ordering::Greater,
+    }
+}
+
+fn binary(op: BinaryOp, left: &FormulaValue, right: &FormulaValue) -> FormulaValue {
+    // Errors propagate from left to right
+    for value in [left, right] {
+        if let FormulaValue::Error(e) = value {
+            return FormulaValue::Error(*e);
+        }
+    }
+    let numbers = || Ok((to_number(left)?, to_number(right)?));
+    match op {
+        BinaryOp::Add => number_result(numbers().map(|(x, y)| x + y)),
+        BinaryOp::Subtract => number_result(numbers().map(|(x, y)| x - y)),
+        BinaryOp::Multiply => number_result(numbers().map(|(x, y)| x * y)),
+        BinaryOp::Divide => number_result(numbers().and_then(|(x, y)| {
+            if y == 0. {
+                Err(CellError::Div0)
+            } else {
+                Ok(x / y)
+            }
+        })),
+        BinaryOp::Power => number_result(numbers().and_then(|(x, y)| {
+            if x == 0. && y == 0. {
+                Err(CellError::Num)
+            } else if x == 0. && y < 0. {
+                Err(CellError::Div0)
+            } else {
+                Ok(x.powf(y))
+            }
+        })),
+        BinaryOp::Concat => match (to_text(left), to_text(right)) {
+            (Ok(x), Ok(y)) => FormulaValue::String(x + &y),
+            (Err(e), _) | (_, Err(e)) => FormulaValue::Error(e),
+        },
+        BinaryOp::Equal => FormulaValue::Boolean(compare(left, right) == Ordering::Equal),
+        BinaryOp::NotEqual => FormulaValue::Boolean(compare(left, right) != Ordering::Equal),
+        BinaryOp::Less => FormulaValue::Boolean(compare(left, right) == Ordering::Less),
+        BinaryOp::LessEqual => FormulaValue::Boolean(compare(left, right) != Ordering::Greater),
+        BinaryOp::Greater => FormulaValue::Boolean(compare(left, right) == Ordering::Greater),
+        BinaryOp::GreaterEqual => FormulaValue::Boolean(compare(left, right) != Ordering::Less),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        let mut evaluator = Evaluator::new(EvaluatorOptions::default());
+        evaluator.set_value("Sheet1", 0, 0, FormulaValue::Number(1.5));
+        evaluator.set_value("Sheet1", 1, 0, FormulaValue::Number(2.5));
+        evaluator.set_value("Sheet1", 2, 0, FormulaValue::String("x".to_string()));
+        evaluator.set_value("Sheet1", 0, 1, FormulaValue::Boolean(true));
+        evaluator.set_value("My Sheet", 4, 2, FormulaValue::Number(10.));
+        evaluator.set_value("Sheet1", 3, 0, FormulaValue::Empty);
+
+        for (formula, expected) in [
+            ("=1+2*3", FormulaValue::Number(7.)),
+            ("=(1+2)*3", FormulaValue::Number(9.)),
+            ("=-2^2", FormulaValue::Number(4.)),
+            ("=2^3^2", FormulaValue::Number(64.)),
+            ("=50%", FormulaValue::Number(0.5)),
+            ("=1.5E+3", FormulaValue::Number(1500.)),
+            ("=A1+A2", FormulaValue::Number(4.)),
+            ("=$A$1*B1", FormulaValue::Number(1.5)),
+            ("=A1+A3", FormulaValue::Error(CellError::Value)),
+            ("=1/0", FormulaValue::Error(CellError::Div0)),
+            ("=#N/A+1", FormulaValue::Error(CellError::NA)),
+            ("=A4", FormulaValue::Number(0.)),
+            ("=A4+1", FormulaValue::Number(1.)),
+            ("=SUM(A1:A4, 1)", FormulaValue::Number(5.)),
+            ("=SUM(A:B)", FormulaValue::Number(4.)),
+            ("=AVERAGE(A1:A3)", FormulaValue::Number(2.)),
+            ("=AVERAGE(Z1:Z3)", FormulaValue::Error(CellError::Div0)),
+            ("=MIN(A1:A3)", FormulaValue::Number(1.5)),
+            ("=MAX(A1:A3, 3)", FormulaValue::Number(3.)),
+            ("=COUNT(A1:B4)", FormulaValue::Number(2.)),
+            ("=SUM('My Sheet'!C5, sheet1!A1)", FormulaValue::Number(11.5)),
+            (
+                "=IF(A1>1, \"big\", \"small\")",
+                FormulaValue::String("big".to_string()),
+            ),
+            ("=IF(A1>2, \"big\")", FormulaValue::Boolean(false)),
+            ("=ROUND(2.345, 2)", FormulaValue::Number(2.35)),
+            ("=ROUND(1234, -2)", FormulaValue::Number(1200.)),
+            ("=ROUND(1.005, 2)", FormulaValue::Number(1.01)),
+            ("=ROUND(-2.5, 0)", FormulaValue::Number(-3.)),
+            ("=ROUND(0.285, 2)", FormulaValue::Number(0.29)),
+            ("=ROUND(1.5, -1)", FormulaValue::Number(0.)),
+            ("=ROUND(0.1, 20)", FormulaValue::Number(0.1)),
+            (
+                "=\"a\" & A1 & B1",
+                FormulaValue::String("a1.5TRUE".to_string()),
+            ),
+            (
+                "=\"say \"\"hi\"\"\"",
+                FormulaValue::String("say \"hi\"".to_string()),
+            ),
+            (
+                "=CONCATENATE(A3, 0.1+0.2)",
+                FormulaValue::String("x0.3".to_string()),
+            ),
+            (
+                "=_xlfn.CONCAT(A1:A4)",
+                FormulaValue::String("1.52.5x".to_string()),
+            ),
+            ("=A3=\"X\"", FormulaValue::Boolean(true)),
+            ("=1<\"a\"", FormulaValue::Boolean(true)),
+            ("=A4=\"\"", FormulaValue::Boolean(true)),
+            ("=A1<>A2", FormulaValue::Boolean(true)),
+            ("=VLOOKUP(1, A1:B3, 2)", FormulaValue::Number(0.)),
+            ("=A1:A3", FormulaValue::Number(0.)),
+            ("=SUM(A1", FormulaValue::Number(0.)),
+        ] {
+            assert_eq!(
+                evaluator.evaluate("Sheet1", formula),
+                expected,
+                "{}",
+                formula
+            );
+        }
+    }
+
+    #[test]
+    fn test_unwritten_cell() {
+        let mut evaluator = Evaluator::new(EvaluatorOptions {
+            fallback: FormulaValue::Error(CellError::NA),
+        });
+        evaluator.set_value("Sheet1", 0, 0, FormulaValue::Number(1.5));
+        evaluator.set_value("Sheet1", 1, 0, FormulaValue::Empty);
+        for (formula, expected) in [
+            ("=Z99", FormulaValue::Error(CellError::NA)),
+            ("=Z99+1", FormulaValue::Error(CellError::NA)),
+            ("=A1+Sheet2!A1", FormulaValue::Error(CellError::NA)),
+            ("=A2+1", FormulaValue::Number(1.)),
+            ("=SUM(A1:A9)", FormulaValue::Number(1.5)),
+        ] {
+            assert_eq!(
+                evaluator.evaluate("Sheet1", formula),
+                expected,
+                "{}",
+                formula
+            );
+        }
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let evaluator = Evaluator::new(EvaluatorOptions {
+            fallback: FormulaValue::Error(CellError::NA),
+        });
+        let nested = |depth: usize| format!("={}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(
+            evaluator.evaluate("Sheet1", &nested(MAX_NESTING_DEPTH)),
+            FormulaValue::Number(1.)
+        );
+        for formula in [
+            nested(MAX_NESTING_DEPTH + 1),
+            nested(100_000),
+            format!("={}1", "-".repeat(100_000)),
+            format!("={}1{}", "SUM(".repeat(100_000), ")".repeat(100_000)),
+        ] {
+            assert_eq!(
+                evaluator.evaluate("Sheet1", &formula),
+                FormulaValue::Error(CellError::NA)
+            );
+        }
+    }
+
+    #[test]
+    fn test_number_to_text() {
+        for (number, expected) in [
+            (0., "0"),
+            (1., "1"),
+            (-12.5, "-12.5"),
+            (0.1 + 0.2, "0.3"),
+            (1. / 3., "0.333333333333333"),
+            (123456789012345., "123456789012345"),
+            (1e21, "1E+21"),
+            (1.5e-11, "1.5E-11"),
+        ] {
+            assert_eq!(number_to_text(number), expected);
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Build formulas without assembling strings by hand.
+//!
+//! A [`Formula`] is built from literals, cell and range references, table references and function calls, and combined with
+//! operators. String literals are escaped, sheet names are quoted as required and parentheses are added where operator precedence requires them.
+//! [`Formula::to_formula`] returns the formula with a leading `=`, which can be passed to
+//! [`Worksheet::write_formula`](crate::Worksheet::write_formula), [`ConditionalFormat::formula`](crate::worksheet::conditional_format::ConditionalFormat::formula),
+//! [`DataValidationType::CustomFormula`](crate::worksheet::validation::DataValidationType::CustomFormula) or [`Chart::add_series`](crate::Chart::add_series).
+//! ```rust
+//! use xlsxwriter::formula::{Formula, TableSpecifier};
+//! use xlsxwriter::utility::CellRange;
+//! # use xlsxwriter::prelude::*;
+//! # fn main() -> Result<(), XlsxError> {
+//! let total = Formula::call(
+//!     "SUM",
+//!     [Formula::range(&CellRange::new(1, 1, 9, 1).with_sheet_name("Q3 Sales"))],
+//! );
+//! assert_eq!(total.to_formula(), "=SUM('Q3 Sales'!B2:B10)");
+//!
+//! let label = Formula::string("Total \"net\": ") & total;
+//! assert_eq!(label.to_string(), "\"Total \"\"net\"\": \"&SUM('Q3 Sales'!B2:B10)");
+//!
+//! let price = Formula::table("Table1", Some(TableSpecifier::ThisRow), Some("Price"));
+//! let amount = (price * Formula::number(1.1)).greater(Formula::cell_at(0, 0));
+//! assert_eq!(amount.to_string(), "Table1[[#This Row],[Price]]*1.1>A1");
+//! # let workbook = Workbook::new("test-formula.xlsx")?;
+//! # let mut worksheet = workbook.add_worksheet(None)?;
+//! # worksheet.write_formula(0, 1, &label.to_formula(), None)?;
+//! # workbook.close()
+//! # }
+//! ```
+//! Excel limits a formula to 8192 characters. Longer formulas are not checked by this module.
+
+use std::fmt;
+use std::ops::{Add, BitAnd, Div, Mul, Neg, Sub};
+
+use crate::error::XlsxErrorSource;
+use crate::limits;
+use crate::utility::{CellRange, CellRef};
+use crate::worksheet::CellError;
+use crate::{WorksheetCol, WorksheetRow, XlsxError};
+
+/// Maximum number of characters of a string literal in a formula.
+const MAX_STRING_LITERAL_LENGTH: usize = 255;
+
+// Operator precedence, from the loosest to the tightest
+const COMPARISON: u8 = 1;
+const CONCAT: u8 = 2;
+const ADDITIVE: u8 = 3;
+const MULTIPLICATIVE: u8 = 4;
+const POWER: u8 = 5;
+const UNARY: u8 = 6;
+const ATOM: u8 = 7;
+
+/// Part of a table selected by a structured reference. See [`Formula::table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TableSpecifier {
+    /// `[#All]`: the headers, the data and the totals.
+    All,
+    /// `[#Data]`: the data rows.
+    Data,
+    /// `[#Headers]`: the header row.
+    Headers,
+    /// `[#Totals]`: the total row.
+    Totals,
+    /// `[#This Row]`: the data in the same row as the formula.
+    ThisRow,
+}
+
+impl TableSpecifier {
+    fn as_str(self) -> &'static str {
+        match self {
+            TableSpecifier::All => "[#All]",
+            TableSpecifier::Data => "[#Data]",
+            TableSpecifier::Headers => "[#Headers]",
+            TableSpecifier::Totals => "[#Totals]",
+            TableSpecifier::ThisRow => "[#This Row]",
+        }
+    }
+}
+
+/// A formula or a part of a formula. See the [module documentation](self).
+///
+/// [`Display`](fmt::Display) formats the formula without the leading `=`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Formula {
+    text: String,
+    precedence: u8,
+}
+
+impl Formula {
+    fn new(text: String, precedence: u8) -> Self {
+        Formula { text, precedence }
+    }
+
+    /// Use `text` as a part of a formula as it is, such as a defined name `SalesTotal`.
+    pub fn raw(text: &str) -> Self {
+        // The text may contain any operator
+        Formula::new(text.to_string(), COMPARISON)
+    }
+
+    /// A defined name or a `LAMBDA` parameter, such as `SalesTotal`.
+    pub fn name(name: &str) -> Self {
+        Formula::new(name.to_string(), ATOM)
+    }
+
+    /// A number literal. NaN and infinite numbers are written as `#NUM!`.
+    pub fn number(number: f64) -> Self {
+        if !number.is_finite() {
+            Formula::error(CellError::Num)
+        } else if number < 0. {
+            Formula::new(number.to_string(), UNARY)
+        } else {
+            Formula::new(number.to_string(), ATOM)
+        }
+    }
+
+    /// A string literal. Double quotes are escaped, and a string longer than the 255 characters Excel allows in a literal
+    /// is split into literals joined with `&`.
+    pub fn string(text: &str) -> Self {
+        let literal = |text: &str| format!("\"{}\"", text.replace('"', "\"\""));
+        let chunks = crate::limits::split_chunks(text, MAX_STRING_LITERAL_LENGTH);
+        if chunks.len() == 1 {
+            Formula::new(literal(text), ATOM)
+        } else {
+            let literals: Vec<_> = chunks.into_iter().map(literal).collect();
+            Formula::new(literals.join("&"), CONCAT)
+        }
+    }
+
+    /// A boolean literal, `TRUE` or `FALSE`.
+    pub fn boolean(value: bool) -> Self {
+        Formula::new(if value { "TRUE" } else { "FALSE" }.to_string(), ATOM)
+    }
+
+    /// An error literal, such as `#N/A`.
+    pub fn error(error: CellError) -> Self {
+        Formula::new(error.as_str().to_string(), ATOM)
+    }
+
+    /// A reference to a cell, such as `A1` or `$A$1`.
+    pub fn cell(cell: CellRef) -> Self {
+        Formula::new(cell.to_string(), ATOM)
+    }
+
+    /// A relative reference to the cell at zero indexed `row` and `col`.
+    pub fn cell_at(row: WorksheetRow, col: WorksheetCol) -> Self {
+        Formula::cell(CellRef::new(row, col))
+    }
+
+    /// A reference to a range, such as `A1:B3` or `'My Sheet'!$A$1`. The sheet name is quoted as required.
+    pub fn range(range: &CellRange) -> Self {
+        Formula::new(range.to_string(), ATOM)
+    }
+
+    /// A relative reference to the range from `first_row`, `first_col` to `last_row`, `last_col` on the same sheet.
+    pub fn range_at(
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    ) -> Self {
+        Formula::range(&CellRange::new(first_row, first_col, last_row, last_col))
+    }
+
+    /// A structured reference to a table, such as `Table1[Price]`, `Table1[#Totals]` or `Table1[[#This Row],[Price]]`.
+    /// The characters `[`, `]`, `#` and `'` in the column name are escaped.
+    pub fn table(table: &str, specifier: Option<TableSpecifier>, column: Option<&str>) -> Self {
+        let column = column.map(|column| {
+            let mut escaped = String::with_capacity(column.len());
+            for c in column.chars() {
+                if matches!(c, '[' | ']' | '#' | '\'') {
+                    escaped.push('\'');
+                }
+                escaped.push(c);
+            }
+            escaped
+        });
+        let text = match (specifier, column) {
+            (None, None) => format!("{}[]", table),
+            (Some(specifier), None) => format!("{}{}", table, specifier.as_str()),
+            (None, Some(column)) => format!("{}[{}]", table, column),
+            (Some(specifier), Some(column)) => {
+                format!("{}[{},[{}]]", table, specifier.as_str(), column)
+            }
+        };
+        Formula::new(text, ATOM)
+    }
+
+    /// A function call such as `SUM(A1:A3,1)`. `name` is written as it is.
+    pub fn call<I: IntoIterator<Item = Formula>>(name: &str, args: I) -> Self {
+        let args: Vec<_> = args.into_iter().map(|x| x.text).collect();
+        Formula::new(format!("{}({})", name, args.join(",")), ATOM)
+    }
+
+    fn binary(self, operator: &str, precedence: u8, right: Formula) -> Self {
+        // Operators are left associative, so the right operand of the same precedence needs parentheses
+        let left = self.operand(precedence);
+        let right = right.operand(precedence + 1);
+        Formula::new(format!("{}{}{}", left, operator, right), precedence)
+    }
+
+    fn operand(self, precedence: u8) -> String {
+        if self.precedence < precedence {
+            format!("({})", self.text)
+        } else {
+            self.text
+        }
+    }
+
+    /// `self^exponent`
+    pub fn pow(self, exponent: Formula) -> Self {
+        self.binary("^", POWER, exponent)
+    }
+
+    /// `self&right`. The `&` operator can also be used.
+    pub fn concat(self, right: Formula) -> Self {
+        self.binary("&", CONCAT, right)
+    }
+
+    /// `self%`
+    pub fn percent(self) -> Self {
+        Formula::new(format!("{}%", self.operand(UNARY)), UNARY)
+    }
+
+    /// `self=right`
+    pub fn equal(self, right: Formula) -> Self {
+        self.binary("=", COMPARISON, right)
+    }
+
+    /// `self<>right`
+    pub fn not_equal(self, right: Formula) -> Self {
+        self.binary("<>", COMPARISON, right)
+    }
+
+    /// `self<right`
+    pub fn less(self, right: Formula) -> Self {
+        self.binary("<", COMPARISON, right)
+    }
+
+    /// `self<=right`
+    pub fn less_equal(self, right: Formula) -> Self {
+        self.binary("<=", COMPARISON, right)
+    }
+
+    /// `self>right`
+    pub fn greater(self, right: Formula) -> Self {
+        self.binary(">", COMPARISON, right)
+    }
+
+    /// `self>=right`
+    pub fn greater_equal(self, right: Formula) -> Self {
+        self.binary(">=", COMPARISON, right)
+    }
+
+    /// Returns the formula with a leading `=`, such as `=SUM(A1:A3)`.
+    pub fn to_formula(&self) -> String {
+        format!("={}", self.text)
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl From<Formula> for String {
+    fn from(formula: Formula) -> Self {
+        formula.to_formula()
+    }
+}
+
+impl Add for Formula {
+    type Output = Formula;
+
+    fn add(self, right: Formula) -> Formula {
+        self.binary("+", ADDITIVE, right)
+    }
+}
+
+impl Sub for Formula {
+    type Output = Formula;
+
+    fn sub(self, right: Formula) -> Formula {
+        self.binary("-", ADDITIVE, right)
+    }
+}
+
+impl Mul for Formula {
+    type Output = Formula;
+
+    fn mul(self, right: Formula) -> Formula {
+        self.binary("*", MULTIPLICATIVE, right)
+    }
+}
+
+impl Div for Formula {
+    type Output = Formula;
+
+    fn div(self, right: Formula) -> Formula {
+        self.binary("/", MULTIPLICATIVE, right)
+    }
+}
+
+/// The `&` operator concatenates strings as in Excel.
+impl BitAnd for Formula {
+    type Output = Formula;
+
+    fn bitand(self, right: Formula) -> Formula {
+        self.concat(right)
+    }
+}
+
+impl Neg for Formula {
+    type Output = Formula;
+
+    fn neg(self) -> Formula {
+        Formula::new(format!("-{}", self.operand(ATOM)), UNARY)
+    }
+}
+
+/// Move the relative cell references in `formula` by `rows` and `cols`, as Excel does when a formula is copied.
+/// Absolute rows and columns, string literals, sheet names, structured references and function names are kept.
+/// Whole row and column references such as `A:A` are not moved.
+pub(crate) fn offset_references(
+    formula: &str,
+    rows: WorksheetRow,
+    cols: WorksheetCol,
+) -> Result<String, XlsxError> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$';
+    let mut result = String::with_capacity(formula.len());
+    let mut chars = formula.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == '"' || c == '\'' {
+            // Quotes are escaped by doubling them, which is handled as two adjacent literals.
+            let mut end = formula.len();
+            for (i, x) in chars.by_ref() {
+                if x == c {
+                    end = i + x.len_utf8();
+                    break;
+                }
+            }
+            result.push_str(&formula[start..end]);
+        } else if c == '[' {
+            // Brackets in a structured reference are nested, or escaped with a quote.
+            let mut depth = 1;
+            let mut end = formula.len();
+            while let Some((i, x)) = chars.next() {
+                match x {
+                    '\'' => {
+                        chars.next();
+                    }
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    end = i + 1;
+                    break;
+                }
+            }
+            result.push_str(&formula[start..end]);
+        } else if is_name_char(c) {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, x)) = chars.peek() {
+                if !is_name_char(x) {
+                    break;
+                }
+                end = i + x.len_utf8();
+                chars.next();
+            }
+            let name = &formula[start..end];
+            let next = chars.peek().map(|&(_, x)| x);
+            match name.parse::<CellRef>() {
+                Ok(cell) if next != Some('(') && next != Some('!') => {
+                    result.push_str(&offset_cell(cell, rows, cols, name)?.to_string())
+                }
+                _ => result.push_str(name),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Ok(result)
+}
+
+fn offset_cell(
+    mut cell: CellRef,
+    rows: WorksheetRow,
+    cols: WorksheetCol,
+    name: &str,
+) -> Result<CellRef, XlsxError> {
+    if !cell.row_absolute {
+        cell.row = cell
+            .row
+            .checked_add(rows)
+            .filter(|x| *x < limits::MAX_ROWS)
+            .ok_or_else(|| XlsxErrorSource::InvalidCellReference(name.to_string()))?;
+    }
+    if !cell.col_absolute {
+        cell.col = cell
+            .col
+            .checked_add(cols)
+            .filter(|x| u32::from(*x) < limits::MAX_COLUMNS)
+            .ok_or_else(|| XlsxErrorSource::InvalidCellReference(name.to_string()))?;
+    }
+    Ok(cell)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_formula() {
+        let a1 = || Formula::cell_at(0, 0);
+        let b1 = || Formula::cell_at(0, 1);
+        for (formula, expected) in [
+            (a1() + b1() * Formula::number(2.), "A1+B1*2"),
+            ((a1() + b1()) * Formula::number(2.), "(A1+B1)*2"),
+            (a1() - (b1() - Formula::number(1.)), "A1-(B1-1)"),
+            ((a1() - b1()) - Formula::number(1.), "A1-B1-1"),
+            (-(a1() + b1()), "-(A1+B1)"),
+            (Formula::number(-2.).pow(Formula::number(2.)), "-2^2"),
+            (a1().pow(Formula::number(-0.5)), "A1^-0.5"),
+            ((a1() + b1()).percent(), "(A1+B1)%"),
+            (
+                (a1() & Formula::string("x")).equal(Formula::string("1x")),
+                "A1&\"x\"=\"1x\"",
+            ),
+            (Formula::number(f64::NAN), "#NUM!"),
+            (
+                Formula::call(
+                    "IF",
+                    [
+                        a1().greater_equal(b1()),
+                        Formula::boolean(true),
+                        Formula::error(CellError::NA),
+                    ],
+                ),
+                "IF(A1>=B1,TRUE,#N/A)",
+            ),
+            (
+                Formula::range(
+                    &CellRange::new(0, 0, 4, 0)
+                        .absolute()
+                        .with_sheet_name("It's"),
+                ),
+                "'It''s'!$A$1:$A$5",
+            ),
+            (
+                Formula::table("Table1", None, Some("Unit [USD]")),
+                "Table1[Unit '[USD']]",
+            ),
+            (
+                Formula::table("Table1", Some(TableSpecifier::Totals), None),
+                "Table1[#Totals]",
+            ),
+            (Formula::table("Table1", None, None), "Table1[]"),
+            (Formula::raw("1+1") * Formula::name("Rate"), "(1+1)*Rate"),
+        ] {
+            assert_eq!(formula.to_string(), expected);
+        }
+
+        let long = "a".repeat(300);
+        assert_eq!(
+            Formula::call("LEN", [Formula::string(&long)]).to_string(),
+            format!("LEN(\"{}\"&\"{}\")", "a".repeat(255), "a".repeat(45))
+        );
+        assert_eq!(
+            (Formula::string(&long) & a1()).to_string(),
+            format!("\"{}\"&\"{}\"&A1", "a".repeat(255), "a".repeat(45))
+        );
+    }
+
+    #[test]
+    fn test_offset_references() {
+        for (formula, expected) in [
+            ("=SUM(A2:A100)", "=SUM(B12:B110)"),
+            ("=$A$1+A$1+$A1+a1", "=$A$1+B$1+$A11+B11"),
+            ("=Sheet2!B3*'B3'!B3", "=Sheet2!C13*'B3'!C13"),
+            ("=\"A1\"&LOG10(A1)", "=\"A1\"&LOG10(B11)"),
+            (
+                "=Table1[[#This Row],[A1 '[x']]]+A1",
+                "=Table1[[#This Row],[A1 '[x']]]+B11",
+            ),
+            ("=SUM(A:A)+1.5E3", "=SUM(A:A)+1.5E3"),
+        ] {
+            assert_eq!(
+                offset_references(formula, 10, 1).unwrap(),
+                expected,
+                "{}",
+                formula
+            );
+        }
+        assert!(offset_references("=XFD1", 0, 1).is_err());
+        assert!(offset_references("=$XFD1", 0, 1).is_ok());
+    }
+}
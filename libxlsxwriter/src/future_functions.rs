@@ -0,0 +1,383 @@
+//! Prefixes of functions added in Excel 2010 and later.
+//!
+//! Excel stores these functions with a `_xlfn.` prefix, and some dynamic array functions additionally with `_xlws.`.
+//! A formula without the prefix is shown as `#NAME?` until the cell is edited.
+//! The parameters of `LET` and `LAMBDA` are stored with a `_xlpm.` prefix.
+//! See [Formulas added in Excel 2010 and later](https://libxlsxwriter.github.io/working_with_formulas.html).
+
+use std::borrow::Cow;
+
+const XLWS_FUNCTIONS: &[&str] = &["FILTER", "SORT"];
+
+const XLFN_FUNCTIONS: &[&str] = &[
+    "ACOT",
+    "ACOTH",
+    "AGGREGATE",
+    "ANCHORARRAY",
+    "ARABIC",
+    "ARRAYTOTEXT",
+    "BASE",
+    "BETA.DIST",
+    "BETA.INV",
+    "BINOM.DIST",
+    "BINOM.DIST.RANGE",
+    "BINOM.INV",
+    "BITAND",
+    "BITLSHIFT",
+    "BITOR",
+    "BITRSHIFT",
+    "BITXOR",
+    "BYCOL",
+    "BYROW",
+    "CEILING.MATH",
+    "CEILING.PRECISE",
+    "CHISQ.DIST",
+    "CHISQ.DIST.RT",
+    "CHISQ.INV",
+    "CHISQ.INV.RT",
+    "CHISQ.TEST",
+    "CHOOSECOLS",
+    "CHOOSEROWS",
+    "COMBINA",
+    "CONCAT",
+    "CONFIDENCE.NORM",
+    "CONFIDENCE.T",
+    "COT",
+    "COTH",
+    "COVARIANCE.P",
+    "COVARIANCE.S",
+    "CSC",
+    "CSCH",
+    "DAYS",
+    "DECIMAL",
+    "DROP",
+    "ENCODEURL",
+    "ERF.PRECISE",
+    "ERFC.PRECISE",
+    "EXPAND",
+    "EXPON.DIST",
+    "F.DIST",
+    "F.DIST.RT",
+    "F.INV",
+    "F.INV.RT",
+    "F.TEST",
+    "FILTERXML",
+    "FLOOR.MATH",
+    "FLOOR.PRECISE",
+    "FORECAST.ETS",
+    "FORECAST.ETS.CONFINT",
+    "FORECAST.ETS.SEASONALITY",
+    "FORECAST.ETS.STAT",
+    "FORECAST.LINEAR",
+    "FORMULATEXT",
+    "GAMMA",
+    "GAMMA.DIST",
+    "GAMMA.INV",
+    "GAMMALN.PRECISE",
+    "GAUSS",
+    "HSTACK",
+    "HYPGEOM.DIST",
+    "IFNA",
+    "IFS",
+    "IMAGE",
+    "IMCOSH",
+    "IMCOT",
+    "IMCSC",
+    "IMCSCH",
+    "IMSEC",
+    "IMSECH",
+    "IMSINH",
+    "IMTAN",
+    "ISFORMULA",
+    "ISO.CEILING",
+    "ISOMITTED",
+    "ISOWEEKNUM",
+    "LAMBDA",
+    "LET",
+    "LOGNORM.DIST",
+    "LOGNORM.INV",
+    "MAKEARRAY",
+    "MAP",
+    "MAXIFS",
+    "MINIFS",
+    "MODE.MULT",
+    "MODE.SNGL",
+    "MUNIT",
+    "NEGBINOM.DIST",
+    "NETWORKDAYS.INTL",
+    "NORM.DIST",
+    "NORM.INV",
+    "NORM.S.DIST",
+    "NORM.S.INV",
+    "NUMBERVALUE",
+    "PDURATION",
+    "PERCENTILE.EXC",
+    "PERCENTILE.INC",
+    "PERCENTRANK.EXC",
+    "PERCENTRANK.INC",
+    "PERMUTATIONA",
+    "PHI",
+    "POISSON.DIST",
+    "QUARTILE.EXC",
+    "QUARTILE.INC",
+    "QUERYSTRING",
+    "RANDARRAY",
+    "RANK.AVG",
+    "RANK.EQ",
+    "REDUCE",
+    "RRI",
+    "SCAN",
+    "SEC",
+    "SECH",
+    "SEQUENCE",
+    "SHEET",
+    "SHEETS",
+    "SINGLE",
+    "SKEW.P",
+    "SORTBY",
+    "STDEV.P",
+    "STDEV.S",
+    "STOCKHISTORY",
+    "SWITCH",
+    "T.DIST",
+    "T.DIST.2T",
+    "T.DIST.RT",
+    "T.INV",
+    "T.INV.2T",
+    "T.TEST",
+    "TAKE",
+    "TEXTAFTER",
+    "TEXTBEFORE",
+    "TEXTJOIN",
+    "TEXTSPLIT",
+    "TOCOL",
+    "TOROW",
+    "UNICHAR",
+    "UNICODE",
+    "UNIQUE",
+    "VALUETOTEXT",
+    "VAR.P",
+    "VAR.S",
+    "VSTACK",
+    "WEBSERVICE",
+    "WEIBULL.DIST",
+    "WORKDAY.INTL",
+    "WRAPCOLS",
+    "WRAPROWS",
+    "XLOOKUP",
+    "XMATCH",
+    "XOR",
+    "Z.TEST",
+];
+
+fn prefix_of(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_uppercase();
+    if XLWS_FUNCTIONS.contains(&name.as_str()) {
+        Some("_xlfn._xlws.")
+    } else if XLFN_FUNCTIONS.contains(&name.as_str()) {
+        Some("_xlfn.")
+    } else {
+        None
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// `LET` and `LAMBDA` calls declare parameters, which are stored with the `_xlpm.` prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupKind {
+    Let,
+    Lambda,
+    Other,
+}
+
+/// A function call, a parenthesis or an array constant, with the parameters it declares.
+struct Group {
+    kind: GroupKind,
+    argument: usize,
+    parameters: Vec<String>,
+}
+
+impl Group {
+    fn new(function: Option<&str>) -> Self {
+        let function = function.map(|x| x.to_ascii_uppercase());
+        let kind = match function.as_deref().map(|x| x.trim_start_matches("_XLFN.")) {
+            Some("LET") => GroupKind::Let,
+            Some("LAMBDA") => GroupKind::Lambda,
+            _ => GroupKind::Other,
+        };
+        Group {
+            kind,
+            argument: 0,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// A name which is a whole argument followed by another argument declares a parameter: the names of `LET` and the parameters of `LAMBDA`.
+    fn declares_parameter(&self) -> bool {
+        match self.kind {
+            GroupKind::Let => self.argument & 1 == 0,
+            GroupKind::Lambda => true,
+            GroupKind::Other => false,
+        }
+    }
+}
+
+/// Add `_xlfn.` and `_xlws.` prefixes to the future functions called in `formula`, and `_xlpm.` prefixes to the parameters of `LET` and `LAMBDA`.
+/// Names in string literals, quoted sheet names and structured references are not changed, and names which already have a prefix are kept as they are.
+pub(crate) fn add_prefixes(formula: &str) -> Cow<'_, str> {
+    let mut result = String::with_capacity(formula.len());
+    let mut groups: Vec<Group> = Vec::new();
+    let mut argument_start = false;
+    let mut function = None;
+    let mut chars = formula.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut name = None;
+        if c == '"' || c == '\'' {
+            // Quotes are escaped by doubling them, which is handled as two adjacent literals.
+            let mut end = formula.len();
+            for (i, x) in chars.by_ref() {
+                if x == c {
+                    end = i + x.len_utf8();
+                    break;
+                }
+            }
+            result.push_str(&formula[start..end]);
+        } else if c == '[' {
+            let mut depth = 1;
+            let mut end = formula.len();
+            for (i, x) in chars.by_ref() {
+                match x {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => (),
+                }
+                if depth == 0 {
+                    end = i + x.len_utf8();
+                    break;
+                }
+            }
+            result.push_str(&formula[start..end]);
+        } else if is_name_char(c) {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, x)) = chars.peek() {
+                if !is_name_char(x) {
+                    break;
+                }
+                end = i + x.len_utf8();
+                chars.next();
+            }
+            let text = &formula[start..end];
+            let next = formula[end..].chars().next();
+            let next_token = formula[end..].trim_start().chars().next();
+            let parameter = strip_parameter_prefix(text);
+            let upper = parameter.to_ascii_uppercase();
+            let declaration = argument_start
+                && next_token == Some(',')
+                && groups.last().is_some_and(Group::declares_parameter);
+            if declaration {
+                if let Some(group) = groups.last_mut() {
+                    group.parameters.push(upper);
+                }
+                result.push_str(PARAMETER_PREFIX);
+                result.push_str(parameter);
+            } else if next == Some('!') {
+                result.push_str(text);
+            } else if groups.iter().any(|x| x.parameters.contains(&upper)) {
+                result.push_str(PARAMETER_PREFIX);
+                result.push_str(parameter);
+            } else {
+                if next == Some('(') {
+                    if let Some(prefix) = prefix_of(text) {
+                        result.push_str(prefix);
+                    }
+                }
+                result.push_str(text);
+            }
+            name = Some(text);
+        } else {
+            match c {
+                '(' | '{' => groups.push(Group::new(if c == '(' { function } else { None })),
+                ')' | '}' => {
+                    groups.pop();
+                }
+                ',' => {
+                    if let Some(group) = groups.last_mut() {
+                        group.argument += 1;
+                    }
+                }
+                _ => (),
+            }
+            result.push(c);
+        }
+        if !c.is_whitespace() {
+            argument_start = matches!(c, '(' | '{' | ',');
+        }
+        function = name;
+    }
+    if result == formula {
+        Cow::Borrowed(formula)
+    } else {
+        Cow::Owned(result)
+    }
+}
+
+const PARAMETER_PREFIX: &str = "_xlpm.";
+
+fn strip_parameter_prefix(name: &str) -> &str {
+    match name.get(..PARAMETER_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(PARAMETER_PREFIX) => {
+            &name[PARAMETER_PREFIX.len()..]
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_add_prefixes() {
+        for (formula, expected) in [
+            ("=SUM(A1:A3)", "=SUM(A1:A3)"),
+            ("=XLOOKUP(A1, B:B, C:C)", "=_xlfn.XLOOKUP(A1, B:B, C:C)"),
+            (
+                "=SORT(FILTER(A1:A9, B1:B9>0))",
+                "=_xlfn._xlws.SORT(_xlfn._xlws.FILTER(A1:A9, B1:B9>0))",
+            ),
+            ("=_xlfn.XLOOKUP(A1,B:B,C:C)", "=_xlfn.XLOOKUP(A1,B:B,C:C)"),
+            ("=stdev.s(A:A)", "=_xlfn.stdev.s(A:A)"),
+            (
+                "=CONCAT(\"IFS(\", 'LET(x'!A1)",
+                "=_xlfn.CONCAT(\"IFS(\", 'LET(x'!A1)",
+            ),
+            ("=\"a\"\"CONCAT(\"", "=\"a\"\"CONCAT(\""),
+            ("=MYSORT(A1)", "=MYSORT(A1)"),
+            ("=LET(x,1,x+1)", "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)"),
+            ("=LET(x,1,x)+x", "=_xlfn.LET(_xlpm.x,1,_xlpm.x)+x"),
+            (
+                "=LAMBDA(a, b, a*b)(2, 3)",
+                "=_xlfn.LAMBDA(_xlpm.a, _xlpm.b, _xlpm.a*_xlpm.b)(2, 3)",
+            ),
+            (
+                "=LET(total, SUM(A1:A3), f, LAMBDA(x, x*total), f(2))",
+                "=_xlfn.LET(_xlpm.total, SUM(A1:A3), _xlpm.f, _xlfn.LAMBDA(_xlpm.x, _xlpm.x*_xlpm.total), _xlpm.f(2))",
+            ),
+            (
+                "=MAP(A1:A3, LAMBDA(v, IF(v>{1,2}, v, Table1[v])))",
+                "=_xlfn.MAP(A1:A3, _xlfn.LAMBDA(_xlpm.v, IF(_xlpm.v>{1,2}, _xlpm.v, Table1[v])))",
+            ),
+            (
+                "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)",
+                "=_xlfn.LET(_xlpm.x,1,_xlpm.x+1)",
+            ),
+            ("=LET(x,Sheet1!A1,x)", "=_xlfn.LET(_xlpm.x,Sheet1!A1,_xlpm.x)"),
+            ("=NETWORKDAYS.INTL(A1,B1,11)", "=_xlfn.NETWORKDAYS.INTL(A1,B1,11)"),
+        ] {
+            assert_eq!(add_prefixes(formula), expected, "{}", formula);
+        }
+    }
+}
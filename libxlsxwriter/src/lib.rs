@@ -51,8 +51,8 @@
 //!     &Format::new().set_font_color(FormatColor::Green).set_align(FormatAlignment::CenterAcross)
 //!                   .set_vertical_align(FormatVerticalAlignment::VerticalCenter)))?;
 //!
-//! sheet1.set_selection(1, 0, 1, 2);
-//! sheet1.set_tab_color(FormatColor::Cyan);
+//! sheet1.set_selection(1, 0, 1, 2)?;
+//! sheet1.set_tab_color(FormatColor::Cyan)?;
 //! workbook.close()?;
 //! # Ok(())
 //! # }
@@ -70,19 +70,45 @@
 //! 2. Use [`Format::new`] instead of [`Workbook::add_format`]
 //! 3. [`Format`] object's methods now return mutable reference. Please rewrite code to adopt this change.
 //! 4. Some functions now return `Result<T, XlsxError>`. Please rewrite code to adopt this change.
+//!
+//! ### Upgrade from prior version 0.6
+//!
+//! 1. [`Worksheet::write_datetime`] now applies a default date, time or datetime format when `format` is `None`.
+//!    Set `General` with [`Workbook::set_default_date_format`], [`Workbook::set_default_time_format`] and [`Workbook::set_default_datetime_format`] to write a bare serial number as before.
+//! 2. [`Worksheet::write_number`] and [`Worksheet::write_formula_num`] now return an error for NaN and infinite numbers instead of writing a file which Excel reports as corrupt.
+//!    See [`Workbook::set_non_finite_number_policy`] to write them in another way.
+//! 3. [`Worksheet`], [`Chart`] and [`ChartSeries`] no longer have a lifetime parameter, so they can be stored next to the [`Workbook`].
+//!    Methods which returned nothing, such as [`Worksheet::set_selection`] and [`ChartSeries::set_line`], now return `Result<(), XlsxError>`
+//!    to report an error of kind [`XlsxErrorKind::WorkbookClosed`] when they are called after [`Workbook::close`].
+//! 4. [`Workbook::get_worksheet`] returns an error of kind [`XlsxErrorKind::WorksheetInUse`] while another [`Worksheet`] object
+//!    for the worksheet is alive. Drop the object returned by [`Workbook::add_worksheet`] before getting the worksheet again.
 
 extern crate libxlsxwriter_sys;
 
 /// Manipulate Charts.
 pub mod chart;
+mod defined_name;
 mod error;
+pub mod evaluator;
+mod future_functions;
+mod package;
+mod progress;
 
 /// Manipulate Formats.
 pub mod format;
+pub mod formula;
+
+pub mod limits;
+pub mod mock;
 
 /// xlsxwriter prelude.
 pub mod prelude;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub mod utility;
+
 /// Manipulate Workbook.
 pub mod workbook;
 
@@ -92,10 +118,11 @@ pub mod worksheet;
 use std::{ffi::CString, os::raw::c_char, pin::Pin};
 
 use chart::*;
-use error::XlsxErrorSource;
+use error::{ErrorContext, XlsxErrorSource};
 use format::*;
 use worksheet::*;
 
+pub use error::XlsxErrorKind;
 pub use format::Format;
 pub use workbook::Workbook;
 pub use worksheet::Worksheet;
@@ -104,6 +131,7 @@ pub use worksheet::Worksheet;
 #[derive(Debug)]
 pub struct XlsxError {
     pub(crate) source: XlsxErrorSource,
+    pub(crate) context: ErrorContext,
 }
 
 fn convert_bool(value: bool) -> u8 {
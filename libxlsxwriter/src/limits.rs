@@ -0,0 +1,137 @@
+//! Excel limits checked before data is passed to libxlsxwriter, and the policy to apply when a value exceeds them.
+//!
+//! Rows and columns out of range are always reported as errors. Other limits are handled with [`OverflowPolicy`],
+//! which is set with [`Workbook::set_overflow_policy`](crate::Workbook::set_overflow_policy).
+//!
+//! Lengths are counted in characters, as libxlsxwriter does.
+//!
+//! NaN and infinite numbers are handled with [`NonFiniteNumberPolicy`].
+
+use crate::{WorksheetCol, WorksheetRow};
+
+/// Maximum number of rows in a worksheet.
+pub const MAX_ROWS: u32 = 1_048_576;
+/// Maximum number of columns in a worksheet.
+pub const MAX_COLUMNS: u32 = 16_384;
+/// Index of the last row of a worksheet.
+pub(crate) const LAST_ROW: WorksheetRow = MAX_ROWS - 1;
+/// Index of the last column of a worksheet.
+pub(crate) const LAST_COL: WorksheetCol = (MAX_COLUMNS - 1) as WorksheetCol;
+/// Maximum number of characters in a cell string.
+pub const MAX_STRING_LENGTH: usize = 32_767;
+/// Maximum number of characters in the values of a list data validation, including the separating commas.
+pub const MAX_LIST_LENGTH: usize = 255;
+/// Maximum number of characters in a worksheet name.
+pub const MAX_SHEET_NAME_LENGTH: usize = 31;
+/// Maximum number of characters in a hyperlink.
+pub const MAX_URL_LENGTH: usize = 2_079;
+
+/// What to do when a value exceeds an Excel limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum OverflowPolicy {
+    /// Return an error. This is the default.
+    #[default]
+    Error,
+    /// Truncate the value to the limit and report an [`OverflowWarning`] to the callback set with
+    /// [`Workbook::set_overflow_callback`](crate::Workbook::set_overflow_callback).
+    /// Values of a list data validation which don't fit are dropped.
+    Truncate,
+    /// Split a long string of [`Worksheet::write_string`](crate::Worksheet::write_string) into chunks
+    /// and write them to the cells to the right. Other values are truncated as [`OverflowPolicy::Truncate`].
+    /// An [`OverflowWarning`] is reported in both cases.
+    Spill,
+}
+
+/// Details of a value which exceeded an Excel limit and was truncated or spilled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverflowWarning {
+    /// Name of the worksheet, or `None` for a workbook level value such as a sheet name.
+    pub sheet_name: Option<String>,
+    /// Row and column of the value, if the value belongs to a cell.
+    pub cell: Option<(WorksheetRow, WorksheetCol)>,
+    /// Name of the method, such as `write_string`.
+    pub method: &'static str,
+    /// Length of the value in characters.
+    pub length: usize,
+    /// Limit of the length.
+    pub limit: usize,
+    /// How the value was handled. [`OverflowPolicy::Truncate`] or [`OverflowPolicy::Spill`].
+    pub policy: OverflowPolicy,
+}
+
+/// How numbers which Excel cannot store, NaN, infinity and negative infinity, are written.
+/// It is set with [`Workbook::set_non_finite_number_policy`](crate::Workbook::set_non_finite_number_policy).
+///
+/// The policy applies to [`Worksheet::write_number`](crate::Worksheet::write_number) and the cached result of
+/// [`Worksheet::write_formula_num`](crate::Worksheet::write_formula_num), and so to every writer built on them.
+/// For a formula, the formula is kept and only its cached result is replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum NonFiniteNumberPolicy {
+    /// Return an error. This is the default.
+    #[default]
+    Error,
+    /// Write a blank cell.
+    Blank,
+    /// Write a `#NUM!` error cell as [`Worksheet::write_error`](crate::Worksheet::write_error).
+    NumError,
+    /// Write a `#N/A` error cell as [`Worksheet::write_error`](crate::Worksheet::write_error).
+    NaError,
+    /// Write the number as a string: `NaN`, `inf` or `-inf`.
+    String,
+}
+
+pub(crate) type OverflowCallback = Box<dyn Fn(&OverflowWarning)>;
+
+pub(crate) fn is_cell_in_range(row: WorksheetRow, col: WorksheetCol) -> bool {
+    row < MAX_ROWS && u32::from(col) < MAX_COLUMNS
+}
+
+/// Returns the first `limit` characters of `text`, or `None` if `text` is not longer than `limit`.
+pub(crate) fn truncate(text: &str, limit: usize) -> Option<&str> {
+    text.char_indices().nth(limit).map(|(i, _)| &text[..i])
+}
+
+/// Split `text` into chunks of at most `limit` characters.
+pub(crate) fn split_chunks(text: &str, limit: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while let Some(chunk) = truncate(rest, limit) {
+        chunks.push(chunk);
+        rest = &rest[chunk.len()..];
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Returns the number of values of a list data validation which fit in [`MAX_LIST_LENGTH`].
+pub(crate) fn fitting_list_values(values: &[String]) -> usize {
+    let mut length = 0;
+    for (i, value) in values.iter().enumerate() {
+        length += value.chars().count() + if i == 0 { 0 } else { 1 };
+        if length > MAX_LIST_LENGTH {
+            return i;
+        }
+    }
+    values.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        assert!(is_cell_in_range(1_048_575, 16_383));
+        assert!(!is_cell_in_range(1_048_576, 0));
+        assert!(!is_cell_in_range(0, 16_384));
+
+        assert_eq!(truncate("abc", 3), None);
+        assert_eq!(truncate("日本語です", 3), Some("日本語"));
+        assert_eq!(split_chunks("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(split_chunks("abc", 3), vec!["abc"]);
+
+        let values: Vec<String> = (0..100).map(|x| format!("{:04}", x)).collect();
+        assert_eq!(fitting_list_values(&values), 51);
+        assert_eq!(fitting_list_values(&values[..10]), 10);
+    }
+}
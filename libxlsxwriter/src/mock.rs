@@ -0,0 +1,376 @@
+//! A recording workbook for unit tests of code which builds workbooks.
+//!
+//! [`MockWorkbook`] implements [`WorkbookWriter`] and records the operations on its worksheets as [`SheetOp`]s
+//! instead of writing a file. Code written against [`WorkbookWriter`] and [`SheetWriter`] takes a [`Workbook`](crate::Workbook)
+//! in production and a [`MockWorkbook`] in tests.
+//! ```rust
+//! use xlsxwriter::mock::MockWorkbook;
+//! use xlsxwriter::prelude::*;
+//!
+//! fn build_report<W: WorkbookWriter>(workbook: &W) -> Result<(), XlsxError> {
+//!     let bold = Format::new().set_bold().clone();
+//!     let mut summary = workbook.add_worksheet(Some("Summary"))?;
+//!     summary.write_string(0, 0, "Total", Some(&bold))?;
+//!     summary.write_formula(0, 1, "=SUM(Data!A:A)", None)?;
+//!     workbook.add_worksheet(Some("Data"))?.write_number(0, 0, 42., None)
+//! }
+//!
+//! # fn main() -> Result<(), XlsxError> {
+//! let workbook = MockWorkbook::new();
+//! build_report(&workbook)?;
+//! assert_eq!(workbook.worksheet_names(), ["Summary", "Data"]);
+//! let summary = workbook.worksheet("Summary").unwrap();
+//! assert_eq!(
+//!     summary.cell(0, 0),
+//!     Some(&SheetOp::String {
+//!         row: 0,
+//!         col: 0,
+//!         text: "Total".to_string(),
+//!         format: Some(Format::new().set_bold().clone()),
+//!     })
+//! );
+//! assert_eq!(summary.len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+//! Charts are recorded as [`MockChartRecord`]s, and defined names as [`DefinedName`]s.
+//! Arguments are not checked by the mock, except the worksheet names and the defined names.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::chart::{ChartOp, ChartSeriesOp, ChartSeriesWriter, ChartType, ChartWriter};
+use crate::limits;
+use crate::workbook::DefinedName;
+use crate::worksheet::{SheetBuffer, SheetOp, SheetWriter, WorkbookWriter};
+use crate::{WorksheetCol, WorksheetRow, XlsxError};
+
+/// A workbook which records the operations on its worksheets and charts. See the [module documentation](self).
+///
+/// Clones of a `MockWorkbook` share the recorded worksheets, charts and defined names.
+#[derive(Debug, Clone, Default)]
+pub struct MockWorkbook {
+    sheets: Rc<RefCell<Vec<(String, SheetBuffer)>>>,
+    charts: Rc<RefCell<Vec<MockChartRecord>>>,
+    defined_names: Rc<RefCell<Vec<DefinedName>>>,
+}
+
+/// A chart added to a [`MockWorkbook`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockChartRecord {
+    /// The type of the chart.
+    pub chart_type: ChartType,
+    /// The operations on the chart and its series, in the order they were performed.
+    pub ops: Vec<ChartOp>,
+    /// The worksheet name, row and column where the chart was inserted, or `None` if it was not inserted.
+    pub position: Option<(String, WorksheetRow, WorksheetCol)>,
+}
+
+/// A chart of a [`MockWorkbook`].
+#[derive(Debug, Clone)]
+pub struct MockChart {
+    charts: Rc<RefCell<Vec<MockChartRecord>>>,
+    index: usize,
+}
+
+/// A series of a [`MockChart`].
+#[derive(Debug, Clone)]
+pub struct MockChartSeries {
+    charts: Rc<RefCell<Vec<MockChartRecord>>>,
+    chart: usize,
+    series: usize,
+}
+
+/// A worksheet of a [`MockWorkbook`].
+#[derive(Debug, Clone)]
+pub struct MockWorksheet {
+    sheets: Rc<RefCell<Vec<(String, SheetBuffer)>>>,
+    index: usize,
+}
+
+impl MockWorkbook {
+    /// A workbook without worksheets.
+    pub fn new() -> Self {
+        MockWorkbook::default()
+    }
+
+    /// Returns the names of the worksheets in the order they were added.
+    pub fn worksheet_names(&self) -> Vec<String> {
+        self.sheets
+            .borrow()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Returns the operations recorded on the worksheet named `name`, ignoring case.
+    pub fn worksheet(&self, name: &str) -> Option<SheetBuffer> {
+        self.sheets
+            .borrow()
+            .iter()
+            .find(|(x, _)| x.to_lowercase() == name.to_lowercase())
+            .map(|(_, buffer)| buffer.clone())
+    }
+
+    /// Returns the charts in the order they were added.
+    pub fn charts(&self) -> Vec<MockChartRecord> {
+        self.charts.borrow().clone()
+    }
+
+    /// Returns the defined names in the order they were added.
+    pub fn defined_names(&self) -> Vec<DefinedName> {
+        self.defined_names.borrow().clone()
+    }
+}
+
+impl WorkbookWriter for MockWorkbook {
+    type Sheet = MockWorksheet;
+    type Chart = MockChart;
+
+    /// Add a worksheet. As [`Workbook::add_worksheet`](crate::Workbook::add_worksheet), the default name is `Sheet1`, `Sheet2` and so on,
+    /// and a name which is too long, contains any of `[]:*?/\`, starts or ends with `'`, or is already used returns an error.
+    fn add_worksheet(&self, sheet_name: Option<&str>) -> Result<MockWorksheet, XlsxError> {
+        let mut sheets = self.sheets.borrow_mut();
+        let name = match sheet_name {
+            Some(name) => name.to_string(),
+            None => format!("Sheet{}", sheets.len() + 1),
+        };
+        if name.chars().count() > limits::MAX_SHEET_NAME_LENGTH {
+            return Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_LENGTH_EXCEEDED,
+            ));
+        }
+        if name.contains(&['[', ']', ':', '*', '?', '/', '\\'][..]) {
+            return Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_INVALID_SHEETNAME_CHARACTER,
+            ));
+        }
+        if name.starts_with('\'') || name.ends_with('\'') {
+            return Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_START_END_APOSTROPHE,
+            ));
+        }
+        if sheets
+            .iter()
+            .any(|(x, _)| x.to_lowercase() == name.to_lowercase())
+        {
+            return Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_ALREADY_USED,
+            ));
+        }
+        sheets.push((name, SheetBuffer::new()));
+        Ok(MockWorksheet {
+            sheets: self.sheets.clone(),
+            index: sheets.len() - 1,
+        })
+    }
+
+    fn add_chart(&self, chart_type: ChartType) -> MockChart {
+        let mut charts = self.charts.borrow_mut();
+        charts.push(MockChartRecord {
+            chart_type,
+            ops: Vec::new(),
+            position: None,
+        });
+        MockChart {
+            charts: self.charts.clone(),
+            index: charts.len() - 1,
+        }
+    }
+
+    /// Record a defined name. A local name is given as `Sheet1!name`, as for [`Workbook::define_name`](crate::Workbook::define_name).
+    fn define_name(&self, name: &str, formula: &str) -> Result<(), XlsxError> {
+        let defined_name = match name.rsplit_once('!') {
+            Some((sheet_name, name)) => {
+                let sheet_name = sheet_name
+                    .strip_prefix('\'')
+                    .and_then(|x| x.strip_suffix('\''))
+                    .unwrap_or(sheet_name);
+                DefinedName::new(name, formula).local(sheet_name)
+            }
+            None => DefinedName::new(name, formula),
+        };
+        self.add_defined_name(&defined_name)
+    }
+
+    fn add_defined_name(&self, name: &DefinedName) -> Result<(), XlsxError> {
+        name.validate()?;
+        self.defined_names.borrow_mut().push(name.clone());
+        Ok(())
+    }
+
+    /// Record the position of `chart`. As [`Worksheet::insert_chart`](crate::Worksheet::insert_chart),
+    /// a chart can be inserted only once.
+    fn insert_chart(
+        &self,
+        worksheet: &mut MockWorksheet,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        chart: &MockChart,
+    ) -> Result<(), XlsxError> {
+        let mut charts = chart.charts.borrow_mut();
+        let record = &mut charts[chart.index];
+        if record.position.is_some() {
+            return Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_PARAMETER_VALIDATION,
+            ));
+        }
+        record.position = Some((worksheet.name(), row, col));
+        Ok(())
+    }
+}
+
+impl MockWorksheet {
+    /// Returns the name of the worksheet.
+    pub fn name(&self) -> String {
+        self.sheets.borrow()[self.index].0.clone()
+    }
+
+    /// Returns the operations recorded on the worksheet so far.
+    pub fn buffer(&self) -> SheetBuffer {
+        self.sheets.borrow()[self.index].1.clone()
+    }
+}
+
+impl SheetWriter for MockWorksheet {
+    fn write_op(&mut self, op: SheetOp) -> Result<(), XlsxError> {
+        self.sheets.borrow_mut()[self.index].1.write_op(op)
+    }
+}
+
+impl ChartWriter for MockChart {
+    type Series = MockChartSeries;
+
+    fn add_series(
+        &mut self,
+        categories: Option<&str>,
+        values: Option<&str>,
+    ) -> Result<MockChartSeries, XlsxError> {
+        let mut charts = self.charts.borrow_mut();
+        let ops = &mut charts[self.index].ops;
+        let series = ops
+            .iter()
+            .filter(|x| matches!(x, ChartOp::AddSeries { .. }))
+            .count();
+        ops.push(ChartOp::AddSeries {
+            categories: categories.map(str::to_string),
+            values: values.map(str::to_string),
+        });
+        Ok(MockChartSeries {
+            charts: self.charts.clone(),
+            chart: self.index,
+            series,
+        })
+    }
+
+    fn add_title(&mut self, title: &str) -> Result<(), XlsxError> {
+        self.charts.borrow_mut()[self.index]
+            .ops
+            .push(ChartOp::Title {
+                title: title.to_string(),
+            });
+        Ok(())
+    }
+}
+
+impl ChartSeriesWriter for MockChartSeries {
+    fn write_series_op(&mut self, op: ChartSeriesOp) -> Result<(), XlsxError> {
+        self.charts.borrow_mut()[self.chart]
+            .ops
+            .push(ChartOp::Series {
+                series: self.series,
+                op,
+            });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_add_worksheet() {
+        let workbook = MockWorkbook::new();
+        let mut first = workbook.add_worksheet(None).unwrap();
+        let second = workbook.add_worksheet(Some("Data")).unwrap();
+        assert_eq!(first.name(), "Sheet1");
+        assert_eq!(second.name(), "Data");
+        assert!(workbook.add_worksheet(Some("DATA")).is_err());
+        assert!(workbook.add_worksheet(Some(&"a".repeat(32))).is_err());
+        for name in [
+            "a[b", "a]b", "a:b", "a*b", "a?b", "a/b", "a\\b", "'ab", "ab'",
+        ] {
+            assert!(workbook.add_worksheet(Some(name)).is_err(), "{}", name);
+        }
+        assert!(workbook.add_worksheet(Some("a'b")).is_ok());
+
+        first.write_number(0, 0, 1., None).unwrap();
+        assert_eq!(first.buffer().len(), 1);
+        assert_eq!(workbook.worksheet("sheet1").unwrap().len(), 1);
+        assert!(workbook.worksheet("Data").unwrap().is_empty());
+        assert_eq!(workbook.worksheet_names(), ["Sheet1", "Data", "a'b"]);
+    }
+
+    #[test]
+    fn test_chart() -> Result<(), XlsxError> {
+        let workbook = MockWorkbook::new();
+        let mut worksheet = workbook.add_worksheet(None)?;
+        let mut chart = workbook.add_chart(ChartType::Column);
+        chart.add_title("Sales")?;
+        chart.add_series(None, Some("=Sheet1!$A$1:$A$5"))?;
+        let mut series = chart.add_series(None, None)?;
+        series.set_values("Sheet1", 0, 1, 4, 1)?;
+        series.set_smooth(true)?;
+        workbook.insert_chart(&mut worksheet, 1, 3, &chart)?;
+        assert!(workbook
+            .insert_chart(&mut worksheet, 10, 3, &chart)
+            .is_err());
+        workbook.define_name("Sales", "=Sheet1!$B$1:$B$5")?;
+        workbook.define_name("'Sheet 1'!Rate", "=0.5")?;
+        assert!(workbook.define_name("1x", "=1").is_err());
+
+        assert_eq!(
+            workbook.charts(),
+            [MockChartRecord {
+                chart_type: ChartType::Column,
+                ops: vec![
+                    ChartOp::Title {
+                        title: "Sales".to_string()
+                    },
+                    ChartOp::AddSeries {
+                        categories: None,
+                        values: Some("=Sheet1!$A$1:$A$5".to_string())
+                    },
+                    ChartOp::AddSeries {
+                        categories: None,
+                        values: None
+                    },
+                    ChartOp::Series {
+                        series: 1,
+                        op: ChartSeriesOp::Values {
+                            sheet_name: "Sheet1".to_string(),
+                            first_row: 0,
+                            first_col: 1,
+                            last_row: 4,
+                            last_col: 1
+                        }
+                    },
+                    ChartOp::Series {
+                        series: 1,
+                        op: ChartSeriesOp::Smooth(true)
+                    },
+                ],
+                position: Some(("Sheet1".to_string(), 1, 3)),
+            }]
+        );
+        assert_eq!(
+            workbook.defined_names(),
+            [
+                DefinedName::new("Sales", "=Sheet1!$B$1:$B$5"),
+                DefinedName::new("Rate", "=0.5").local("Sheet 1")
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+//! Post-processing of a closed xlsx file for settings libxlsxwriter doesn't expose.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use zip::{ZipArchive, ZipWriter};
+
+use crate::error::XlsxErrorSource;
+use crate::XlsxError;
+
+impl From<std::io::Error> for XlsxError {
+    fn from(e: std::io::Error) -> Self {
+        XlsxErrorSource::Io(e.to_string()).into()
+    }
+}
+
+impl From<zip::result::ZipError> for XlsxError {
+    fn from(e: zip::result::ZipError) -> Self {
+        XlsxErrorSource::Io(e.to_string()).into()
+    }
+}
+
+/// Rewrite XML parts of the xlsx file at `path`. `rewrite` is called with the name and the content of each part named in `parts`
+/// and returns the new content, or `None` to keep the part as it is. Other parts are copied without being decompressed.
+pub(crate) fn rewrite_parts<F>(path: &Path, parts: &[&str], mut rewrite: F) -> Result<(), XlsxError>
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut temporary_name = path.as_os_str().to_owned();
+    temporary_name.push(".tmp");
+    let temporary_path = Path::new(&temporary_name);
+
+    let result = (|| {
+        let mut writer = ZipWriter::new(File::create(temporary_path)?);
+        for i in 0..archive.len() {
+            let name = archive.by_index_raw(i)?.name().to_string();
+            let new_content = if parts.contains(&name.as_str()) {
+                let mut content = String::new();
+                archive.by_index(i)?.read_to_string(&mut content)?;
+                rewrite(&name, &content)
+            } else {
+                None
+            };
+            match new_content {
+                Some(new_content) => {
+                    writer.start_file(
+                        name,
+                        zip::write::FileOptions::default()
+                            .compression_method(zip::CompressionMethod::Deflated),
+                    )?;
+                    writer.write_all(new_content.as_bytes())?;
+                }
+                None => writer.raw_copy_file(archive.by_index_raw(i)?)?,
+            }
+        }
+        writer.finish()?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            fs::rename(temporary_path, path)?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = fs::remove_file(temporary_path);
+            Err(e)
+        }
+    }
+}
+
+/// Replace the first empty element `<tag .../>` in `xml` with `element`, or insert `element` before `</before>` if there is no such element.
+pub(crate) fn replace_empty_element(xml: &str, tag: &str, element: &str, before: &str) -> String {
+    let start_tag = format!("<{}", tag);
+    let start = xml.find(&start_tag).filter(|start| {
+        matches!(
+            xml[start + start_tag.len()..].chars().next(),
+            Some(' ') | Some('/')
+        )
+    });
+    if let Some(start) = start {
+        if let Some(length) = xml[start..].find("/>") {
+            let mut result = xml.to_string();
+            result.replace_range(start..start + length + 2, element);
+            return result;
+        }
+    }
+    let end_tag = format!("</{}>", before);
+    match xml.rfind(&end_tag) {
+        Some(end) => {
+            let mut result = xml.to_string();
+            result.insert_str(end, element);
+            result
+        }
+        None => xml.to_string(),
+    }
+}
+
+/// Reorder the `<sheet>` elements of the workbook part `xml`. `order` lists the current indices of the sheets in the new order.
+/// Sheet indices in `localSheetId`, `activeTab` and `firstSheet` attributes are updated to the new order.
+pub(crate) fn reorder_sheets(xml: &str, order: &[usize]) -> String {
+    let (start, end) = match (xml.find("<sheets>"), xml.find("</sheets>")) {
+        (Some(start), Some(end)) => (start + "<sheets>".len(), end),
+        _ => return xml.to_string(),
+    };
+    let sheets: Vec<&str> = xml[start..end].split_inclusive("/>").collect();
+    if sheets.len() != order.len() {
+        return xml.to_string();
+    }
+    let mut new_index = vec![0; order.len()];
+    for (new, &old) in order.iter().enumerate() {
+        new_index[old] = new;
+    }
+    let mut result = xml[..start].to_string();
+    for &old in order {
+        result.push_str(sheets[old]);
+    }
+    result.push_str(&xml[end..]);
+    for attribute in ["localSheetId", "activeTab", "firstSheet"] {
+        result = replace_indices(&result, attribute, &new_index);
+    }
+    result
+}
+
+/// Reorder the worksheet names in `TitlesOfParts` of the document properties part `xml` as [`reorder_sheets`] does.
+/// The worksheet names are the first titles, followed by the names of the print areas and other defined names, which are kept.
+pub(crate) fn reorder_titles(xml: &str, order: &[usize]) -> String {
+    const START_TAG: &str = "<vt:lpstr>";
+    const END_TAG: &str = "</vt:lpstr>";
+    let start = match xml
+        .find("<TitlesOfParts>")
+        .and_then(|x| xml[x..].find(START_TAG).map(|y| x + y))
+    {
+        Some(start) => start,
+        None => return xml.to_string(),
+    };
+    let mut titles = Vec::new();
+    let mut end = start;
+    for _ in order {
+        match xml[end..].find(END_TAG) {
+            Some(length) if xml[end..].starts_with(START_TAG) => {
+                titles.push(&xml[end..end + length + END_TAG.len()]);
+                end += length + END_TAG.len();
+            }
+            _ => return xml.to_string(),
+        }
+    }
+    let mut result = xml[..start].to_string();
+    for &old in order {
+        result.push_str(titles[old]);
+    }
+    result.push_str(&xml[end..]);
+    result
+}
+
+/// Replace the values of `attribute` with their new indices in `new_index`.
+fn replace_indices(xml: &str, attribute: &str, new_index: &[usize]) -> String {
+    let pattern = format!(r#" {}=""#, attribute);
+    let mut result = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(position) = rest.find(&pattern) {
+        let value_start = position + pattern.len();
+        result.push_str(&rest[..value_start]);
+        rest = &rest[value_start..];
+        let length = rest.find('"').unwrap_or(0);
+        match rest[..length]
+            .parse::<usize>()
+            .ok()
+            .and_then(|x| new_index.get(x))
+        {
+            Some(index) => result.push_str(&index.to_string()),
+            None => result.push_str(&rest[..length]),
+        }
+        rest = &rest[length..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_replace_empty_element() {
+        let xml = r#"<workbook><sheets/><calcPr calcId="124519" fullCalcOnLoad="1"/></workbook>"#;
+        assert_eq!(
+            replace_empty_element(xml, "calcPr", r#"<calcPr calcId="124519"/>"#, "workbook"),
+            r#"<workbook><sheets/><calcPr calcId="124519"/></workbook>"#
+        );
+        assert_eq!(
+            replace_empty_element(
+                "<workbook><sheets/></workbook>",
+                "calcPr",
+                "<calcPr/>",
+                "workbook"
+            ),
+            "<workbook><sheets/><calcPr/></workbook>"
+        );
+        assert_eq!(
+            replace_empty_element(
+                "<workbook><calcPrX/></workbook>",
+                "calcPr",
+                "<calcPr/>",
+                "workbook"
+            ),
+            "<workbook><calcPrX/><calcPr/></workbook>"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_parts() -> Result<(), XlsxError> {
+        let path = std::env::temp_dir().join("xlsxwriter-test_rewrite_parts.xlsx");
+        let mut writer = ZipWriter::new(File::create(&path)?);
+        for (name, content) in [
+            ("xl/workbook.xml", "<workbook/>"),
+            ("xl/worksheets/sheet1.xml", "<worksheet/>"),
+        ] {
+            writer.start_file(name, zip::write::FileOptions::default())?;
+            writer.write_all(content.as_bytes())?;
+        }
+        writer.finish()?;
+
+        let mut rewritten = Vec::new();
+        rewrite_parts(&path, &["xl/workbook.xml"], |name, _| {
+            rewritten.push(name.to_string());
+            Some("<workbook><sheets/></workbook>".to_string())
+        })?;
+        assert_eq!(rewritten, ["xl/workbook.xml"]);
+
+        let mut archive = ZipArchive::new(File::open(&path)?)?;
+        let mut content = String::new();
+        archive
+            .by_name("xl/workbook.xml")?
+            .read_to_string(&mut content)?;
+        assert_eq!(content, "<workbook><sheets/></workbook>");
+        content.clear();
+        archive
+            .by_name("xl/worksheets/sheet1.xml")?
+            .read_to_string(&mut content)?;
+        assert_eq!(content, "<worksheet/>");
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_reorder_sheets() {
+        let xml = concat!(
+            r#"<workbook><bookViews><workbookView xWindow="240" activeTab="2"/></bookViews><sheets>"#,
+            r#"<sheet name="A" sheetId="1" r:id="rId1"/><sheet name="B" sheetId="2" r:id="rId2"/>"#,
+            r#"<sheet name="C" sheetId="3" r:id="rId3"/></sheets><definedNames>"#,
+            r#"<definedName name="_xlnm.Print_Area" localSheetId="0">A!$A$1</definedName>"#,
+            r#"<definedName name="x" localSheetId="2">C!$A$1</definedName></definedNames></workbook>"#
+        );
+        assert_eq!(
+            reorder_sheets(xml, &[2, 0, 1]),
+            concat!(
+                r#"<workbook><bookViews><workbookView xWindow="240" activeTab="0"/></bookViews><sheets>"#,
+                r#"<sheet name="C" sheetId="3" r:id="rId3"/><sheet name="A" sheetId="1" r:id="rId1"/>"#,
+                r#"<sheet name="B" sheetId="2" r:id="rId2"/></sheets><definedNames>"#,
+                r#"<definedName name="_xlnm.Print_Area" localSheetId="1">A!$A$1</definedName>"#,
+                r#"<definedName name="x" localSheetId="0">C!$A$1</definedName></definedNames></workbook>"#
+            )
+        );
+        assert_eq!(reorder_sheets(xml, &[0, 1]), xml);
+    }
+
+    #[test]
+    fn test_reorder_titles() {
+        let xml = concat!(
+            r#"<Properties><TitlesOfParts><vt:vector size="4" baseType="lpstr">"#,
+            r#"<vt:lpstr>A</vt:lpstr><vt:lpstr>B</vt:lpstr><vt:lpstr>C</vt:lpstr>"#,
+            r#"<vt:lpstr>A!Print_Area</vt:lpstr></vt:vector></TitlesOfParts></Properties>"#
+        );
+        assert_eq!(
+            reorder_titles(xml, &[2, 0, 1]),
+            concat!(
+                r#"<Properties><TitlesOfParts><vt:vector size="4" baseType="lpstr">"#,
+                r#"<vt:lpstr>C</vt:lpstr><vt:lpstr>A</vt:lpstr><vt:lpstr>B</vt:lpstr>"#,
+                r#"<vt:lpstr>A!Print_Area</vt:lpstr></vt:vector></TitlesOfParts></Properties>"#
+            )
+        );
+        assert_eq!(reorder_titles(xml, &[0, 1, 2, 3, 4]), xml);
+        assert_eq!(reorder_titles("<Properties/>", &[1, 0]), "<Properties/>");
+    }
+}
@@ -1,16 +1,28 @@
 pub use crate::chart::{
-    Chart, ChartDashType, ChartFill, ChartLine, ChartMarkerType, ChartPattern, ChartPatternType,
-    ChartSeries, ChartType,
+    Chart, ChartDashType, ChartFill, ChartLine, ChartMarkerType, ChartOp, ChartPattern,
+    ChartPatternType, ChartSeries, ChartSeriesOp, ChartSeriesWriter, ChartType, ChartWriter,
 };
+pub use crate::evaluator::{EvaluatorOptions, FormulaValue};
 pub use crate::format::{
     Format, FormatAlignment, FormatBorder, FormatColor, FormatPatterns, FormatScript,
     FormatUnderline, FormatVerticalAlignment,
 };
-pub use crate::workbook::Workbook;
+pub use crate::limits::{NonFiniteNumberPolicy, OverflowPolicy};
+pub use crate::workbook::{
+    CalcMode, CalcOptions, CancellationToken, CloseProgress, DefinedName, DropPolicy,
+    DroppedWorkbook, Workbook,
+};
+#[cfg(feature = "rust_decimal")]
+pub use crate::worksheet::DecimalPolicy;
+#[cfg(feature = "chrono")]
+pub use crate::worksheet::TimeZonePolicy;
 pub use crate::worksheet::{
-    CommentDisplayType, CommentOptions, DateTime, GridLines, HeaderFooterOptions, ImageOptions,
-    PaperType, Protection, RowColOptions, Worksheet, WorksheetCol, WorksheetRow, LXW_DEF_COL_WIDTH,
-    LXW_DEF_COL_WIDTH_PIXELS, LXW_DEF_ROW_HEIGHT, LXW_DEF_ROW_HEIGHT_PIXELS,
+    CellError, CommentDisplayType, CommentOptions, DateEpoch, DateTime, GridLines,
+    HeaderFooterOptions, ImageOptions, PaperType, Protection, ReplayOptions, RowColOptions,
+    SheetBuffer, SheetOp, SheetTemplate, SheetWriter, TemplateValue, WorkbookWriter, Worksheet,
+    WorksheetCol, WorksheetRow, LXW_DEF_COL_WIDTH, LXW_DEF_COL_WIDTH_PIXELS, LXW_DEF_ROW_HEIGHT,
+    LXW_DEF_ROW_HEIGHT_PIXELS,
 };
 pub use crate::StringOrFloat;
 pub use crate::XlsxError;
+pub use crate::XlsxErrorKind;
@@ -0,0 +1,192 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Interval between calls of the progress callback of [`Workbook::close_with_progress`](crate::Workbook::close_with_progress).
+pub(crate) const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Signature of a local file header in a zip file.
+const LOCAL_FILE_HEADER: &[u8] = b"PK\x03\x04";
+/// Length of a local file header without the file name and the extra field.
+const LOCAL_FILE_HEADER_LENGTH: usize = 30;
+
+/// Progress of [`Workbook::close_with_progress`](crate::Workbook::close_with_progress).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CloseProgress {
+    /// Number of worksheets written to the xlsx file.
+    pub sheets_packaged: usize,
+    /// Number of worksheets in the workbook.
+    pub sheet_count: usize,
+    /// Size of the xlsx file written so far, in bytes.
+    pub bytes_written: u64,
+}
+
+/// A token to cancel [`Workbook::close_with_progress`](crate::Workbook::close_with_progress) from another thread.
+///
+/// Clones of a token share the cancellation state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token which is not cancelled.
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Request cancellation. The request cannot be withdrawn.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if [`CancellationToken::cancel`] was called on this token or its clones.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Follows the xlsx file while libxlsxwriter writes it, and counts the worksheet parts from their local file headers.
+pub(crate) struct PackageScanner {
+    path: PathBuf,
+    offset: u64,
+    /// Bytes which may contain the beginning of a local file header.
+    pending: Vec<u8>,
+    sheet_parts: usize,
+    last_part_is_sheet: bool,
+    sheet_count: usize,
+}
+
+impl PackageScanner {
+    pub(crate) fn new(path: &str, sheet_count: usize) -> Self {
+        PackageScanner {
+            path: PathBuf::from(path),
+            offset: 0,
+            pending: Vec::new(),
+            sheet_parts: 0,
+            last_part_is_sheet: false,
+            sheet_count,
+        }
+    }
+
+    /// Read the bytes appended to the file since the last call.
+    pub(crate) fn poll(&mut self) -> CloseProgress {
+        // The file doesn't exist until libxlsxwriter starts writing the package
+        if let Ok(mut file) = File::open(&self.path) {
+            let mut appended = Vec::new();
+            if file.seek(SeekFrom::Start(self.offset)).is_ok()
+                && file.read_to_end(&mut appended).is_ok()
+            {
+                self.offset += appended.len() as u64;
+                self.scan(&appended);
+            }
+        }
+        self.progress()
+    }
+
+    /// Progress after libxlsxwriter finished writing the file.
+    pub(crate) fn finish(&mut self) -> CloseProgress {
+        self.sheet_parts = self.sheet_count;
+        self.last_part_is_sheet = false;
+        self.offset = std::fs::metadata(&self.path).map_or(self.offset, |x| x.len());
+        self.progress()
+    }
+
+    fn progress(&self) -> CloseProgress {
+        // A worksheet part is complete when the next part starts
+        let sheets_packaged = self.sheet_parts - self.last_part_is_sheet as usize;
+        CloseProgress {
+            sheets_packaged: sheets_packaged.min(self.sheet_count),
+            sheet_count: self.sheet_count,
+            bytes_written: self.offset,
+        }
+    }
+
+    fn scan(&mut self, appended: &[u8]) {
+        self.pending.extend_from_slice(appended);
+        let mut position = 0;
+        while let Some(found) = find(&self.pending[position..], LOCAL_FILE_HEADER) {
+            let start = position + found;
+            let name_start = start + LOCAL_FILE_HEADER_LENGTH;
+            if name_start > self.pending.len() {
+                position = start;
+                break;
+            }
+            let name_length =
+                u16::from_le_bytes([self.pending[start + 26], self.pending[start + 27]]) as usize;
+            if name_start + name_length > self.pending.len() {
+                position = start;
+                break;
+            }
+            // Compressed data may contain the signature by chance
+            if let Ok(name) =
+                std::str::from_utf8(&self.pending[name_start..name_start + name_length])
+            {
+                if !name.is_empty() && !name.contains(char::is_control) {
+                    let is_sheet =
+                        name.starts_with("xl/worksheets/sheet") && name.ends_with(".xml");
+                    self.sheet_parts += is_sheet as usize;
+                    self.last_part_is_sheet = is_sheet;
+                }
+            }
+            position = name_start + name_length;
+        }
+        // Keep the bytes which may be the beginning of a signature split across reads
+        let keep_from = if find(&self.pending[position..], LOCAL_FILE_HEADER).is_some() {
+            position
+        } else {
+            position.max(
+                self.pending
+                    .len()
+                    .saturating_sub(LOCAL_FILE_HEADER.len() - 1),
+            )
+        };
+        self.pending.drain(..keep_from);
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    #[test]
+    fn test_scan() {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for name in [
+            "[Content_Types].xml",
+            "xl/worksheets/sheet1.xml",
+            "xl/worksheets/sheet2.xml",
+            "xl/workbook.xml",
+        ] {
+            writer
+                .start_file(name, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(&[b'x'; 100]).unwrap();
+        }
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let mut scanner = PackageScanner::new("", 2);
+        let mut sheets_packaged = Vec::new();
+        for chunk in bytes.chunks(7) {
+            scanner.scan(chunk);
+            scanner.offset += chunk.len() as u64;
+            let progress = scanner.progress();
+            if sheets_packaged.last() != Some(&progress.sheets_packaged) {
+                sheets_packaged.push(progress.sheets_packaged);
+            }
+        }
+        assert_eq!(sheets_packaged, [0, 1, 2]);
+        assert_eq!(scanner.progress().bytes_written, bytes.len() as u64);
+        assert!(scanner.pending.len() < LOCAL_FILE_HEADER.len());
+    }
+}
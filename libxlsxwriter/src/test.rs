@@ -1,3 +1,7 @@
+use crate::evaluator::FormulaValue;
+use crate::testing::{self, XlsxFile};
+use crate::utility::CellRange;
+use crate::workbook::{CancellationToken, DropPolicy, DroppedWorkbook};
 use crate::{table::*, worksheet::DateTime};
 
 use super::*;
@@ -39,10 +43,35 @@ fn test_simple1() -> Result<(), XlsxError> {
         ),
     )?;
 
-    sheet1.set_selection(1, 0, 1, 2);
-    sheet1.set_tab_color(FormatColor::Cyan);
+    sheet1.set_selection(1, 0, 1, 2)?;
+    sheet1.set_tab_color(FormatColor::Cyan)?;
     workbook.close()?;
 
+    let sheet = XlsxFile::open("../target/simple1.xlsx")?.worksheet("Sheet1")?;
+    let red_text = sheet.cell(0, 0).unwrap();
+    assert_eq!(red_text.value, FormulaValue::String("Red text".to_string()));
+    assert_eq!(red_text.style.font_color, Some(0xFF_00_00));
+    assert_eq!(sheet.value(0, 1), FormulaValue::Number(20.));
+    let formula = sheet.cell(1, 0).unwrap();
+    assert_eq!(formula.formula.as_deref(), Some("=10+B1"));
+    assert_eq!(formula.value, FormulaValue::Number(30.));
+    let url = sheet.cell(1, 1).unwrap();
+    assert_eq!(
+        url.value,
+        FormulaValue::String("https://github.com/informationsea/xlsxwriter-rs".to_string())
+    );
+    assert_eq!(url.style.underline, Some(FormatUnderline::Single));
+    assert_eq!(sheet.merged_ranges(), [CellRange::new(2, 0, 3, 2)]);
+    let merged = sheet.cell(2, 0).unwrap();
+    assert_eq!(
+        merged.value,
+        FormulaValue::String("Hello, world".to_string())
+    );
+    assert_eq!(merged.style.align, Some(FormatAlignment::CenterAcross));
+    assert_eq!(
+        merged.style.vertical_align,
+        Some(FormatVerticalAlignment::VerticalCenter)
+    );
     Ok(())
 }
 
@@ -91,6 +120,47 @@ fn test_sample1() -> Result<(), XlsxError> {
         Some(&format4),
     )?;
     workbook.close()?;
+
+    let sheet = XlsxFile::open("../target/test.xlsx")?.worksheet("Sheet1")?;
+    let hello = &sheet.cell(0, 0).unwrap().style;
+    assert_eq!(hello.font_name, "Arial");
+    assert!(hello.bold && hello.italic);
+    assert_eq!(hello.underline, Some(FormatUnderline::Single));
+    assert_eq!(sheet.cell(1, 0).unwrap().formula.as_deref(), Some("=1+2"));
+    assert_eq!(
+        sheet.value(2, 0),
+        FormulaValue::String("hello world".to_string())
+    );
+    assert_eq!(sheet.value(3, 0), FormulaValue::Number(100.));
+    assert_eq!(sheet.value(4, 0), FormulaValue::Number(5.));
+    assert_eq!(
+        sheet.cell(0, 1).unwrap().style.underline,
+        Some(FormatUnderline::Single)
+    );
+    let datetime = sheet.cell(2, 1).unwrap();
+    assert!(matches!(datetime.value, FormulaValue::Number(x) if x.floor() == 43781.));
+    assert_eq!(datetime.style.num_format, "mmm d yyyy hh:mm AM/PM");
+    Ok(())
+}
+
+#[test]
+fn test_golden_file() -> Result<(), XlsxError> {
+    let write = |path: &str, value: f64| -> Result<(), XlsxError> {
+        let workbook = Workbook::new(path)?;
+        let mut worksheet = workbook.add_worksheet(Some("Data"))?;
+        worksheet.write_string(0, 0, "Value", None)?;
+        worksheet.write_number(0, 1, value, None)?;
+        workbook.close()
+    };
+    write("../target/golden.xlsx", 1.)?;
+    write("../target/golden_same.xlsx", 1.)?;
+    write("../target/golden_other.xlsx", 2.)?;
+
+    testing::assert_matches_golden("../target/golden_same.xlsx", "../target/golden.xlsx");
+    let golden = XlsxFile::open("../target/golden.xlsx")?;
+    let differences = XlsxFile::open("../target/golden_other.xlsx")?.diff(&golden);
+    assert_eq!(differences.len(), 1);
+    assert!(differences[0].starts_with("xl/worksheets/sheet1.xml: "));
     Ok(())
 }
 
@@ -184,3 +254,301 @@ fn test_add_table2() -> Result<(), XlsxError> {
     workbook.close()?;
     Ok(())
 }
+
+#[test]
+fn test_owned_handles() -> Result<(), XlsxError> {
+    struct Report {
+        workbook: Workbook,
+        summary: Worksheet,
+        chart: Chart,
+    }
+    let workbook = Workbook::new("../target/owned_handles.xlsx")?;
+    let summary = workbook.add_worksheet(Some("Summary"))?;
+    let chart = workbook.add_chart(ChartType::Column);
+    let mut report = Report {
+        workbook,
+        summary,
+        chart,
+    };
+    report.summary.write_number(0, 0, 1., None)?;
+    report.chart.add_series(None, Some("=Summary!$A$1"))?;
+    report.summary.insert_chart(1, 1, &report.chart)?;
+    report.workbook.close()?;
+
+    let error = report.summary.write_number(1, 0, 2., None).unwrap_err();
+    assert_eq!(error.kind(), XlsxErrorKind::WorkbookClosed);
+    assert_eq!(error.sheet_name(), Some("Summary"));
+    assert_eq!(
+        report.chart.add_title("Title").unwrap_err().kind(),
+        XlsxErrorKind::WorkbookClosed
+    );
+    Ok(())
+}
+
+#[test]
+fn test_closed_workbook_handle() -> Result<(), XlsxError> {
+    let workbook = Workbook::new("../target/closed_workbook_handle.xlsx")?;
+    let worksheet = workbook.add_worksheet(None)?;
+    workbook.close()?;
+
+    let workbook = worksheet._workbook.handle();
+    let is_closed = |kind: Option<XlsxErrorKind>| kind == Some(XlsxErrorKind::WorkbookClosed);
+    assert!(is_closed(
+        workbook.add_worksheet(None).err().map(|x| x.kind())
+    ));
+    assert!(is_closed(
+        workbook
+            .add_worksheet_sanitized("A/B")
+            .err()
+            .map(|x| x.kind())
+    ));
+    assert!(is_closed(
+        workbook.get_worksheet("Sheet1").err().map(|x| x.kind())
+    ));
+    assert!(is_closed(
+        workbook.get_worksheet_by_index(0).err().map(|x| x.kind())
+    ));
+    assert!(is_closed(
+        workbook
+            .worksheets()
+            .next()
+            .and_then(|x| x.err())
+            .map(|x| x.kind())
+    ));
+    assert_eq!(workbook.worksheet_count(), 0);
+    assert!(workbook.worksheet_names().is_empty());
+    assert!(is_closed(
+        workbook.define_name("Rate", "=1").err().map(|x| x.kind())
+    ));
+    assert!(is_closed(workbook.use_1904_epoch().err().map(|x| x.kind())));
+    let mut chart = workbook.add_chart(ChartType::Column);
+    assert!(is_closed(chart.add_title("Title").err().map(|x| x.kind())));
+    Ok(())
+}
+
+#[test]
+fn test_close_with_progress() -> Result<(), XlsxError> {
+    let workbook = Workbook::new("../target/close_with_progress.xlsx")?;
+    for i in 0..3 {
+        workbook
+            .add_worksheet(None)?
+            .write_number(0, 0, i.into(), None)?;
+    }
+    let mut reports = Vec::new();
+    workbook.close_with_progress(None, |progress| reports.push(progress))?;
+    let last = reports.last().unwrap();
+    assert_eq!(last.sheets_packaged, 3);
+    assert_eq!(last.sheet_count, 3);
+    assert_eq!(
+        last.bytes_written,
+        std::fs::metadata("../target/close_with_progress.xlsx")?.len()
+    );
+    assert!(reports
+        .windows(2)
+        .all(|x| x[0].sheets_packaged <= x[1].sheets_packaged));
+    Ok(())
+}
+
+#[test]
+fn test_close_with_progress_cancelled() -> Result<(), XlsxError> {
+    use std::path::Path;
+
+    let path = "../target/close_with_progress_cancelled.xlsx";
+    let tmpdir = "../target/close_with_progress_cancelled_tmp";
+    std::fs::create_dir_all(tmpdir)?;
+    let workbook = Workbook::new_with_options(path, false, Some(tmpdir), false)?;
+    for _ in 0..3 {
+        let mut worksheet = workbook.add_worksheet(None)?;
+        for row in 0..1_000 {
+            worksheet.write_number(row, 0, row.into(), None)?;
+        }
+    }
+    // A worksheet order makes the close rewrite the package through a temporary file
+    workbook.set_worksheet_order(&["Sheet3"])?;
+    let cancellation = CancellationToken::new();
+    let mut calls = 0;
+    let result = workbook.close_with_progress(Some(&cancellation), |_| {
+        calls += 1;
+        cancellation.cancel();
+    });
+    assert_eq!(result.unwrap_err().kind(), XlsxErrorKind::Cancelled);
+    assert_eq!(calls, 1);
+    assert!(!Path::new(path).exists());
+    assert!(!Path::new(&format!("{}.tmp", path)).exists());
+    assert_eq!(std::fs::read_dir(tmpdir)?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_worksheet_order_titles() -> Result<(), XlsxError> {
+    let path = "../target/worksheet_order_titles.xlsx";
+    let workbook = Workbook::new(path)?;
+    for name in ["2023", "2024", "Summary"] {
+        workbook.add_worksheet(Some(name))?;
+    }
+    workbook.set_worksheet_order(&["Summary", "2024"])?;
+    workbook.close()?;
+
+    let file = XlsxFile::open(path)?;
+    assert_eq!(file.worksheet_names()?, ["Summary", "2024", "2023"]);
+    assert_eq!(file.titles_of_parts()?, ["Summary", "2024", "2023"]);
+    Ok(())
+}
+
+#[test]
+fn test_drop_policy() -> Result<(), XlsxError> {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let dropped = Rc::new(RefCell::new(Vec::new()));
+    let workbook = Workbook::new("../target/drop_policy_skip.xlsx")?;
+    workbook.set_drop_policy(DropPolicy::Skip);
+    let reports = dropped.clone();
+    workbook.set_drop_callback(move |x: &DroppedWorkbook| {
+        reports.borrow_mut().push((x.policy, x.result.is_ok()));
+    });
+    let worksheet = workbook.add_worksheet(None)?;
+    drop(workbook);
+    // The worksheet keeps the workbook alive
+    assert!(dropped.borrow().is_empty());
+    drop(worksheet);
+    assert_eq!(*dropped.borrow(), [(DropPolicy::Skip, true)]);
+    assert!(!std::path::Path::new("../target/drop_policy_skip.xlsx").exists());
+
+    let workbook = Workbook::new("../target/drop_policy_panic.xlsx")?;
+    workbook.set_drop_policy(DropPolicy::PanicInDebug);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(workbook)));
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+    assert!(std::path::Path::new("../target/drop_policy_panic.xlsx").exists());
+
+    let workbook = Workbook::new("../target/drop_policy_close.xlsx")?;
+    workbook.set_drop_policy(DropPolicy::PanicInDebug);
+    workbook.close()?;
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_close_async_dropped() -> Result<(), XlsxError> {
+    let workbook = Workbook::new("../target/close_async_dropped.xlsx")?;
+    let mut worksheet = workbook.add_worksheet(None)?;
+    worksheet.write_string(0, 0, "Hello", None)?;
+    drop(workbook.close_async());
+    assert!(!std::path::Path::new("../target/close_async_dropped.xlsx").exists());
+    assert_eq!(
+        worksheet.write_number(1, 0, 1., None).unwrap_err().kind(),
+        XlsxErrorKind::WorkbookClosed
+    );
+    Ok(())
+}
+
+#[test]
+fn test_worksheet_in_use() -> Result<(), XlsxError> {
+    let workbook = Workbook::new("../target/worksheet_in_use.xlsx")?;
+    let mut first = workbook.add_worksheet(Some("First"))?;
+    workbook.add_worksheet(Some("Second"))?;
+    let in_use = |result: Result<Option<Worksheet>, XlsxError>| {
+        result.err().map(|x| x.kind()) == Some(XlsxErrorKind::WorksheetInUse)
+    };
+    assert!(in_use(workbook.get_worksheet("First")));
+    assert!(in_use(workbook.get_worksheet_by_index(0)));
+    let second = workbook.get_worksheet("Second")?;
+    assert!(second.is_some());
+    assert!(in_use(workbook.get_worksheet("Second")));
+    drop(second);
+    assert_eq!(
+        workbook.worksheets().map(|x| x.is_ok()).collect::<Vec<_>>(),
+        [false, true]
+    );
+    first.write_string(0, 0, "First", None)?;
+    drop(first);
+    workbook.get_worksheet("First")?.expect("First exists");
+    workbook.close()
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn test_datetime_tz_and_duration() -> Result<(), XlsxError> {
+    use chrono::TimeZone;
+
+    let workbook = Workbook::new("../target/datetime_tz_and_duration.xlsx")?;
+    let mut worksheet = workbook.add_worksheet(None)?;
+    let instant = chrono::Utc.with_ymd_and_hms(2024, 7, 15, 12, 0, 0).unwrap();
+    let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+    worksheet.write_datetime_tz(0, 0, &instant, None, TimeZonePolicy::Convert(offset))?;
+    worksheet.write_duration(1, 0, std::time::Duration::from_secs(6 * 3600), None)?;
+    worksheet.write_duration(2, 0, chrono::Duration::hours(-36), None)?;
+    workbook.close()?;
+
+    let sheet = XlsxFile::open("../target/datetime_tz_and_duration.xlsx")?.worksheet("Sheet1")?;
+    assert_eq!(
+        sheet.value(0, 0),
+        FormulaValue::Number(
+            DateTime::new(2024, 7, 15, 14, 0, 0.).to_excel_serial(DateEpoch::Excel1900)
+        )
+    );
+    assert_eq!(sheet.value(1, 0), FormulaValue::Number(0.25));
+    assert_eq!(sheet.value(2, 0), FormulaValue::Number(-1.5));
+    assert_eq!(sheet.cell(2, 0).unwrap().style.num_format, "[h]:mm:ss");
+    Ok(())
+}
+
+#[test]
+fn test_nul_in_names() -> Result<(), XlsxError> {
+    let is_nul_error = |kind: Option<XlsxErrorKind>| kind == Some(XlsxErrorKind::NulError);
+    assert!(is_nul_error(
+        Workbook::new_with_options("../target/nul\0.xlsx", false, None, false)
+            .err()
+            .map(|x| x.kind())
+    ));
+    let workbook = Workbook::new("../target/nul_in_names.xlsx")?;
+    assert!(is_nul_error(
+        workbook.add_worksheet(Some("a\0b")).err().map(|x| x.kind())
+    ));
+    workbook.close()
+}
+
+#[test]
+fn test_row_column_error_context() -> Result<(), XlsxError> {
+    let workbook = Workbook::new("../target/row_column_error_context.xlsx")?;
+    let mut worksheet = workbook.add_worksheet(Some("Data"))?;
+    let error = worksheet.set_row(2_000_000, 20., None).unwrap_err();
+    assert_eq!(error.method(), Some("set_row"));
+    assert_eq!(error.cell(), Some((2_000_000, 0)));
+    assert!(error
+        .to_string()
+        .starts_with("Sheet 'Data'!2000001:2000001: "));
+    let error = worksheet.set_column(1, 20_000, 10., None).unwrap_err();
+    assert_eq!(error.method(), Some("set_column"));
+    assert!(error.to_string().starts_with("Sheet 'Data'!B:ACOG: "));
+    let error = worksheet.write_comment(2, 0, "a\0b").unwrap_err();
+    assert_eq!(error.kind(), XlsxErrorKind::NulError);
+    assert!(error.to_string().starts_with("Sheet 'Data'!A3: "));
+    let error = worksheet.set_header("a\0b").unwrap_err();
+    assert_eq!(error.method(), Some("set_header"));
+    assert_eq!(error.sheet_name(), Some("Data"));
+    assert_eq!(
+        workbook
+            .add_worksheet(Some("a\0b"))
+            .err()
+            .and_then(|x| x.method()),
+        Some("add_worksheet")
+    );
+    workbook.close()
+}
+
+#[test]
+fn test_cell_ref_writers() -> Result<(), XlsxError> {
+    let workbook = Workbook::new("../target/cell_ref_writers.xlsx")?;
+    let mut worksheet = workbook.add_worksheet(None)?;
+    worksheet.write_string_ref(&"B3".parse()?, "text", None)?;
+    worksheet.write_number_ref(&"$C$4".parse()?, 1.5, None)?;
+    worksheet.write_boolean_ref(&"D5".parse()?, true, None)?;
+    workbook.close()?;
+
+    let sheet = XlsxFile::open("../target/cell_ref_writers.xlsx")?.worksheet("Sheet1")?;
+    assert_eq!(sheet.value(2, 1), FormulaValue::String("text".to_string()));
+    assert_eq!(sheet.value(3, 2), FormulaValue::Number(1.5));
+    assert_eq!(sheet.value(4, 3), FormulaValue::Boolean(true));
+    Ok(())
+}
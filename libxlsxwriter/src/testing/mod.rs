@@ -0,0 +1,928 @@
+//! Read back xlsx files in tests. Enabled by the `testing` feature.
+//!
+//! [`XlsxFile`] unzips a workbook and parses its worksheets, shared strings and styles, so that a test can check
+//! what was written instead of only checking that writing succeeded:
+//! ```rust
+//! use xlsxwriter::prelude::*;
+//! use xlsxwriter::testing::XlsxFile;
+//! use xlsxwriter::utility::CellRange;
+//!
+//! # fn main() -> Result<(), XlsxError> {
+//! let workbook = Workbook::new("test-testing.xlsx")?;
+//! let mut worksheet = workbook.add_worksheet(Some("Data"))?;
+//! let bold = Format::new().set_bold().clone();
+//! worksheet.write_string(0, 0, "Total", Some(&bold))?;
+//! worksheet.write_formula_num(0, 1, "=SUM(1, 2)", None, 3.)?;
+//! worksheet.merge_range(1, 0, 1, 1, "Merged", None)?;
+//! workbook.add_defined_name(&DefinedName::new("Rate", "=0.95"))?;
+//! workbook.close()?;
+//!
+//! let file = XlsxFile::open("test-testing.xlsx")?;
+//! assert_eq!(file.worksheet_names()?, ["Data"]);
+//! let sheet = file.worksheet("Data")?;
+//! let total = sheet.cell(0, 0).unwrap();
+//! assert_eq!(total.value, FormulaValue::String("Total".to_string()));
+//! assert!(total.style.bold);
+//! assert_eq!(sheet.cell(0, 1).unwrap().formula.as_deref(), Some("=SUM(1, 2)"));
+//! assert_eq!(sheet.value(0, 1), FormulaValue::Number(3.));
+//! assert_eq!(sheet.merged_ranges(), [CellRange::new(1, 0, 1, 1)]);
+//! assert_eq!(file.defined_names()?, [DefinedName::new("Rate", "=0.95")]);
+//! # Ok(())
+//! # }
+//! ```
+//! A file can also be compared to a golden file with [`XlsxFile::diff`] or [`assert_matches_golden`].
+
+mod xml;
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use self::xml::Element;
+use crate::error::XlsxErrorSource;
+use crate::evaluator::FormulaValue;
+use crate::format::{
+    FormatAlignment, FormatBorder, FormatPatterns, FormatScript, FormatUnderline,
+    FormatVerticalAlignment,
+};
+use crate::utility::{CellRange, CellRef};
+use crate::workbook::DefinedName;
+use crate::worksheet::{CellError, WorksheetCol, WorksheetRow};
+use crate::XlsxError;
+
+/// Document properties, whose creation and modification times change with each file.
+const CORE_PROPERTIES: &str = "docProps/core.xml";
+const TIMESTAMP_TAGS: [&str; 2] = ["dcterms:created", "dcterms:modified"];
+
+/// The parts of an xlsx file. See the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct XlsxFile {
+    parts: BTreeMap<String, Vec<u8>>,
+}
+
+/// The cells, merged ranges and data validations of a worksheet.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SheetContents {
+    cells: BTreeMap<(WorksheetRow, WorksheetCol), CellContents>,
+    merged_ranges: Vec<CellRange>,
+    data_validations: Vec<DataValidationContents>,
+}
+
+/// A cell of a worksheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellContents {
+    /// Value of the cell, or the cached result of its formula. Dates are numbers.
+    pub value: FormulaValue,
+    /// Formula of the cell, starting with `=`.
+    pub formula: Option<String>,
+    /// Format of the cell.
+    pub style: CellStyle,
+}
+
+/// The format of a cell, resolved from the styles of the file. The fields are named after the [`Format`](crate::Format) setters.
+///
+/// Colors are RGB values as written in the file, so black is `0x000000`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CellStyle {
+    /// Font name, such as `Calibri`.
+    pub font_name: String,
+    /// Font size in points.
+    pub font_size: f64,
+    /// Font color, `None` for the default color.
+    pub font_color: Option<u32>,
+    /// Bold font.
+    pub bold: bool,
+    /// Italic font.
+    pub italic: bool,
+    /// Underline style.
+    pub underline: Option<FormatUnderline>,
+    /// Strikeout font.
+    pub font_strikeout: bool,
+    /// Superscript or subscript.
+    pub font_script: Option<FormatScript>,
+    /// Number format, such as `General` or `0.00`.
+    pub num_format: String,
+    /// The cell is not locked when the worksheet is protected.
+    pub unlocked: bool,
+    /// The formula is hidden when the worksheet is protected.
+    pub hidden: bool,
+    /// Horizontal alignment.
+    pub align: Option<FormatAlignment>,
+    /// Vertical alignment.
+    pub vertical_align: Option<FormatVerticalAlignment>,
+    /// Text is wrapped.
+    pub text_wrap: bool,
+    /// Text rotation in degrees, from -90 to 90, or 270 for vertical text.
+    pub rotation: i16,
+    /// Indentation level.
+    pub indent: u8,
+    /// Text is shrunk to fit the cell.
+    pub shrink: bool,
+    /// Fill pattern, `None` for no fill.
+    pub pattern: Option<FormatPatterns>,
+    /// For a solid pattern, the color of the cell.
+    pub bg_color: Option<u32>,
+    /// Foreground color of a pattern.
+    pub fg_color: Option<u32>,
+    /// Bottom border.
+    pub border_bottom: Option<FormatBorder>,
+    /// Top border.
+    pub border_top: Option<FormatBorder>,
+    /// Left border.
+    pub border_left: Option<FormatBorder>,
+    /// Right border.
+    pub border_right: Option<FormatBorder>,
+}
+
+/// A data validation of a worksheet. Types and operators are named as in the file, such as `whole` and `between`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DataValidationContents {
+    /// Validated ranges.
+    pub ranges: Vec<CellRange>,
+    /// Type of the validation, `any` when no type is written.
+    pub validation_type: String,
+    /// Operator of the validation, `between` when no operator is written.
+    pub operator: String,
+    /// First value or formula, without `=`.
+    pub formula1: Option<String>,
+    /// Second value or formula of `between` and `notBetween`, without `=`.
+    pub formula2: Option<String>,
+    /// Blank cells are valid.
+    pub ignore_blank: bool,
+    /// The input message is shown when the cell is selected.
+    pub show_input: bool,
+    /// The error alert is shown for invalid data.
+    pub show_error: bool,
+    /// Title of the input message.
+    pub input_title: Option<String>,
+    /// Text of the input message.
+    pub input_message: Option<String>,
+    /// Title of the error alert.
+    pub error_title: Option<String>,
+    /// Text of the error alert.
+    pub error_message: Option<String>,
+    /// Style of the error alert, `stop` when no style is written.
+    pub error_type: String,
+}
+
+fn invalid_part(name: &str, message: impl Display) -> XlsxError {
+    XlsxErrorSource::Io(format!("{}: {}", name, message)).into()
+}
+
+impl XlsxFile {
+    /// Read the xlsx file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, XlsxError> {
+        XlsxFile::read(File::open(path)?)
+    }
+
+    /// Read an xlsx file from memory.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, XlsxError> {
+        XlsxFile::read(std::io::Cursor::new(bytes))
+    }
+
+    fn read<R: Read + Seek>(reader: R) -> Result<Self, XlsxError> {
+        let mut archive = ZipArchive::new(reader)?;
+        let mut parts = BTreeMap::new();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            parts.insert(file.name().to_string(), content);
+        }
+        Ok(XlsxFile { parts })
+    }
+
+    /// Returns the names of the parts, such as `xl/workbook.xml`, in alphabetical order.
+    pub fn part_names(&self) -> Vec<&str> {
+        self.parts.keys().map(|x| x.as_str()).collect()
+    }
+
+    /// Returns the content of the part named `name`.
+    pub fn part(&self, name: &str) -> Option<&[u8]> {
+        self.parts.get(name).map(|x| x.as_slice())
+    }
+
+    fn xml(&self, name: &str) -> Result<Element, XlsxError> {
+        let content = self
+            .part(name)
+            .ok_or_else(|| invalid_part(name, "missing part"))?;
+        let text = std::str::from_utf8(content).map_err(|e| invalid_part(name, e))?;
+        xml::parse(text).map_err(|e| invalid_part(name, e))
+    }
+
+    /// Returns the names of the worksheets in the order of the workbook.
+    pub fn worksheet_names(&self) -> Result<Vec<String>, XlsxError> {
+        Ok(self.sheets()?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Returns the titles of the parts listed in `docProps/app.xml`: the worksheet names in the order of the workbook,
+    /// followed by the names of print areas and other defined names.
+    pub fn titles_of_parts(&self) -> Result<Vec<String>, XlsxError> {
+        let app = self.xml("docProps/app.xml")?;
+        Ok(app
+            .child("TitlesOfParts")
+            .and_then(|x| x.child("vt:vector"))
+            .into_iter()
+            .flat_map(|x| x.children("vt:lpstr"))
+            .map(|x| x.text())
+            .collect())
+    }
+
+    /// The names and relationship ids of the sheets.
+    fn sheets(&self) -> Result<Vec<(String, String)>, XlsxError> {
+        let workbook = self.xml("xl/workbook.xml")?;
+        Ok(workbook
+            .child("sheets")
+            .into_iter()
+            .flat_map(|x| x.children("sheet"))
+            .map(|x| {
+                (
+                    x.attr("name").unwrap_or_default().to_string(),
+                    x.attr("r:id").unwrap_or_default().to_string(),
+                )
+            })
+            .collect())
+    }
+
+    /// Read the worksheet named `sheet_name`, ignoring case.
+    pub fn worksheet(&self, sheet_name: &str) -> Result<SheetContents, XlsxError> {
+        let id = self
+            .sheets()?
+            .into_iter()
+            .find(|(name, _)| name.to_lowercase() == sheet_name.to_lowercase())
+            .map(|(_, id)| id)
+            .ok_or_else(|| XlsxErrorSource::UnknownWorksheet(sheet_name.to_string()))?;
+        let relationships = self.xml("xl/_rels/workbook.xml.rels")?;
+        let target = relationships
+            .children("Relationship")
+            .find(|x| x.attr("Id") == Some(id.as_str()))
+            .and_then(|x| x.attr("Target"))
+            .ok_or_else(|| invalid_part("xl/_rels/workbook.xml.rels", format!("no {}", id)))?;
+        let path = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{}", target),
+        };
+
+        let shared_strings = if self.part("xl/sharedStrings.xml").is_some() {
+            self.xml("xl/sharedStrings.xml")?
+                .children("si")
+                .map(|x| x.string_text())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let styles = if self.part("xl/styles.xml").is_some() {
+            parse_styles(&self.xml("xl/styles.xml")?)
+        } else {
+            Vec::new()
+        };
+        parse_sheet(&path, &self.xml(&path)?, &shared_strings, &styles)
+    }
+
+    /// Returns the defined names of the workbook, including the names libxlsxwriter writes for autofilters and print areas
+    /// such as `_xlnm._FilterDatabase`.
+    pub fn defined_names(&self) -> Result<Vec<DefinedName>, XlsxError> {
+        let sheets = self.sheets()?;
+        let workbook = self.xml("xl/workbook.xml")?;
+        Ok(workbook
+            .child("definedNames")
+            .into_iter()
+            .flat_map(|x| x.children("definedName"))
+            .map(|x| {
+                let name = DefinedName::new(
+                    x.attr("name").unwrap_or_default(),
+                    &format!("={}", x.text()),
+                )
+                .hidden(x.attr("hidden") == Some("1"));
+                let sheet = x
+                    .attr("localSheetId")
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .and_then(|x| sheets.get(x));
+                match sheet {
+                    Some((sheet_name, _)) => name.local(sheet_name),
+                    None => name,
+                }
+            })
+            .collect())
+    }
+
+    /// Compare the file with `golden`, ignoring the creation and modification times of `docProps/core.xml`.
+    /// Returns a description of each differing part, which is empty if the files are the same.
+    ///
+    /// XML parts are compared tag by tag, and the first differing tag is described.
+    pub fn diff(&self, golden: &XlsxFile) -> Vec<String> {
+        let mut differences = Vec::new();
+        for name in golden.parts.keys() {
+            if !self.parts.contains_key(name) {
+                differences.push(format!("{}: missing", name));
+            }
+        }
+        for (name, content) in &self.parts {
+            match golden.parts.get(name) {
+                Some(expected) => differences.extend(diff_part(name, content, expected)),
+                None => differences.push(format!("{}: not in the golden file", name)),
+            }
+        }
+        differences
+    }
+}
+
+/// Compare the xlsx file at `path` with the golden file at `golden_path` with [`XlsxFile::diff`].
+///
+/// # Panics
+/// Panics with the differences if the files differ or can't be read.
+pub fn assert_matches_golden<P: AsRef<Path>, Q: AsRef<Path>>(path: P, golden_path: Q) {
+    let (path, golden_path) = (path.as_ref(), golden_path.as_ref());
+    let file =
+        XlsxFile::open(path).unwrap_or_else(|e| panic!("Cannot read {}: {}", path.display(), e));
+    let golden = XlsxFile::open(golden_path)
+        .unwrap_or_else(|e| panic!("Cannot read {}: {}", golden_path.display(), e));
+    let differences = file.diff(&golden);
+    if !differences.is_empty() {
+        panic!(
+            "{} differs from {}:\n{}",
+            path.display(),
+            golden_path.display(),
+            differences.join("\n")
+        );
+    }
+}
+
+fn diff_part(name: &str, content: &[u8], expected: &[u8]) -> Option<String> {
+    if content == expected {
+        return None;
+    }
+    let is_xml = name.ends_with(".xml") || name.ends_with(".rels");
+    let (found, expected) = match (std::str::from_utf8(content), std::str::from_utf8(expected)) {
+        (Ok(found), Ok(expected)) if is_xml => (found, expected),
+        _ => return Some(format!("{}: the contents differ", name)),
+    };
+    let (found, expected) = if name == CORE_PROPERTIES {
+        (clear_timestamps(found), clear_timestamps(expected))
+    } else {
+        (found.to_string(), expected.to_string())
+    };
+    let found_tags = split_tags(&found);
+    let expected_tags = split_tags(&expected);
+    (0..found_tags.len().max(expected_tags.len())).find_map(|i| {
+        let (found, expected) = (found_tags.get(i), expected_tags.get(i));
+        if found == expected {
+            return None;
+        }
+        Some(format!(
+            "{}: tag {}: expected `{}`, found `{}`",
+            name,
+            i + 1,
+            expected.map_or("end of part", |x| x.as_str()),
+            found.map_or("end of part", |x| x.as_str()),
+        ))
+    })
+}
+
+/// Split XML into its tags, each with the text which follows it.
+fn split_tags(xml: &str) -> Vec<String> {
+    xml.replace("><", ">\n<")
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// Remove the contents of the timestamp tags of `docProps/core.xml`.
+fn clear_timestamps(xml: &str) -> String {
+    let mut xml = xml.to_string();
+    for tag in TIMESTAMP_TAGS {
+        let start = xml
+            .find(&format!("<{}", tag))
+            .and_then(|start| xml[start..].find('>').map(|x| start + x + 1));
+        if let Some(start) = start {
+            if let Some(end) = xml[start..].find(&format!("</{}>", tag)) {
+                xml.replace_range(start..start + end, "");
+            }
+        }
+    }
+    xml
+}
+
+impl SheetContents {
+    /// Returns the cell at `row`, `col`, or `None` if nothing is written there.
+    pub fn cell(&self, row: WorksheetRow, col: WorksheetCol) -> Option<&CellContents> {
+        self.cells.get(&(row, col))
+    }
+
+    /// Returns the value of the cell at `row`, `col`, which is [`FormulaValue::Empty`] if nothing is written there.
+    pub fn value(&self, row: WorksheetRow, col: WorksheetCol) -> FormulaValue {
+        self.cell(row, col)
+            .map_or(FormulaValue::Empty, |x| x.value.clone())
+    }
+
+    /// Returns the cells which are written, by row and then by column.
+    pub fn cells(&self) -> impl Iterator<Item = (WorksheetRow, WorksheetCol, &CellContents)> + '_ {
+        self.cells.iter().map(|(&(row, col), x)| (row, col, x))
+    }
+
+    /// Returns the merged ranges.
+    pub fn merged_ranges(&self) -> &[CellRange] {
+        &self.merged_ranges
+    }
+
+    /// Returns the data validations.
+    pub fn data_validations(&self) -> &[DataValidationContents] {
+        &self.data_validations
+    }
+}
+
+fn parse_sheet(
+    name: &str,
+    root: &Element,
+    shared_strings: &[String],
+    styles: &[CellStyle],
+) -> Result<SheetContents, XlsxError> {
+    let mut contents = SheetContents::default();
+    for cell in root
+        .child("sheetData")
+        .into_iter()
+        .flat_map(|x| x.children("row"))
+        .flat_map(|x| x.children("c"))
+    {
+        let reference: CellRef = cell
+            .attr("r")
+            .ok_or_else(|| invalid_part(name, "cell without a reference"))?
+            .parse()?;
+        let text = cell.child("v").map(|x| x.text());
+        let value = match (cell.attr("t"), text) {
+            (Some("inlineStr"), _) => FormulaValue::String(
+                cell.child("is")
+                    .map(|x| x.string_text())
+                    .unwrap_or_default(),
+            ),
+            (_, None) => FormulaValue::Empty,
+            (Some("s"), Some(text)) => text
+                .parse::<usize>()
+                .ok()
+                .and_then(|x| shared_strings.get(x))
+                .map(|x| FormulaValue::String(x.clone()))
+                .ok_or_else(|| invalid_part(name, format!("invalid string index {}", text)))?,
+            (Some("str"), Some(text)) => FormulaValue::String(text),
+            (Some("b"), Some(text)) => FormulaValue::Boolean(text == "1"),
+            (Some("e"), Some(text)) => FormulaValue::Error(
+                parse_cell_error(&text)
+                    .ok_or_else(|| invalid_part(name, format!("invalid error {}", text)))?,
+            ),
+            (_, Some(text)) => FormulaValue::Number(
+                text.parse()
+                    .map_err(|_| invalid_part(name, format!("invalid number {}", text)))?,
+            ),
+        };
+        let style = cell
+            .attr("s")
+            .and_then(|x| x.parse::<usize>().ok())
+            .unwrap_or(0);
+        contents.cells.insert(
+            (reference.row, reference.col),
+            CellContents {
+                value,
+                formula: cell.child("f").map(|x| format!("={}", x.text())),
+                style: styles.get(style).cloned().unwrap_or_default(),
+            },
+        );
+    }
+
+    for range in root
+        .child("mergeCells")
+        .into_iter()
+        .flat_map(|x| x.children("mergeCell"))
+        .filter_map(|x| x.attr("ref"))
+    {
+        contents.merged_ranges.push(range.parse()?);
+    }
+
+    for validation in root
+        .child("dataValidations")
+        .into_iter()
+        .flat_map(|x| x.children("dataValidation"))
+    {
+        let ranges = validation
+            .attr("sqref")
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|x| x.parse())
+            .collect::<Result<_, _>>()?;
+        let text = |name: &str| validation.attr(name).map(|x| x.to_string());
+        let flag = |name: &str| validation.attr(name) == Some("1");
+        contents.data_validations.push(DataValidationContents {
+            ranges,
+            validation_type: text("type").unwrap_or_else(|| "any".to_string()),
+            operator: text("operator").unwrap_or_else(|| "between".to_string()),
+            formula1: validation.child("formula1").map(|x| x.text()),
+            formula2: validation.child("formula2").map(|x| x.text()),
+            ignore_blank: flag("allowBlank"),
+            show_input: flag("showInputMessage"),
+            show_error: flag("showErrorMessage"),
+            input_title: text("promptTitle"),
+            input_message: text("prompt"),
+            error_title: text("errorTitle"),
+            error_message: text("error"),
+            error_type: text("errorStyle").unwrap_or_else(|| "stop".to_string()),
+        });
+    }
+    Ok(contents)
+}
+
+fn parse_cell_error(text: &str) -> Option<CellError> {
+    [
+        CellError::Null,
+        CellError::Div0,
+        CellError::Value,
+        CellError::Ref,
+        CellError::Name,
+        CellError::Num,
+        CellError::NA,
+    ]
+    .iter()
+    .copied()
+    .find(|x| x.as_str() == text)
+}
+
+/// Resolve the cell formats (`cellXfs`) of `xl/styles.xml`.
+fn parse_styles(root: &Element) -> Vec<CellStyle> {
+    let items = |list: &str, item: &'static str| -> Vec<Element> {
+        root.child(list)
+            .into_iter()
+            .flat_map(|x| x.children(item))
+            .cloned()
+            .collect()
+    };
+    let fonts = items("fonts", "font");
+    let fills = items("fills", "fill");
+    let borders = items("borders", "border");
+    let num_formats: BTreeMap<usize, String> = items("numFmts", "numFmt")
+        .iter()
+        .filter_map(|x| {
+            Some((
+                x.attr("numFmtId")?.parse().ok()?,
+                x.attr("formatCode")?.to_string(),
+            ))
+        })
+        .collect();
+
+    items("cellXfs", "xf")
+        .iter()
+        .map(|xf| {
+            let index = |name: &str| {
+                xf.attr(name)
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .unwrap_or(0)
+            };
+            let mut style = CellStyle::default();
+            if let Some(font) = fonts.get(index("fontId")) {
+                read_font(font, &mut style);
+            }
+            if let Some(pattern) = fills
+                .get(index("fillId"))
+                .and_then(|x| x.child("patternFill"))
+            {
+                read_fill(pattern, &mut style);
+            }
+            if let Some(border) = borders.get(index("borderId")) {
+                let side = |name: &str| {
+                    border
+                        .child(name)
+                        .and_then(|x| x.attr("style"))
+                        .and_then(border_style)
+                };
+                style.border_bottom = side("bottom");
+                style.border_top = side("top");
+                style.border_left = side("left");
+                style.border_right = side("right");
+            }
+            let num_format = index("numFmtId");
+            style.num_format = num_formats
+                .get(&num_format)
+                .cloned()
+                .or_else(|| builtin_num_format(num_format).map(|x| x.to_string()))
+                .unwrap_or_default();
+            if let Some(alignment) = xf.child("alignment") {
+                read_alignment(alignment, &mut style);
+            }
+            if let Some(protection) = xf.child("protection") {
+                style.unlocked = protection.attr("locked") == Some("0");
+                style.hidden = protection.attr("hidden") == Some("1");
+            }
+            style
+        })
+        .collect()
+}
+
+fn color(element: Option<&Element>) -> Option<u32> {
+    element?
+        .attr("rgb")
+        .and_then(|x| u32::from_str_radix(x, 16).ok())
+        .map(|x| x & 0xFF_FF_FF)
+}
+
+fn read_font(font: &Element, style: &mut CellStyle) {
+    let value = |name: &str| font.child(name).and_then(|x| x.attr("val"));
+    style.font_name = value("name").unwrap_or_default().to_string();
+    style.font_size = value("sz").and_then(|x| x.parse().ok()).unwrap_or_default();
+    style.font_color = color(font.child("color"));
+    style.bold = font.child("b").is_some();
+    style.italic = font.child("i").is_some();
+    style.font_strikeout = font.child("strike").is_some();
+    style.underline = font.child("u").map(|x| match x.attr("val") {
+        Some("double") => FormatUnderline::Double,
+        Some("singleAccounting") => FormatUnderline::SingleAccounting,
+        Some("doubleAccounting") => FormatUnderline::DoubleAccounting,
+        _ => FormatUnderline::Single,
+    });
+    style.font_script = match value("vertAlign") {
+        Some("superscript") => Some(FormatScript::SuperScript),
+        Some("subscript") => Some(FormatScript::SubScript),
+        _ => None,
+    };
+}
+
+fn read_fill(pattern: &Element, style: &mut CellStyle) {
+    style.pattern = pattern.attr("patternType").and_then(pattern_type);
+    let fg_color = color(pattern.child("fgColor"));
+    let bg_color = color(pattern.child("bgColor"));
+    // libxlsxwriter writes the background color of a solid fill as its foreground color
+    if style.pattern == Some(FormatPatterns::Solid) {
+        style.bg_color = fg_color;
+        style.fg_color = bg_color;
+    } else {
+        style.bg_color = bg_color;
+        style.fg_color = fg_color;
+    }
+}
+
+fn read_alignment(alignment: &Element, style: &mut CellStyle) {
+    style.align = match alignment.attr("horizontal") {
+        Some("left") => Some(FormatAlignment::Left),
+        Some("center") => Some(FormatAlignment::Center),
+        Some("right") => Some(FormatAlignment::Right),
+        Some("fill") => Some(FormatAlignment::Fill),
+        Some("justify") => Some(FormatAlignment::Justify),
+        Some("centerContinuous") => Some(FormatAlignment::CenterAcross),
+        Some("distributed") => Some(FormatAlignment::Distributed),
+        _ => None,
+    };
+    style.vertical_align = match alignment.attr("vertical") {
+        Some("top") => Some(FormatVerticalAlignment::VerticalTop),
+        Some("bottom") => Some(FormatVerticalAlignment::VerticalBottom),
+        Some("center") => Some(FormatVerticalAlignment::VerticalCenter),
+        Some("justify") => Some(FormatVerticalAlignment::VerticalJustify),
+        Some("distributed") => Some(FormatVerticalAlignment::VerticalDistributed),
+        _ => None,
+    };
+    style.text_wrap = alignment.attr("wrapText") == Some("1");
+    style.shrink = alignment.attr("shrinkToFit") == Some("1");
+    style.indent = alignment
+        .attr("indent")
+        .and_then(|x| x.parse().ok())
+        .unwrap_or_default();
+    // Angles from -1 to -90 are stored as 91 to 180, and vertical text as 255
+    style.rotation = match alignment.attr("textRotation").and_then(|x| x.parse().ok()) {
+        Some(255) => 270,
+        Some(angle @ 91..=180) => 90 - angle,
+        Some(angle) => angle,
+        None => 0,
+    };
+}
+
+fn pattern_type(name: &str) -> Option<FormatPatterns> {
+    let pattern = match name {
+        "solid" => FormatPatterns::Solid,
+        "mediumGray" => FormatPatterns::MediumGray,
+        "darkGray" => FormatPatterns::DarkGray,
+        "lightGray" => FormatPatterns::LightGray,
+        "darkHorizontal" => FormatPatterns::DarkHorizontal,
+        "darkVertical" => FormatPatterns::DarkVertical,
+        "darkDown" => FormatPatterns::DarkDown,
+        "darkUp" => FormatPatterns::DarkUp,
+        "darkGrid" => FormatPatterns::DarkGrid,
+        "darkTrellis" => FormatPatterns::DarkTrellis,
+        "lightHorizontal" => FormatPatterns::LightHorizontal,
+        "lightVertical" => FormatPatterns::LightVertical,
+        "lightDown" => FormatPatterns::LightDown,
+        "lightUp" => FormatPatterns::LightUp,
+        "lightGrid" => FormatPatterns::LightGrid,
+        "lightTrellis" => FormatPatterns::LightTrellis,
+        "gray125" => FormatPatterns::Gray125,
+        "gray0625" => FormatPatterns::Gray0625,
+        _ => return None,
+    };
+    Some(pattern)
+}
+
+fn border_style(name: &str) -> Option<FormatBorder> {
+    let border = match name {
+        "thin" => FormatBorder::Thin,
+        "medium" => FormatBorder::Medium,
+        "dashed" => FormatBorder::Dashed,
+        "dotted" => FormatBorder::Dotted,
+        "thick" => FormatBorder::Thick,
+        "double" => FormatBorder::Double,
+        "hair" => FormatBorder::Hair,
+        "mediumDashed" => FormatBorder::MediumDashed,
+        "dashDot" => FormatBorder::DashDot,
+        "mediumDashDot" => FormatBorder::MediumDashDot,
+        "dashDotDot" => FormatBorder::DashDotDot,
+        "mediumDashDotDot" => FormatBorder::MediumDashDotDot,
+        "slantDashDot" => FormatBorder::SlantDashDot,
+        _ => return None,
+    };
+    Some(border)
+}
+
+/// Number formats which Excel knows by their id, and which are not written to the file.
+fn builtin_num_format(id: usize) -> Option<&'static str> {
+    let format = match id {
+        0 => "General",
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        5 => "($#,##0_);($#,##0)",
+        6 => "($#,##0_);[Red]($#,##0)",
+        7 => "($#,##0.00_);($#,##0.00)",
+        8 => "($#,##0.00_);[Red]($#,##0.00)",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        14 => "m/d/yy",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "m/d/yy h:mm",
+        37 => "(#,##0_);(#,##0)",
+        38 => "(#,##0_);[Red](#,##0)",
+        39 => "(#,##0.00_);(#,##0.00)",
+        40 => "(#,##0.00_);[Red](#,##0.00)",
+        41 => "_(* #,##0_);_(* (#,##0);_(* \"-\"_);_(@_)",
+        42 => "_($* #,##0_);_($* (#,##0);_($* \"-\"_);_(@_)",
+        43 => "_(* #,##0.00_);_(* (#,##0.00);_(* \"-\"??_);_(@_)",
+        44 => "_($* #,##0.00_);_($* (#,##0.00);_($* \"-\"??_);_(@_)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mm:ss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => return None,
+    };
+    Some(format)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(parts: &[(&str, &str)]) -> XlsxFile {
+        XlsxFile {
+            parts: parts
+                .iter()
+                .map(|(name, content)| (name.to_string(), content.as_bytes().to_vec()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let core = |time: &str| {
+            format!(
+                "<cp:coreProperties><dc:creator>a</dc:creator><dcterms:created xsi:type=\"dcterms:W3CDTF\">{0}</dcterms:created><dcterms:modified xsi:type=\"dcterms:W3CDTF\">{0}</dcterms:modified></cp:coreProperties>",
+                time
+            )
+        };
+        let golden = file(&[
+            (CORE_PROPERTIES, &core("2020-01-01T00:00:00Z")),
+            (
+                "xl/worksheets/sheet1.xml",
+                "<worksheet><c r=\"A1\"><v>1</v></c></worksheet>",
+            ),
+            ("xl/media/image1.png", "png"),
+        ]);
+        let same = file(&[
+            (CORE_PROPERTIES, &core("2024-05-06T07:08:09Z")),
+            (
+                "xl/worksheets/sheet1.xml",
+                "<worksheet><c r=\"A1\"><v>1</v></c></worksheet>",
+            ),
+            ("xl/media/image1.png", "png"),
+        ]);
+        assert!(same.diff(&golden).is_empty());
+
+        let other = file(&[
+            (
+                CORE_PROPERTIES,
+                &core("2024-05-06T07:08:09Z").replace(">a<", ">b<"),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                "<worksheet><c r=\"A1\"><v>2</v></c></worksheet>",
+            ),
+            ("xl/sharedStrings.xml", "<sst/>"),
+        ]);
+        assert_eq!(
+            other.diff(&golden),
+            [
+                "xl/media/image1.png: missing",
+                "docProps/core.xml: tag 2: expected `<dc:creator>a</dc:creator>`, found `<dc:creator>b</dc:creator>`",
+                "xl/sharedStrings.xml: not in the golden file",
+                "xl/worksheets/sheet1.xml: tag 3: expected `<v>1</v>`, found `<v>2</v>`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_worksheet() {
+        let file = file(&[
+            (
+                "xl/workbook.xml",
+                r#"<workbook><sheets><sheet name="Data" sheetId="1" r:id="rId1"/></sheets><definedNames><definedName name="_xlnm._FilterDatabase" localSheetId="0" hidden="1">Data!$A$1:$B$1</definedName></definedNames></workbook>"#,
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            ),
+            ("xl/sharedStrings.xml", "<sst><si><t>Total</t></si></sst>"),
+            (
+                "xl/styles.xml",
+                r#"<styleSheet><numFmts><numFmt numFmtId="164" formatCode="0.000"/></numFmts><fonts><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><color rgb="FFFF0000"/><name val="Calibri"/></font></fonts><fills><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill><fill><patternFill patternType="solid"><fgColor rgb="FFFFFF00"/><bgColor indexed="64"/></patternFill></fill></fills><borders><border><left/><right/></border><border><left style="thin"/><bottom style="double"/></border></borders><cellXfs><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/><xf numFmtId="164" fontId="1" fillId="2" borderId="1"><alignment horizontal="center" textRotation="135" wrapText="1"/><protection locked="0"/></xf></cellXfs></styleSheet>"#,
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                r#"<worksheet><sheetData><row r="1"><c r="A1" s="1" t="s"><v>0</v></c><c r="B1"><f>SUM(1,2)</f><v>3</v></c><c r="C1" t="e"><f>1/0</f><v>#DIV/0!</v></c></row><row r="2"><c r="A2" t="b"><v>1</v></c><c r="B2" s="1"/></row></sheetData><mergeCells count="1"><mergeCell ref="D1:E2"/></mergeCells><dataValidations count="1"><dataValidation type="whole" operator="greaterThan" allowBlank="1" showErrorMessage="1" error="Too small" sqref="A3 B4:B5"><formula1>10</formula1></dataValidation></dataValidations></worksheet>"#,
+            ),
+        ]);
+        assert_eq!(file.worksheet_names().unwrap(), ["Data"]);
+        assert!(file.worksheet("Missing").is_err());
+        let sheet = file.worksheet("data").unwrap();
+
+        let total = sheet.cell(0, 0).unwrap();
+        assert_eq!(total.value, FormulaValue::String("Total".to_string()));
+        assert_eq!(total.formula, None);
+        assert_eq!(
+            total.style,
+            CellStyle {
+                font_name: "Calibri".to_string(),
+                font_size: 11.,
+                font_color: Some(0xFF_00_00),
+                bold: true,
+                num_format: "0.000".to_string(),
+                unlocked: true,
+                align: Some(FormatAlignment::Center),
+                text_wrap: true,
+                rotation: -45,
+                pattern: Some(FormatPatterns::Solid),
+                bg_color: Some(0xFF_FF_00),
+                border_bottom: Some(FormatBorder::Double),
+                border_left: Some(FormatBorder::Thin),
+                ..CellStyle::default()
+            }
+        );
+        assert_eq!(
+            sheet.cell(0, 1).unwrap().formula.as_deref(),
+            Some("=SUM(1,2)")
+        );
+        assert_eq!(sheet.value(0, 1), FormulaValue::Number(3.));
+        assert_eq!(sheet.cell(0, 1).unwrap().style.num_format, "General");
+        assert_eq!(sheet.value(0, 2), FormulaValue::Error(CellError::Div0));
+        assert_eq!(sheet.value(1, 0), FormulaValue::Boolean(true));
+        assert_eq!(sheet.value(1, 1), FormulaValue::Empty);
+        assert!(sheet.cell(1, 1).unwrap().style.bold);
+        assert_eq!(sheet.cell(5, 5), None);
+        assert_eq!(sheet.cells().count(), 5);
+
+        assert_eq!(sheet.merged_ranges(), [CellRange::new(0, 3, 1, 4)]);
+        assert_eq!(
+            sheet.data_validations(),
+            [DataValidationContents {
+                ranges: vec![CellRange::new(2, 0, 2, 0), CellRange::new(3, 1, 4, 1)],
+                validation_type: "whole".to_string(),
+                operator: "greaterThan".to_string(),
+                formula1: Some("10".to_string()),
+                ignore_blank: true,
+                show_error: true,
+                error_message: Some("Too small".to_string()),
+                error_type: "stop".to_string(),
+                ..DataValidationContents::default()
+            }]
+        );
+        assert_eq!(
+            file.defined_names().unwrap(),
+            [DefinedName::new("_xlnm._FilterDatabase", "=Data!$A$1:$B$1")
+                .local("Data")
+                .hidden(true)]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! A small XML parser for the parts of an xlsx file. Namespace prefixes are kept as a part of the names.
+
+/// An XML element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Element {
+    pub(crate) name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    pub(crate) fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(x, _)| x == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The first child element named `name`.
+    pub(crate) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|x| match x {
+            Node::Element(element) if element.name == name => Some(element),
+            _ => None,
+        })
+    }
+
+    /// The child elements named `name`.
+    pub(crate) fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter_map(move |x| match x {
+            Node::Element(element) if element.name == name => Some(element),
+            _ => None,
+        })
+    }
+
+    /// The text of the `<t>` descendants, which hold the text of a string or a rich string. Phonetic runs are skipped.
+    pub(crate) fn string_text(&self) -> String {
+        let mut text = String::new();
+        self.collect_string_text(&mut text);
+        text
+    }
+
+    fn collect_string_text(&self, text: &mut String) {
+        for child in &self.children {
+            if let Node::Element(element) = child {
+                if element.name == "t" {
+                    text.push_str(&element.text());
+                } else if element.name != "rPh" {
+                    element.collect_string_text(text);
+                }
+            }
+        }
+    }
+
+    /// The text directly in the element.
+    pub(crate) fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|x| match x {
+                Node::Text(text) => Some(text.as_str()),
+                Node::Element(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Parse `xml` into its root element.
+pub(crate) fn parse(xml: &str) -> Result<Element, String> {
+    let mut stack = vec![Element::default()];
+    let mut rest = xml;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<?") {
+            rest = skip_past(after, "?>")?;
+        } else if let Some(after) = rest.strip_prefix("<!--") {
+            rest = skip_past(after, "-->")?;
+        } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or("Unclosed CDATA section")?;
+            push_text(&mut stack, after[..end].to_string());
+            rest = &after[end + 3..];
+        } else if let Some(after) = rest.strip_prefix("<!") {
+            rest = skip_past(after, ">")?;
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let end = after.find('>').ok_or("Unclosed end tag")?;
+            let name = after[..end].trim();
+            let element = stack.pop().filter(|_| !stack.is_empty());
+            match element {
+                Some(element) if element.name == name => {
+                    push_element(&mut stack, element);
+                }
+                _ => return Err(format!("Unexpected end tag: {}", name)),
+            }
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix('<') {
+            let (element, closed, after) = parse_start_tag(after)?;
+            if closed {
+                push_element(&mut stack, element);
+            } else {
+                stack.push(element);
+            }
+            rest = after;
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            push_text(&mut stack, unescape(&rest[..end])?);
+            rest = &rest[end..];
+        }
+    }
+    if stack.len() != 1 {
+        return Err("Unclosed element".to_string());
+    }
+    stack
+        .pop()
+        .and_then(|root| {
+            root.children.into_iter().find_map(|x| match x {
+                Node::Element(element) => Some(element),
+                Node::Text(_) => None,
+            })
+        })
+        .ok_or_else(|| "No root element".to_string())
+}
+
+fn skip_past<'a>(text: &'a str, end: &str) -> Result<&'a str, String> {
+    text.find(end)
+        .map(|i| &text[i + end.len()..])
+        .ok_or_else(|| format!("Missing {}", end))
+}
+
+fn push_element(stack: &mut [Element], element: Element) {
+    if let Some(parent) = stack.last_mut() {
+        parent.children.push(Node::Element(element));
+    }
+}
+
+fn push_text(stack: &mut [Element], text: String) {
+    // Text outside the root element is whitespace
+    if stack.len() > 1 {
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(Node::Text(text));
+        }
+    }
+}
+
+/// Parse a start tag after `<`. Returns the element, whether it is self-closing, and the rest of the text.
+fn parse_start_tag(text: &str) -> Result<(Element, bool, &str), String> {
+    let is_delimiter = |x: char| x.is_whitespace() || x == '/' || x == '>';
+    let name_end = text.find(is_delimiter).ok_or("Unclosed start tag")?;
+    let mut element = Element {
+        name: text[..name_end].to_string(),
+        ..Element::default()
+    };
+    let mut rest = &text[name_end..];
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return Ok((element, true, after));
+        } else if let Some(after) = rest.strip_prefix('>') {
+            return Ok((element, false, after));
+        }
+        let equal = rest.find('=').ok_or("Invalid attribute")?;
+        let name = rest[..equal].trim().to_string();
+        let value = rest[equal + 1..].trim_start();
+        let quote = value.chars().next().filter(|x| *x == '"' || *x == '\'');
+        let quote = quote.ok_or("Unquoted attribute value")?;
+        let end = value[1..].find(quote).ok_or("Unclosed attribute value")?;
+        element
+            .attributes
+            .push((name, unescape(&value[1..end + 1])?));
+        rest = &value[end + 2..];
+    }
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or("Unclosed entity")? + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32)
+                    .ok_or_else(|| format!("Unknown entity: {}", entity))?
+            }
+        };
+        result.push(c);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let root = parse(concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+            "\n<!-- comment --><sst count='2'><si><t xml:space=\"preserve\"> a &amp; b </t></si>",
+            "<si><r><rPr><b/></rPr><t>x&#x41;</t></r><r><t>&lt;y&#66;&gt;</t></r></si></sst>\n"
+        ))
+        .unwrap();
+        assert_eq!(root.name, "sst");
+        assert_eq!(root.attr("count"), Some("2"));
+        let items: Vec<_> = root.children("si").map(|x| x.string_text()).collect();
+        assert_eq!(items, [" a & b ", "xA<yB>"]);
+        assert_eq!(
+            root.child("si")
+                .and_then(|x| x.child("t"))
+                .and_then(|x| x.attr("xml:space")),
+            Some("preserve")
+        );
+
+        assert!(parse("<a><b></a>").is_err());
+        assert!(parse("<a>").is_err());
+        assert!(parse("<a x=1/>").is_err());
+        assert!(parse("").is_err());
+    }
+}
@@ -0,0 +1,436 @@
+//! Cell references and ranges in A1 notation.
+//!
+//! [`CellRef`] is a cell such as `A1` or `$A$1`, and [`CellRange`] is a range such as `A1:C10` or `'My Sheet'!$A$1:$B$2`.
+//! Both can be parsed with [`str::parse`] and formatted with [`ToString::to_string`].
+//! Worksheet methods which take a range, such as [`Worksheet::merge_range`](crate::Worksheet::merge_range), have a variant
+//! ending with `_ref` which takes a [`CellRange`], and methods which take a cell, such as
+//! [`Worksheet::write_string`](crate::Worksheet::write_string), have one which takes a [`CellRef`].
+//! ```rust
+//! use xlsxwriter::utility::{CellRange, CellRef};
+//! # fn main() -> Result<(), xlsxwriter::XlsxError> {
+//! let cell: CellRef = "$B$3".parse()?;
+//! assert_eq!((cell.row, cell.col), (2, 1));
+//!
+//! let range = CellRange::new(0, 1, 4, 1).absolute().with_sheet_name("Sales 2024");
+//! assert_eq!(range.to_string(), "'Sales 2024'!$B$1:$B$5");
+//! assert_eq!(range.to_formula(), "='Sales 2024'!$B$1:$B$5");
+//! assert_eq!("'Sales 2024'!$B$1:$B$5".parse::<CellRange>()?, range);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::XlsxErrorSource;
+use crate::limits;
+use crate::{WorksheetCol, WorksheetRow, XlsxError};
+
+/// Convert a zero indexed column number to its name, such as `0` to `A` and `27` to `AB`.
+pub fn col_to_name(col: WorksheetCol) -> String {
+    let mut letters = Vec::new();
+    let mut col = u32::from(col) + 1;
+    while col > 0 {
+        letters.push(b'A' + ((col - 1) % 26) as u8);
+        col = (col - 1) / 26;
+    }
+    letters.reverse();
+    String::from_utf8_lossy(&letters).into_owned()
+}
+
+/// Convert a column name such as `AB` to its zero indexed number. Column names are case insensitive.
+pub fn name_to_col(name: &str) -> Result<WorksheetCol, XlsxError> {
+    if name.is_empty() || name.len() > 3 || !name.chars().all(|x| x.is_ascii_alphabetic()) {
+        return Err(invalid_reference(name));
+    }
+    let col = name.bytes().fold(0u32, |col, x| {
+        col * 26 + u32::from(x.to_ascii_uppercase() - b'A' + 1)
+    }) - 1;
+    if col < limits::MAX_COLUMNS {
+        WorksheetCol::try_from(col).map_err(|_| invalid_reference(name))
+    } else {
+        Err(invalid_reference(name))
+    }
+}
+
+/// Quote a sheet name with single quotes if it is required in a formula, such as `'My Sheet'`.
+/// Single quotes in the name are doubled.
+pub fn quote_sheet_name(name: &str) -> Cow<'_, str> {
+    let plain = !name.is_empty()
+        && name
+            .chars()
+            .all(|x| x.is_alphanumeric() || x == '_' || x == '.')
+        && !name.starts_with(|x: char| x.is_ascii_digit())
+        && name.parse::<CellRef>().is_err()
+        && !is_r1c1_reference(name);
+    if plain {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("'{}'", name.replace('\'', "''")))
+    }
+}
+
+/// Make `name` a valid worksheet name: the characters `[ ] : * ? / \` are replaced with `_`, single quotes at
+/// both ends are removed and the name is truncated to 31 characters. An empty name becomes `Sheet`.
+/// See [`Workbook::add_worksheet_sanitized`](crate::Workbook::add_worksheet_sanitized) to also make the name unique.
+/// ```rust
+/// use xlsxwriter::utility::sanitize_sheet_name;
+/// assert_eq!(sanitize_sheet_name("'Q3/Q4 [draft]'"), "Q3_Q4 _draft_");
+/// assert_eq!(sanitize_sheet_name("Customers in the north-east region"), "Customers in the north-east reg");
+/// ```
+pub fn sanitize_sheet_name(name: &str) -> String {
+    let name: String = name
+        .trim_matches('\'')
+        .chars()
+        .map(|x| match x {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            x => x,
+        })
+        .collect();
+    let name = limits::truncate(&name, limits::MAX_SHEET_NAME_LENGTH)
+        .unwrap_or(&name)
+        .trim_end_matches('\'');
+    if name.is_empty() {
+        DEFAULT_SHEET_NAME.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Name used by [`sanitize_sheet_name`] for an empty name.
+const DEFAULT_SHEET_NAME: &str = "Sheet";
+
+/// Names such as `R`, `C12` or `R1C1` would be read as R1C1 references.
+pub(crate) fn is_r1c1_reference(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    let rest = upper.strip_prefix('R').unwrap_or(&upper);
+    let rest = rest.trim_start_matches(|x: char| x.is_ascii_digit());
+    let rest = rest.strip_prefix('C').unwrap_or(rest);
+    let rest = rest.trim_start_matches(|x: char| x.is_ascii_digit());
+    rest.is_empty() && upper != rest
+}
+
+fn invalid_reference(reference: &str) -> XlsxError {
+    XlsxErrorSource::InvalidCellReference(reference.to_string()).into()
+}
+
+/// A cell in A1 notation, such as `B3` or `$B$3`. See the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CellRef {
+    /// Zero indexed row.
+    pub row: WorksheetRow,
+    /// Zero indexed column.
+    pub col: WorksheetCol,
+    /// The row is absolute, such as `A$1`.
+    pub row_absolute: bool,
+    /// The column is absolute, such as `$A1`.
+    pub col_absolute: bool,
+}
+
+impl CellRef {
+    /// A relative reference to the cell at zero indexed `row` and `col`.
+    pub fn new(row: WorksheetRow, col: WorksheetCol) -> Self {
+        CellRef {
+            row,
+            col,
+            row_absolute: false,
+            col_absolute: false,
+        }
+    }
+
+    /// Make both the row and the column absolute.
+    pub fn absolute(mut self) -> Self {
+        self.row_absolute = true;
+        self.col_absolute = true;
+        self
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}",
+            if self.col_absolute { "$" } else { "" },
+            col_to_name(self.col),
+            if self.row_absolute { "$" } else { "" },
+            u64::from(self.row) + 1
+        )
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = XlsxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (col_absolute, rest) = match s.strip_prefix('$') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let letters = rest
+            .find(|x: char| !x.is_ascii_alphabetic())
+            .ok_or_else(|| invalid_reference(s))?;
+        let col = name_to_col(&rest[..letters]).map_err(|_| invalid_reference(s))?;
+        let (row_absolute, digits) = match rest[letters..].strip_prefix('$') {
+            Some(digits) => (true, digits),
+            None => (false, &rest[letters..]),
+        };
+        if digits.is_empty() || !digits.chars().all(|x| x.is_ascii_digit()) {
+            return Err(invalid_reference(s));
+        }
+        let row: WorksheetRow = digits.parse().map_err(|_| invalid_reference(s))?;
+        if !(1..=limits::MAX_ROWS).contains(&row) {
+            return Err(invalid_reference(s));
+        }
+        Ok(CellRef {
+            row: row - 1,
+            col,
+            row_absolute,
+            col_absolute,
+        })
+    }
+}
+
+/// A range of cells in A1 notation with an optional sheet name, such as `A1:C10` or `'My Sheet'!$A$1:$B$2`.
+/// A single cell such as `'My Sheet'!A1` is a range whose first and last cells are the same.
+/// A parsed range is normalized like Excel does, so `C3:A1` is the same as `A1:C3`. See the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CellRange {
+    /// Name of the sheet, without quotes.
+    pub sheet_name: Option<String>,
+    /// Top left cell.
+    pub first: CellRef,
+    /// Bottom right cell.
+    pub last: CellRef,
+}
+
+impl CellRange {
+    /// A relative range from `first_row`, `first_col` to `last_row`, `last_col`, without a sheet name.
+    pub fn new(
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    ) -> Self {
+        CellRange {
+            sheet_name: None,
+            first: CellRef::new(first_row, first_col),
+            last: CellRef::new(last_row, last_col),
+        }
+    }
+
+    /// Make the rows and the columns of both cells absolute.
+    pub fn absolute(mut self) -> Self {
+        self.first = self.first.absolute();
+        self.last = self.last.absolute();
+        self
+    }
+
+    /// Set the sheet name.
+    pub fn with_sheet_name(mut self, sheet_name: &str) -> Self {
+        self.sheet_name = Some(sheet_name.to_string());
+        self
+    }
+
+    /// Returns the range as a formula such as `=Sheet1!$A$1:$A$5`, which can be passed to [`Chart::add_series`](crate::Chart::add_series).
+    pub fn to_formula(&self) -> String {
+        format!("={}", self)
+    }
+}
+
+impl From<CellRef> for CellRange {
+    fn from(cell: CellRef) -> Self {
+        CellRange {
+            sheet_name: None,
+            first: cell,
+            last: cell,
+        }
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(sheet_name) = &self.sheet_name {
+            write!(f, "{}!", quote_sheet_name(sheet_name))?;
+        }
+        if self.first == self.last {
+            write!(f, "{}", self.first)
+        } else {
+            write!(f, "{}:{}", self.first, self.last)
+        }
+    }
+}
+
+impl FromStr for CellRange {
+    type Err = XlsxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sheet_name, range) = if let Some(quoted) = s.strip_prefix('\'') {
+            // Quotes in a sheet name are escaped by doubling them
+            let mut end = None;
+            let mut chars = quoted.char_indices().peekable();
+            while let Some((i, x)) = chars.next() {
+                if x == '\'' {
+                    if chars.peek().map(|&(_, y)| y) == Some('\'') {
+                        chars.next();
+                    } else {
+                        end = Some(i);
+                        break;
+                    }
+                }
+            }
+            let end = end.ok_or_else(|| invalid_reference(s))?;
+            let range = quoted[end + 1..]
+                .strip_prefix('!')
+                .ok_or_else(|| invalid_reference(s))?;
+            (Some(quoted[..end].replace("''", "'")), range)
+        } else {
+            match s.rsplit_once('!') {
+                Some((sheet_name, range)) => (Some(sheet_name.to_string()), range),
+                None => (None, s),
+            }
+        };
+        let (first, last) = match range.split_once(':') {
+            Some((first, last)) => (first, last),
+            None => (range, range),
+        };
+        let mut first: CellRef = first.parse().map_err(|_| invalid_reference(s))?;
+        let mut last: CellRef = last.parse().map_err(|_| invalid_reference(s))?;
+        if first.row > last.row {
+            std::mem::swap(&mut first.row, &mut last.row);
+            std::mem::swap(&mut first.row_absolute, &mut last.row_absolute);
+        }
+        if first.col > last.col {
+            std::mem::swap(&mut first.col, &mut last.col);
+            std::mem::swap(&mut first.col_absolute, &mut last.col_absolute);
+        }
+        Ok(CellRange {
+            sheet_name,
+            first,
+            last,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_col_name() {
+        for (col, name) in [
+            (0, "A"),
+            (25, "Z"),
+            (26, "AA"),
+            (701, "ZZ"),
+            (16_383, "XFD"),
+        ] {
+            assert_eq!(col_to_name(col), name);
+            assert_eq!(name_to_col(name).unwrap(), col);
+        }
+        assert_eq!(name_to_col("ab").unwrap(), 27);
+        assert!(name_to_col("XFE").is_err());
+        assert!(name_to_col("A1").is_err());
+    }
+
+    #[test]
+    fn test_quote_sheet_name() {
+        for (name, quoted) in [
+            ("Sheet1", "Sheet1"),
+            ("Data_2024.v2", "Data_2024.v2"),
+            ("My Sheet", "'My Sheet'"),
+            ("It's", "'It''s'"),
+            ("A1", "'A1'"),
+            ("2024", "'2024'"),
+            ("R1C1", "'R1C1'"),
+            ("c", "'c'"),
+            ("Rates", "Rates"),
+            ("売上", "売上"),
+        ] {
+            assert_eq!(quote_sheet_name(name), quoted);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_sheet_name() {
+        for (name, sanitized) in [
+            ("Sheet1", "Sheet1"),
+            ("a/b\\c?d*e:f[g]", "a_b_c_d_e_f_g_"),
+            ("'quoted'", "quoted"),
+            ("It's", "It's"),
+            ("''", "Sheet"),
+            ("", "Sheet"),
+            (
+                "0123456789012345678901234567890123",
+                "0123456789012345678901234567890",
+            ),
+            (
+                "012345678901234567890123456789'x",
+                "012345678901234567890123456789",
+            ),
+            (
+                "売上売上売上売上売上売上売上売上売上売上売上売上売上売上売上売上",
+                "売上売上売上売上売上売上売上売上売上売上売上売上売上売上売上売",
+            ),
+        ] {
+            assert_eq!(sanitize_sheet_name(name), sanitized, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_cell_range() {
+        let cell: CellRef = "$C7".parse().unwrap();
+        assert_eq!(
+            cell,
+            CellRef {
+                row: 6,
+                col: 2,
+                row_absolute: false,
+                col_absolute: true
+            }
+        );
+        assert_eq!(cell.to_string(), "$C7");
+        for text in ["A0", "A", "1", "$$A1", "XFE1", "A1048577", "A1:B2"] {
+            assert!(text.parse::<CellRef>().is_err(), "{}", text);
+        }
+
+        for (text, range) in [
+            ("A1:C10", CellRange::new(0, 0, 9, 2)),
+            ("B2", CellRange::new(1, 1, 1, 1)),
+            (
+                "Sheet1!$A$1:$A$5",
+                CellRange::new(0, 0, 4, 0)
+                    .absolute()
+                    .with_sheet_name("Sheet1"),
+            ),
+            (
+                "'It''s here'!A1:B2",
+                CellRange::new(0, 0, 1, 1).with_sheet_name("It's here"),
+            ),
+        ] {
+            assert_eq!(text.parse::<CellRange>().unwrap(), range);
+            assert_eq!(range.to_string(), text);
+        }
+        assert_eq!(
+            "'My Sheet'!b2".parse::<CellRange>().unwrap().to_string(),
+            "'My Sheet'!B2"
+        );
+        for (text, normalized) in [
+            ("C3:A1", "A1:C3"),
+            ("A3:C1", "A1:C3"),
+            ("$C3:A$1", "A$1:$C3"),
+        ] {
+            assert_eq!(
+                text.parse::<CellRange>().unwrap().to_string(),
+                normalized,
+                "{}",
+                text
+            );
+        }
+        for text in ["A1:", "'Sheet1!A1", "'Sheet1'A1", "Sheet1!"] {
+            assert!(text.parse::<CellRange>().is_err(), "{}", text);
+        }
+    }
+}
@@ -1,12 +1,28 @@
+use crate::defined_name;
+use crate::error::{ErrorContext, XlsxErrorSource};
+use crate::evaluator::{Evaluator, EvaluatorOptions};
+use crate::future_functions;
+use crate::limits::{
+    self, NonFiniteNumberPolicy, OverflowCallback, OverflowPolicy, OverflowWarning,
+};
+use crate::progress::{self, PackageScanner};
+use crate::utility;
 use crate::CStringHelper;
 
-use super::{Chart, ChartType, Format, Worksheet, XlsxError};
-use std::cell::RefCell;
-use std::collections::HashMap;
+use super::{Chart, ChartType, DateEpoch, DateTime, Format, Worksheet, XlsxError};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
 use std::os::raw::c_char;
+use std::path::Path;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+
+pub use crate::defined_name::DefinedName;
+pub use crate::progress::{CancellationToken, CloseProgress};
 
 /// The Workbook is the main object exposed by the libxlsxwriter library. It represents the entire spreadsheet as you see it in Excel and internally it represents the Excel file as it is written on disk.
 ///
@@ -19,23 +35,171 @@ use std::rc::Rc;
 ///     workbook.close()
 /// }
 /// ```
+///
+/// A workbook is shared with the [`Worksheet`], [`Chart`] and [`ChartSeries`](crate::chart::ChartSeries) objects created from it,
+/// which are owned values without a lifetime. The file is written by [`Workbook::close`], or when the workbook and all of these
+/// objects are dropped as [`Workbook::set_drop_policy`] specifies. Using a worksheet or a chart after [`Workbook::close`] returns an error of kind [`XlsxErrorKind::WorkbookClosed`](crate::XlsxErrorKind::WorkbookClosed).
 pub struct Workbook {
-    workbook: *mut libxlsxwriter_sys::lxw_workbook,
-    pub(crate) const_str: Rc<RefCell<Vec<Pin<Box<CString>>>>>,
-    format_map: Rc<RefCell<HashMap<Format, *mut libxlsxwriter_sys::lxw_format>>>,
+    inner: Rc<WorkbookInner>,
+}
+
+/// State of a workbook shared by [`Workbook`] handles.
+struct WorkbookInner {
+    workbook: Cell<*mut libxlsxwriter_sys::lxw_workbook>,
+    const_str: RefCell<Vec<Pin<Box<CString>>>>,
+    format_map: RefCell<HashMap<Format, *mut libxlsxwriter_sys::lxw_format>>,
+    datetime_formats: RefCell<DateTimeFormats>,
+    filename: String,
+    date_epoch: Cell<DateEpoch>,
+    calc_options: RefCell<Option<CalcOptions>>,
+    hidden_names: RefCell<Vec<DefinedName>>,
+    worksheet_order: RefCell<Vec<*mut libxlsxwriter_sys::lxw_worksheet>>,
+    live_worksheets: RefCell<HashSet<*mut libxlsxwriter_sys::lxw_worksheet>>,
+    overflow_policy: Cell<OverflowPolicy>,
+    overflow_callback: RefCell<Option<OverflowCallback>>,
+    non_finite_number_policy: Cell<NonFiniteNumberPolicy>,
+    future_functions: Cell<bool>,
+    evaluator: RefCell<Option<Evaluator>>,
+    drop_policy: Cell<DropPolicy>,
+    drop_callback: RefCell<Option<DropCallback>>,
+}
+
+/// What to do when a workbook is dropped without [`Workbook::close`] or [`Workbook::abandon`].
+/// It is set with [`Workbook::set_drop_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum DropPolicy {
+    /// Write the file and report the result to the callback set with [`Workbook::set_drop_callback`]. This is the default.
+    #[default]
+    Close,
+    /// Write the file as [`DropPolicy::Close`], and then panic in debug builds.
+    /// The workbook doesn't panic while the thread is already panicking.
+    PanicInDebug,
+    /// Discard the workbook without writing the file, and report it to the callback set with [`Workbook::set_drop_callback`].
+    Skip,
+}
+
+/// A workbook dropped without [`Workbook::close`] or [`Workbook::abandon`], which is reported to the callback set with
+/// [`Workbook::set_drop_callback`].
+#[derive(Debug)]
+pub struct DroppedWorkbook {
+    /// Name of the xlsx file.
+    pub filename: String,
+    /// Policy applied to the workbook.
+    pub policy: DropPolicy,
+    /// Result of writing the file. `Ok(())` if the file was not written by [`DropPolicy::Skip`].
+    pub result: Result<(), XlsxError>,
+}
+
+type DropCallback = Box<dyn Fn(&DroppedWorkbook)>;
+
+/// Calculation mode of a workbook. See [`CalcOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum CalcMode {
+    /// Recalculate formulas whenever a value is changed.
+    #[default]
+    Automatic,
+    /// Recalculate formulas except data tables automatically.
+    AutomaticExceptTables,
+    /// Recalculate formulas only when the user requests it, for example with F9.
+    Manual,
+}
+
+/// Calculation settings of a workbook, which are set with [`Workbook::set_calc_options`].
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CalcOptions {
+    /// Recalculate all formulas when the workbook is opened. It is on by default, because libxlsxwriter doesn't calculate the results of formulas.
+    pub full_calc_on_load: bool,
+    /// Calculation mode. Default is [`CalcMode::Automatic`].
+    pub mode: CalcMode,
+    /// Enable iterative calculation to resolve circular references. It is off by default.
+    pub iterate: bool,
+    /// Maximum number of iterations. Default is `100`.
+    pub iterate_count: u32,
+    /// Maximum change between iterations. Default is `0.001`.
+    pub iterate_delta: f64,
+}
+
+impl Default for CalcOptions {
+    fn default() -> Self {
+        CalcOptions {
+            full_calc_on_load: true,
+            mode: CalcMode::Automatic,
+            iterate: false,
+            iterate_count: 100,
+            iterate_delta: 0.001,
+        }
+    }
+}
+
+impl CalcOptions {
+    fn to_xml(&self) -> String {
+        let mut xml = r#"<calcPr calcId="124519""#.to_string();
+        match self.mode {
+            CalcMode::Automatic => {}
+            CalcMode::AutomaticExceptTables => xml.push_str(r#" calcMode="autoNoTable""#),
+            CalcMode::Manual => xml.push_str(r#" calcMode="manual""#),
+        }
+        if self.full_calc_on_load {
+            xml.push_str(r#" fullCalcOnLoad="1""#);
+        }
+        if self.iterate {
+            xml.push_str(&format!(
+                r#" iterate="1" iterateCount="{}" iterateDelta="{}""#,
+                self.iterate_count, self.iterate_delta
+            ));
+        }
+        xml.push_str("/>");
+        xml
+    }
+}
+
+struct DateTimeFormats {
+    date: Format,
+    time: Format,
+    datetime: Format,
+}
+
+impl Default for DateTimeFormats {
+    fn default() -> Self {
+        DateTimeFormats {
+            date: Format::new().set_num_format("yyyy-mm-dd").clone(),
+            time: Format::new().set_num_format("hh:mm:ss").clone(),
+            datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss").clone(),
+        }
+    }
 }
 
 impl Workbook {
+    /// Returns another handle to the same workbook, which is held by a worksheet or a chart.
+    pub(crate) fn handle(&self) -> Workbook {
+        Workbook {
+            inner: Rc::clone(&self.inner),
+        }
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.inner.workbook.get().is_null()
+    }
+
+    /// Returns the libxlsxwriter workbook, or an error if the workbook is already closed.
+    pub(crate) fn raw_workbook(&self) -> Result<*mut libxlsxwriter_sys::lxw_workbook, XlsxError> {
+        if self.is_closed() {
+            Err(XlsxErrorSource::WorkbookClosed.into())
+        } else {
+            Ok(self.inner.workbook.get())
+        }
+    }
+
     pub(crate) fn get_internal_format(
         &self,
         format: &Format,
     ) -> Result<*mut libxlsxwriter_sys::lxw_format, XlsxError> {
-        let mut map = self.format_map.borrow_mut();
+        let mut map = self.inner.format_map.borrow_mut();
         if let Some(p) = map.get(format) {
             Ok(*p)
         } else {
             unsafe {
-                let new_format = libxlsxwriter_sys::workbook_add_format(self.workbook);
+                let new_format = libxlsxwriter_sys::workbook_add_format(self.raw_workbook()?);
                 format.set_internal_format(new_format)?;
                 map.insert(format.clone(), new_format);
                 Ok(new_format)
@@ -54,10 +218,76 @@ impl Workbook {
         }
     }
 
+    pub(crate) fn default_datetime_format(&self, datetime: &DateTime) -> Format {
+        let formats = self.inner.datetime_formats.borrow();
+        if !datetime.has_date() {
+            formats.time.clone()
+        } else if datetime.has_time() {
+            formats.datetime.clone()
+        } else {
+            formats.date.clone()
+        }
+    }
+
+    pub(crate) fn overflow_policy(&self) -> OverflowPolicy {
+        self.inner.overflow_policy.get()
+    }
+
+    /// Returns `formula` with future function prefixes if [`Workbook::use_future_functions`] is enabled.
+    pub(crate) fn prepare_formula<'b>(&self, formula: &'b str) -> Cow<'b, str> {
+        if self.inner.future_functions.get() {
+            future_functions::add_prefixes(formula)
+        } else {
+            Cow::Borrowed(formula)
+        }
+    }
+
+    pub(crate) fn evaluator(&self) -> &RefCell<Option<Evaluator>> {
+        &self.inner.evaluator
+    }
+
+    pub(crate) fn non_finite_number_policy(&self) -> NonFiniteNumberPolicy {
+        self.inner.non_finite_number_policy.get()
+    }
+
+    pub(crate) fn warn_overflow(&self, warning: &OverflowWarning) {
+        if let Some(callback) = self.inner.overflow_callback.borrow().as_ref() {
+            callback(warning);
+        }
+    }
+
+    /// Apply the overflow policy to `text`, which must not be longer than `limit` characters.
+    pub(crate) fn fit_text<'t>(
+        &self,
+        text: &'t str,
+        limit: usize,
+        error: libxlsxwriter_sys::lxw_error,
+        context: ErrorContext,
+    ) -> Result<Cow<'t, str>, XlsxError> {
+        let truncated = match limits::truncate(text, limit) {
+            Some(truncated) => truncated,
+            None => return Ok(Cow::Borrowed(text)),
+        };
+        match self.overflow_policy() {
+            OverflowPolicy::Error => Err(XlsxError::new(error).with_context(context)),
+            policy => {
+                self.warn_overflow(&OverflowWarning {
+                    sheet_name: context.sheet_name,
+                    cell: context.first_cell,
+                    method: context.method.unwrap_or_default(),
+                    length: text.chars().count(),
+                    limit,
+                    policy,
+                });
+                Ok(Cow::Borrowed(truncated))
+            }
+        }
+    }
+
     pub(crate) fn register_str(&self, s: &str) -> Result<*const c_char, XlsxError> {
         let c = Box::pin(CString::new(s)?);
         let p = c.as_ptr();
-        self.const_str.borrow_mut().push(c);
+        self.inner.const_str.borrow_mut().push(c);
         Ok(p)
     }
 
@@ -65,27 +295,54 @@ impl Workbook {
         if let Some(s) = s {
             let c = Box::pin(CString::new(s)?);
             let p = c.as_ptr();
-            self.const_str.borrow_mut().push(c);
+            self.inner.const_str.borrow_mut().push(c);
             Ok(p)
         } else {
             Ok(std::ptr::null())
         }
     }
 
+    /// Wraps a workbook created by libxlsxwriter. `workbook_name` is the C string of `filename` passed to libxlsxwriter.
+    fn from_raw(
+        raw_workbook: *mut libxlsxwriter_sys::lxw_workbook,
+        workbook_name: Pin<Box<CString>>,
+        filename: &str,
+    ) -> Workbook {
+        Workbook {
+            inner: Rc::new(WorkbookInner {
+                workbook: Cell::new(raw_workbook),
+                const_str: RefCell::new(vec![workbook_name]),
+                format_map: RefCell::new(HashMap::new()),
+                datetime_formats: RefCell::new(DateTimeFormats::default()),
+                filename: filename.to_string(),
+                date_epoch: Cell::new(DateEpoch::Excel1900),
+                calc_options: RefCell::new(None),
+                hidden_names: RefCell::new(Vec::new()),
+                worksheet_order: RefCell::new(Vec::new()),
+                live_worksheets: RefCell::new(HashSet::new()),
+                overflow_policy: Cell::new(OverflowPolicy::Error),
+                overflow_callback: RefCell::new(None),
+                non_finite_number_policy: Cell::new(NonFiniteNumberPolicy::Error),
+                future_functions: Cell::new(false),
+                evaluator: RefCell::new(None),
+                drop_policy: Cell::new(DropPolicy::Close),
+                drop_callback: RefCell::new(None),
+            }),
+        }
+    }
+
     /// This function is used to create a new Excel workbook with a given filename.
     /// When specifying a filename it is recommended that you use an .xlsx extension or Excel will generate a warning when opening the file.
     pub fn new(filename: &str) -> Result<Workbook, XlsxError> {
         unsafe {
-            let workbook_name = Box::pin(CString::new(filename)?);
+            let workbook_name = Box::pin(
+                CString::new(filename).map_err(|e| XlsxError::from(e).with_method("new"))?,
+            );
             let raw_workbook = libxlsxwriter_sys::workbook_new(workbook_name.as_ptr());
             if raw_workbook.is_null() {
                 unreachable!()
             }
-            Ok(Workbook {
-                workbook: raw_workbook,
-                const_str: Rc::new(RefCell::new(vec![workbook_name])),
-                format_map: Rc::new(RefCell::new(HashMap::new())),
-            })
+            Ok(Workbook::from_raw(raw_workbook, workbook_name, filename))
         }
     }
 
@@ -139,79 +396,333 @@ impl Workbook {
                 output_buffer_size: std::ptr::null_mut(),
             };
 
-            let workbook_name = Box::pin(CString::new(filename).expect("Null Error"));
+            let workbook_name = Box::pin(
+                CString::new(filename)
+                    .map_err(|e| XlsxError::from(e).with_method("new_with_options"))?,
+            );
 
             let raw_workbook =
                 libxlsxwriter_sys::workbook_new_opt(workbook_name.as_ptr(), &mut workbook_options);
             if raw_workbook.is_null() {
                 unreachable!()
             }
-            Ok(Workbook {
-                workbook: raw_workbook,
-                const_str: Rc::new(RefCell::new(vec![workbook_name])),
-                format_map: Rc::new(RefCell::new(HashMap::new())),
-            })
+            Ok(Workbook::from_raw(raw_workbook, workbook_name, filename))
         }
     }
 
-    pub fn add_worksheet<'a>(
-        &'a self,
-        sheet_name: Option<&str>,
-    ) -> Result<Worksheet<'a>, XlsxError> {
-        let name_cstr =
-            sheet_name.map(|x| Box::pin(CString::new(x).expect("Cannot create CString")));
+    pub fn add_worksheet(&self, sheet_name: Option<&str>) -> Result<Worksheet, XlsxError> {
+        let sheet_name = sheet_name
+            .map(|x| {
+                self.fit_text(
+                    x,
+                    limits::MAX_SHEET_NAME_LENGTH,
+                    libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_LENGTH_EXCEEDED,
+                    ErrorContext {
+                        method: Some("add_worksheet"),
+                        ..ErrorContext::default()
+                    },
+                )
+            })
+            .transpose()?;
+        let context = || ErrorContext {
+            sheet_name: sheet_name.as_deref().map(str::to_string),
+            method: Some("add_worksheet"),
+            ..ErrorContext::default()
+        };
+        let name_cstr = sheet_name
+            .as_deref()
+            .map(|x| CString::new(x).map(Box::pin))
+            .transpose()
+            .map_err(|e| XlsxError::from(e).with_context(context()))?;
+        let raw_workbook = self.raw_workbook().map_err(|e| e.with_context(context()))?;
         unsafe {
             if let Some(sheet_name) = name_cstr.as_ref() {
                 let result = libxlsxwriter_sys::workbook_validate_sheet_name(
-                    self.workbook,
+                    raw_workbook,
                     sheet_name.as_ptr(),
                 );
                 if result != libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
-                    return Err(XlsxError::new(result));
+                    return Err(XlsxError::new(result).with_context(context()));
                 }
             }
 
             let worksheet = libxlsxwriter_sys::workbook_add_worksheet(
-                self.workbook,
+                raw_workbook,
                 name_cstr
                     .as_ref()
                     .map_or_else(std::ptr::null, |x| x.as_ptr()),
             );
 
             if let Some(name) = name_cstr {
-                self.const_str.borrow_mut().push(name);
+                self.inner.const_str.borrow_mut().push(name);
             }
 
             if worksheet.is_null() {
                 return Err(XlsxError::unknown_error());
             }
 
-            Ok(Worksheet {
-                _workbook: self,
-                worksheet,
-            })
+            self.acquire_worksheet(worksheet)
+        }
+    }
+
+    /// Add a worksheet named after `sheet_name` made valid with [`utility::sanitize_sheet_name`], for a name derived from data.
+    /// If a worksheet with the name already exists, ignoring case, a suffix such as ` (2)` or ` (3)` is appended.
+    /// Use [`Worksheet::name`] to get the final name.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-add_worksheet_sanitized.xlsx")?;
+    /// workbook.add_worksheet(Some("Sales"))?;
+    /// assert_eq!(workbook.add_worksheet_sanitized("Sales/Q1")?.name(), "Sales_Q1");
+    /// assert_eq!(workbook.add_worksheet_sanitized("SALES")?.name(), "SALES (2)");
+    /// assert_eq!(workbook.add_worksheet_sanitized("sales")?.name(), "sales (3)");
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn add_worksheet_sanitized(&self, sheet_name: &str) -> Result<Worksheet, XlsxError> {
+        let raw_workbook = self
+            .raw_workbook()
+            .map_err(|e| e.with_method("add_worksheet_sanitized"))?;
+        let sanitized = utility::sanitize_sheet_name(sheet_name);
+        let mut candidate = sanitized.clone();
+        for number in 2.. {
+            let result = unsafe {
+                libxlsxwriter_sys::workbook_validate_sheet_name(
+                    raw_workbook,
+                    CString::new(candidate.as_str())
+                        .map_err(|e| {
+                            XlsxError::from(e).with_context(ErrorContext {
+                                sheet_name: Some(candidate.clone()),
+                                method: Some("add_worksheet_sanitized"),
+                                ..ErrorContext::default()
+                            })
+                        })?
+                        .as_ptr(),
+                )
+            };
+            if result != libxlsxwriter_sys::lxw_error_LXW_ERROR_SHEETNAME_ALREADY_USED {
+                break;
+            }
+            let suffix = format!(" ({})", number);
+            let base = limits::truncate(
+                &sanitized,
+                limits::MAX_SHEET_NAME_LENGTH - suffix.chars().count(),
+            )
+            .unwrap_or(&sanitized);
+            candidate = format!("{}{}", base, suffix);
         }
+        self.add_worksheet(Some(&candidate))
     }
 
     /// This function returns a [`Worksheet`] object reference based on its name.
-    pub fn get_worksheet<'a>(
-        &'a self,
+    ///
+    /// Only one [`Worksheet`] object can refer to a worksheet at a time. If the object returned by [`Workbook::add_worksheet`]
+    /// or by a previous call is still alive, an error of kind [`XlsxErrorKind::WorksheetInUse`](crate::XlsxErrorKind::WorksheetInUse) is returned.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-get_worksheet.xlsx")?;
+    /// let mut data = workbook.add_worksheet(Some("Data"))?;
+    /// data.write_number(0, 0, 1., None)?;
+    /// assert_eq!(workbook.get_worksheet("Data").err().map(|x| x.kind()), Some(XlsxErrorKind::WorksheetInUse));
+    /// drop(data);
+    /// let mut data = workbook.get_worksheet("Data")?.expect("Data exists");
+    /// data.write_number(1, 0, 2., None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn get_worksheet(&self, sheet_name: &str) -> Result<Option<Worksheet>, XlsxError> {
+        let worksheet = self.find_worksheet(sheet_name).map_err(|e| {
+            e.with_context(ErrorContext {
+                sheet_name: Some(sheet_name.to_string()),
+                method: Some("get_worksheet"),
+                ..ErrorContext::default()
+            })
+        })?;
+        match worksheet {
+            Some(worksheet) => self.acquire_worksheet(worksheet).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn find_worksheet(
+        &self,
         sheet_name: &str,
-    ) -> Result<Option<Worksheet<'a>>, XlsxError> {
-        unsafe {
-            let worksheet = libxlsxwriter_sys::workbook_get_worksheet_by_name(
-                self.workbook,
+    ) -> Result<Option<*mut libxlsxwriter_sys::lxw_worksheet>, XlsxError> {
+        let raw_workbook = self.raw_workbook()?;
+        let worksheet = unsafe {
+            libxlsxwriter_sys::workbook_get_worksheet_by_name(
+                raw_workbook,
                 CString::new(sheet_name)?.as_c_str().as_ptr(),
+            )
+        };
+        Ok(Some(worksheet).filter(|x| !x.is_null()))
+    }
+
+    /// Create a [`Worksheet`] object for `worksheet`, unless another one is alive.
+    fn acquire_worksheet(
+        &self,
+        worksheet: *mut libxlsxwriter_sys::lxw_worksheet,
+    ) -> Result<Worksheet, XlsxError> {
+        let name = unsafe { worksheet_name(worksheet) };
+        if !self.inner.live_worksheets.borrow_mut().insert(worksheet) {
+            return Err(
+                XlsxError::from(XlsxErrorSource::WorksheetInUse(name.clone())).with_context(
+                    ErrorContext {
+                        sheet_name: Some(name),
+                        ..ErrorContext::default()
+                    },
+                ),
             );
-            if worksheet.is_null() {
-                Ok(None)
-            } else {
-                Ok(Some(Worksheet {
-                    _workbook: self,
-                    worksheet,
-                }))
+        }
+        Ok(Worksheet::new(self.handle(), worksheet, name))
+    }
+
+    /// Called when a [`Worksheet`] object is dropped.
+    pub(crate) fn release_worksheet(&self, worksheet: *mut libxlsxwriter_sys::lxw_worksheet) {
+        self.inner.live_worksheets.borrow_mut().remove(&worksheet);
+    }
+
+    /// Returns the number of worksheets, or `0` after [`Workbook::close`].
+    pub fn worksheet_count(&self) -> usize {
+        self.worksheet_pointers().map_or(0, |x| x.len())
+    }
+
+    /// Returns the names of the worksheets in the order they were added, or no names after [`Workbook::close`].
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-worksheet_names.xlsx")?;
+    /// workbook.add_worksheet(Some("Detail"))?;
+    /// workbook.add_worksheet(None)?;
+    /// assert_eq!(workbook.worksheet_names(), ["Detail", "Sheet2"]);
+    /// assert_eq!(workbook.worksheet_count(), 2);
+    /// assert_eq!(workbook.get_worksheet_by_index(1)?.map(|x| x.name()).as_deref(), Some("Sheet2"));
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn worksheet_names(&self) -> Vec<String> {
+        self.worksheet_pointers()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| unsafe { worksheet_name(x) })
+            .collect()
+    }
+
+    /// This function returns a [`Worksheet`] object reference based on its zero indexed position in the order
+    /// the worksheets were added, or `None` if `index` is out of range. See [`Workbook::get_worksheet`] for the error
+    /// returned if the worksheet is in use.
+    pub fn get_worksheet_by_index(&self, index: usize) -> Result<Option<Worksheet>, XlsxError> {
+        match self
+            .worksheet_pointers()
+            .map_err(|e| e.with_method("get_worksheet_by_index"))?
+            .get(index)
+        {
+            Some(&worksheet) => self.acquire_worksheet(worksheet).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator over the worksheets in the order they were added. Each worksheet is returned as
+    /// [`Workbook::get_worksheet`] does, so the [`Worksheet`] objects of the previous items should be dropped.
+    /// After [`Workbook::close`], the only item is an error of kind [`XlsxErrorKind::WorkbookClosed`](crate::XlsxErrorKind::WorkbookClosed).
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-worksheets.xlsx")?;
+    /// workbook.add_worksheet(Some("North"))?;
+    /// workbook.add_worksheet(Some("South"))?;
+    /// for worksheet in workbook.worksheets() {
+    ///     let mut worksheet = worksheet?;
+    ///     let name = worksheet.name();
+    ///     worksheet.write_string(0, 0, &name, None)?;
+    /// }
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn worksheets(&self) -> impl Iterator<Item = Result<Worksheet, XlsxError>> {
+        let workbook = self.handle();
+        let (pointers, closed) = match self.worksheet_pointers() {
+            Ok(pointers) => (pointers, None),
+            Err(e) => (Vec::new(), Some(e.with_method("worksheets"))),
+        };
+        closed.into_iter().map(Err).chain(
+            pointers
+                .into_iter()
+                .map(move |worksheet| workbook.acquire_worksheet(worksheet)),
+        )
+    }
+
+    fn worksheet_pointers(&self) -> Result<Vec<*mut libxlsxwriter_sys::lxw_worksheet>, XlsxError> {
+        let raw_workbook = self.raw_workbook()?;
+        let mut pointers = Vec::new();
+        unsafe {
+            let mut worksheet = (*(*raw_workbook).worksheets).stqh_first;
+            while !worksheet.is_null() {
+                pointers.push(worksheet);
+                worksheet = (*worksheet).list_pointers.stqe_next;
             }
         }
+        Ok(pointers)
+    }
+
+    /// Set the order of the worksheet tabs in the closed file. The worksheets named in `sheet_names` come first in the given order,
+    /// followed by the other worksheets in the order they were added. It can be called at any time before [`Workbook::close`],
+    /// so a summary sheet can be added after the detail sheets and moved to the front.
+    ///
+    /// The active worksheet, which is the first added worksheet unless [`Worksheet::activate`] is called, isn't changed.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-set_worksheet_order.xlsx")?;
+    /// workbook.add_worksheet(Some("2023"))?;
+    /// workbook.add_worksheet(Some("2024"))?;
+    /// let mut summary = workbook.add_worksheet(Some("Summary"))?;
+    /// summary.write_formula(0, 0, "=SUM('2023'!A1,'2024'!A1)", None)?;
+    /// summary.activate()?;
+    /// // Tabs are "Summary", "2024", "2023"
+    /// workbook.set_worksheet_order(&["Summary", "2024"])?;
+    /// assert!(workbook.set_worksheet_order(&["2025"]).is_err());
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn set_worksheet_order(&self, sheet_names: &[&str]) -> Result<(), XlsxError> {
+        let mut order = Vec::new();
+        for name in sheet_names {
+            let context = || ErrorContext {
+                sheet_name: Some(name.to_string()),
+                method: Some("set_worksheet_order"),
+                ..ErrorContext::default()
+            };
+            let worksheet = self
+                .find_worksheet(name)
+                .map_err(|e| e.with_context(context()))?
+                .ok_or_else(|| {
+                    XlsxError::from(XlsxErrorSource::UnknownWorksheet(name.to_string()))
+                        .with_context(context())
+                })?;
+            if !order.contains(&worksheet) {
+                order.push(worksheet);
+            }
+        }
+        self.inner.worksheet_order.replace(order);
+        Ok(())
+    }
+
+    /// Indices of the worksheets in the order set with [`Workbook::set_worksheet_order`].
+    fn worksheet_order(&self) -> Option<Vec<usize>> {
+        let first = self.inner.worksheet_order.borrow();
+        if first.is_empty() {
+            return None;
+        }
+        let pointers = self.worksheet_pointers().ok()?;
+        let mut order: Vec<usize> = first
+            .iter()
+            .filter_map(|x| pointers.iter().position(|y| y == x))
+            .collect();
+        let rest: Vec<usize> = (0..pointers.len()).filter(|x| !order.contains(x)).collect();
+        order.extend(rest);
+        Some(order)
     }
 
     /// Create new format struct.
@@ -225,18 +736,25 @@ impl Workbook {
 
     /// [`Workbook::add_chart`] function creates a new chart object that can be added to a worksheet.
     /// Available chart types are defined in [`ChartType`].
+    ///
+    /// After [`Workbook::close`], the returned chart is not created in any file, and its methods return an error
+    /// of kind [`XlsxErrorKind::WorkbookClosed`](crate::XlsxErrorKind::WorkbookClosed).
     #[must_use]
     pub fn add_chart(&self, chart_type: ChartType) -> Chart {
-        unsafe {
-            let chart = libxlsxwriter_sys::workbook_add_chart(self.workbook, chart_type.value());
-            if chart.is_null() {
-                unreachable!();
-            }
-
-            Chart {
-                _workbook: self,
-                chart,
-            }
+        let chart = match self.raw_workbook() {
+            Ok(raw_workbook) => unsafe {
+                let chart = libxlsxwriter_sys::workbook_add_chart(raw_workbook, chart_type.value());
+                if chart.is_null() {
+                    unreachable!();
+                }
+                chart
+            },
+            // Never dereferenced, as the methods of the chart check that the workbook is open
+            Err(_) => std::ptr::null_mut(),
+        };
+        Chart {
+            _workbook: self.handle(),
+            chart,
         }
     }
 
@@ -253,45 +771,597 @@ impl Workbook {
     /// # workbook.close()
     /// # }
     /// ```
+    ///
+    /// A name local to a worksheet is given as `Sheet1!Name`. See [`Workbook::add_defined_name`] to check the name
+    /// and to build local, hidden and range names.
     pub fn define_name(&self, name: &str, formula: &str) -> Result<(), XlsxError> {
+        let name = CString::new(name).map_err(|e| XlsxError::from(e).with_method("define_name"))?;
+        let formula = CString::new(self.prepare_formula(formula).as_ref())
+            .map_err(|e| XlsxError::from(e).with_method("define_name"))?;
+        let raw_workbook = self
+            .raw_workbook()
+            .map_err(|e| e.with_method("define_name"))?;
         unsafe {
             let result = libxlsxwriter_sys::workbook_define_name(
-                self.workbook,
-                CString::new(name).expect("Null Error").as_c_str().as_ptr(),
-                CString::new(formula)
-                    .expect("Null Error")
-                    .as_c_str()
-                    .as_ptr(),
+                raw_workbook,
+                name.as_ptr(),
+                formula.as_ptr(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
+            } else {
+                Err(XlsxError::new(result).with_method("define_name"))
+            }
+        }
+    }
+
+    /// Add a [`DefinedName`] to the workbook. An error is returned if the name doesn't follow the naming rules of Excel:
+    /// it must start with a letter, `_` or `\`, contain only letters, digits, `_` and `.`, be at most 255 characters
+    /// and must not look like a cell reference such as `A1` or `R1C1`.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-add_defined_name2.xlsx")?;
+    /// let mut worksheet = workbook.add_worksheet(Some("Data"))?;
+    /// workbook.add_defined_name(&DefinedName::range_at("Values", "Data", 0, 0, 99, 0))?;
+    /// workbook.add_defined_name(&DefinedName::new("Limit", "=Data!$C$1").local("Data"))?;
+    /// assert_eq!(
+    ///     workbook.add_defined_name(&DefinedName::new("Total Sales", "=1")).unwrap_err().kind(),
+    ///     XlsxErrorKind::InvalidDefinedName
+    /// );
+    /// worksheet.write_formula(0, 1, "=COUNTIF(Values,\">\"&Limit)", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn add_defined_name(&self, name: &DefinedName) -> Result<(), XlsxError> {
+        name.validate()?;
+        self.define_name(&name.qualified_name(), &name.formula)?;
+        if name.hidden {
+            self.inner.hidden_names.borrow_mut().push(name.clone());
+        }
+        Ok(())
+    }
+
+    /// Set the number format applied by [`Worksheet::write_datetime`] to a [`DateTime`] with a date part and without a time part
+    /// when no format is given. Default is `yyyy-mm-dd`.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-set_default_date_format.xlsx")?;
+    /// workbook.set_default_date_format("dd/mm/yyyy");
+    /// workbook.set_default_time_format("h:mm AM/PM");
+    /// workbook.set_default_datetime_format("dd/mm/yyyy h:mm AM/PM");
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// // 28/02/2013
+    /// worksheet.write_datetime(0, 0, &DateTime::date(2013, 2, 28), None)?;
+    /// // 12:30 PM
+    /// worksheet.write_datetime(1, 0, &DateTime::time(12, 30, 0.), None)?;
+    /// // 28/02/2013 12:30 PM
+    /// worksheet.write_datetime(2, 0, &DateTime::new(2013, 2, 28, 12, 30, 0.), None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn set_default_date_format(&self, num_format: &str) {
+        self.inner.datetime_formats.borrow_mut().date =
+            Format::new().set_num_format(num_format).clone();
+    }
+
+    /// Set the number format applied by [`Worksheet::write_datetime`] to a [`DateTime`] without a date part
+    /// when no format is given. Default is `hh:mm:ss`.
+    pub fn set_default_time_format(&self, num_format: &str) {
+        self.inner.datetime_formats.borrow_mut().time =
+            Format::new().set_num_format(num_format).clone();
+    }
+
+    /// Set the number format applied by [`Worksheet::write_datetime`] to a [`DateTime`] with both a date part and a time part
+    /// when no format is given. Default is `yyyy-mm-dd hh:mm:ss`.
+    pub fn set_default_datetime_format(&self, num_format: &str) {
+        self.inner.datetime_formats.borrow_mut().datetime =
+            Format::new().set_num_format(num_format).clone();
+    }
+
+    /// Use the 1904 date system, which was the default of older Excel for Mac, instead of the 1900 date system.
+    /// Dates written by [`Worksheet::write_datetime`] are converted with the 1904 epoch after this function is called.
+    /// Use [`Workbook::date_epoch`] with [`DateTime::to_excel_serial`] to convert dates for formulas or numbers in the same date system.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-use_1904_epoch.xlsx")?;
+    /// workbook.use_1904_epoch()?;
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// let date = DateTime::date(2013, 1, 1);
+    /// worksheet.write_datetime(0, 0, &date, None)?;
+    /// worksheet.write_number(0, 1, date.to_excel_serial(workbook.date_epoch()), None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn use_1904_epoch(&self) -> Result<(), XlsxError> {
+        let raw_workbook = self
+            .raw_workbook()
+            .map_err(|e| e.with_method("use_1904_epoch"))?;
+        unsafe {
+            let result = libxlsxwriter_sys::workbook_use_1904_epoch(raw_workbook);
+            if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.inner.date_epoch.set(DateEpoch::Excel1904);
+                Ok(())
             } else {
                 Err(XlsxError::new(result))
             }
         }
     }
 
+    /// Returns the date system of the workbook.
+    pub fn date_epoch(&self) -> DateEpoch {
+        self.inner.date_epoch.get()
+    }
+
+    /// Set calculation settings of the workbook. They are written to the file by [`Workbook::close`].
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-set_calc_options.xlsx")?;
+    /// workbook.set_calc_options(CalcOptions {
+    ///     mode: CalcMode::Manual,
+    ///     iterate: true,
+    ///     iterate_count: 50,
+    ///     ..CalcOptions::default()
+    /// });
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.write_formula(0, 0, "=A1+1", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn set_calc_options(&self, options: CalcOptions) {
+        self.inner.calc_options.replace(Some(options));
+    }
+
+    /// Set how values exceeding Excel limits are handled. Default is [`OverflowPolicy::Error`].
+    /// See [`limits`](crate::limits) for the list of checked limits.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # use xlsxwriter::limits::OverflowWarning;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-set_overflow_policy.xlsx")?;
+    /// workbook.set_overflow_policy(OverflowPolicy::Spill);
+    /// workbook.set_overflow_callback(|warning: &OverflowWarning| {
+    ///     eprintln!("{:?} is too long: {} > {}", warning.cell, warning.length, warning.limit);
+    /// });
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// // Written to A1, B1 and C1
+    /// worksheet.write_string(0, 0, &"a".repeat(70_000), None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn set_overflow_policy(&self, policy: OverflowPolicy) {
+        self.inner.overflow_policy.set(policy);
+    }
+
+    /// Set a callback which is called when a value is truncated or spilled by [`OverflowPolicy::Truncate`] or [`OverflowPolicy::Spill`].
+    pub fn set_overflow_callback<F: Fn(&OverflowWarning) + 'static>(&self, callback: F) {
+        self.inner
+            .overflow_callback
+            .replace(Some(Box::new(callback)));
+    }
+
+    /// Set what to do when the workbook is dropped without [`Workbook::close`] or [`Workbook::abandon`].
+    /// Default is [`DropPolicy::Close`].
+    ///
+    /// Errors of writing the file on drop cannot be returned, so they are only reported to the callback set with [`Workbook::set_drop_callback`].
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-set_drop_policy.xlsx")?;
+    /// workbook.set_drop_policy(DropPolicy::Skip);
+    /// workbook.set_drop_callback(|dropped: &DroppedWorkbook| {
+    ///     eprintln!("{} was dropped without close: {:?}", dropped.filename, dropped.result);
+    /// });
+    /// workbook.add_worksheet(None)?;
+    /// drop(workbook);
+    /// assert!(!std::path::Path::new("test-workbook-set_drop_policy.xlsx").exists());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_drop_policy(&self, policy: DropPolicy) {
+        self.inner.drop_policy.set(policy);
+    }
+
+    /// Set a callback which is called when the workbook is dropped without [`Workbook::close`] or [`Workbook::abandon`].
+    pub fn set_drop_callback<F: Fn(&DroppedWorkbook) + 'static>(&self, callback: F) {
+        self.inner.drop_callback.replace(Some(Box::new(callback)));
+    }
+
+    /// Set how NaN and infinite numbers are written. Default is [`NonFiniteNumberPolicy::Error`].
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-set_non_finite_number_policy.xlsx")?;
+    /// workbook.set_non_finite_number_policy(NonFiniteNumberPolicy::NaError);
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// // #N/A
+    /// worksheet.write_number(0, 0, f64::NAN, None)?;
+    /// // The formula is kept and its cached result is #N/A
+    /// worksheet.write_formula_num(1, 0, "=1/0", None, f64::INFINITY)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn set_non_finite_number_policy(&self, policy: NonFiniteNumberPolicy) {
+        self.inner.non_finite_number_policy.set(policy);
+    }
+
+    /// Add the `_xlfn.` and `_xlws.` prefixes which Excel requires for functions added in Excel 2010 and later,
+    /// such as `XLOOKUP`, `LET`, `CONCAT`, `IFS`, `FILTER` and `SORT`. Without the prefixes Excel shows `#NAME?` for these functions.
+    ///
+    /// When enabled, the prefixes are added to the formulas of worksheet formula writers, conditional formats, data validations, table columns
+    /// and defined names written after this call. Functions which already have the prefix are kept as they are.
+    /// The parameters of `LET` and `LAMBDA` get the `_xlpm.` prefix which Excel requires, such as `=_xlfn.LET(_xlpm.x, 1, _xlpm.x + 1)`.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-use_future_functions.xlsx")?;
+    /// workbook.use_future_functions(true);
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// // Written as =_xlfn.XLOOKUP(D1, A1:A3, B1:B3)
+    /// worksheet.write_formula(0, 4, "=XLOOKUP(D1, A1:A3, B1:B3)", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn use_future_functions(&self, enable: bool) {
+        self.inner.future_functions.set(enable);
+    }
+
+    /// Compute the results of formulas written with [`Worksheet::write_formula`] and store them as the cached values,
+    /// so that applications which don't recalculate formulas show the results instead of `0`.
+    /// The workbook keeps the values written to worksheets after this call. See [`evaluator`](crate::evaluator) for the supported formulas.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-enable_formula_evaluator.xlsx")?;
+    /// workbook.enable_formula_evaluator(EvaluatorOptions {
+    ///     fallback: FormulaValue::Empty,
+    /// });
+    /// let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.write_number(0, 0, 10., None)?;
+    /// // Cached result is "large"
+    /// worksheet.write_formula(0, 1, "=IF(A1>5, \"large\", \"small\")", None)?;
+    /// // Not supported. Cached result is an empty string
+    /// worksheet.write_formula(0, 2, "=VLOOKUP(A1, D1:E9, 2)", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn enable_formula_evaluator(&self, options: EvaluatorOptions) {
+        *self.inner.evaluator.borrow_mut() = Some(Evaluator::new(options));
+    }
+
     /// The [`Workbook::close`] function closes a Workbook object, writes the Excel file to disk,
     /// frees any memory allocated internally to the Workbook and frees the object itself.
-    pub fn close(mut self) -> Result<(), XlsxError> {
+    pub fn close(self) -> Result<(), XlsxError> {
+        self.close_internal()
+    }
+
+    /// Same as [`Workbook::close`], but calls `progress` when the file starts to be written and periodically afterwards,
+    /// and stops when `cancellation` is cancelled.
+    ///
+    /// A cancelled close returns an error of [`XlsxErrorKind::Cancelled`](crate::XlsxErrorKind::Cancelled).
+    /// libxlsxwriter cannot be interrupted while it writes the package, so a close cancelled during packaging waits for libxlsxwriter
+    /// to finish, skips the remaining steps and removes the partially written file and the temporary files before it returns.
+    /// `progress` is not called after cancellation.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-close_with_progress.xlsx")?;
+    /// for _ in 0..3 {
+    ///     workbook.add_worksheet(None)?.write_string(0, 0, "Hello Excel", None)?;
+    /// }
+    /// let cancellation = CancellationToken::new();
+    /// workbook.close_with_progress(Some(&cancellation), |progress| {
+    ///     println!(
+    ///         "{}/{} sheets, {} bytes",
+    ///         progress.sheets_packaged, progress.sheet_count, progress.bytes_written
+    ///     );
+    /// })?;
+    ///
+    /// let workbook = Workbook::new("test-workbook-close_with_progress-cancelled.xlsx")?;
+    /// workbook.add_worksheet(None)?;
+    /// cancellation.cancel();
+    /// let error = workbook.close_with_progress(Some(&cancellation), |_| {}).unwrap_err();
+    /// assert_eq!(error.kind(), XlsxErrorKind::Cancelled);
+    /// assert!(!std::path::Path::new("test-workbook-close_with_progress-cancelled.xlsx").exists());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn close_with_progress<F>(
+        self,
+        cancellation: Option<&CancellationToken>,
+        mut progress: F,
+    ) -> Result<(), XlsxError>
+    where
+        F: FnMut(CloseProgress),
+    {
+        let is_cancelled = || cancellation.is_some_and(CancellationToken::is_cancelled);
+        let sheet_count = self.worksheet_count();
+        let mut pending = self.begin_close()?;
+        if is_cancelled() {
+            pending.discard();
+            return Err(XlsxErrorSource::Cancelled.into());
+        }
+
+        let filename = pending.filename.clone();
+        let mut scanner = PackageScanner::new(&filename, sheet_count);
+        let (sender, receiver) = mpsc::channel();
+        let token = cancellation.cloned();
+        let worker = thread::spawn(move || {
+            let is_cancelled = || token.as_ref().is_some_and(CancellationToken::is_cancelled);
+            let mut result = pending.write_package();
+            if result.is_ok() && !is_cancelled() {
+                result = pending.rewrite_parts();
+            }
+            let _ = sender.send(result);
+        });
+        progress(scanner.poll());
+        let result = loop {
+            match receiver.recv_timeout(progress::PROGRESS_INTERVAL) {
+                Ok(result) => break result,
+                Err(RecvTimeoutError::Timeout) if is_cancelled() => (),
+                Err(RecvTimeoutError::Timeout) => progress(scanner.poll()),
+                Err(RecvTimeoutError::Disconnected) => break Err(XlsxErrorSource::Unknown.into()),
+            }
+        };
+        // The output is removed only after the worker, which writes it, has finished
+        if worker.join().is_err() {
+            return Err(XlsxErrorSource::Unknown.into());
+        }
+        if is_cancelled() {
+            remove_output(&filename)?;
+            return Err(XlsxErrorSource::Cancelled.into());
+        }
+        result?;
+        progress(scanner.finish());
+        Ok(())
+    }
+
+    /// Discard the workbook without writing the file. Worksheets and charts of the workbook return an error afterwards,
+    /// as they do after [`Workbook::close`].
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// let workbook = Workbook::new("test-workbook-abandon.xlsx")?;
+    /// workbook.add_worksheet(None)?.write_string(0, 0, "Hello Excel", None)?;
+    /// workbook.abandon();
+    /// assert!(!std::path::Path::new("test-workbook-abandon.xlsx").exists());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn abandon(self) {
+        if let Ok(pending) = self.begin_close() {
+            pending.discard();
+        }
+    }
+
+    fn close_internal(&self) -> Result<(), XlsxError> {
+        self.begin_close()?.finish()
+    }
+
+    /// Take the libxlsxwriter workbook and the settings applied after closing it. The workbook is marked as closed,
+    /// so no other handle can access the libxlsxwriter workbook afterwards.
+    fn begin_close(&self) -> Result<PendingClose, XlsxError> {
+        let worksheet_order = self.worksheet_order();
+        let workbook = self.raw_workbook()?;
+        self.inner.workbook.set(std::ptr::null_mut());
+        Ok(PendingClose {
+            workbook,
+            _const_str: self.inner.const_str.take(),
+            filename: self.inner.filename.clone(),
+            calc_options: self.inner.calc_options.borrow().clone(),
+            hidden_names: self.inner.hidden_names.take(),
+            worksheet_order,
+        })
+    }
+}
+
+/// A workbook being closed by [`PendingClose::finish`], which may run on another thread.
+/// The libxlsxwriter workbook is freed by [`PendingClose::write_package`], or without writing the file when it is dropped.
+struct PendingClose {
+    workbook: *mut libxlsxwriter_sys::lxw_workbook,
+    /// Strings referenced by libxlsxwriter until the workbook is closed.
+    _const_str: Vec<Pin<Box<CString>>>,
+    filename: String,
+    calc_options: Option<CalcOptions>,
+    hidden_names: Vec<DefinedName>,
+    worksheet_order: Option<Vec<usize>>,
+}
+
+// The workbook is marked as closed by `Workbook::begin_close`, so `PendingClose` is the only owner of the libxlsxwriter workbook
+// and the strings it references.
+unsafe impl Send for PendingClose {}
+
+impl PendingClose {
+    fn finish(mut self) -> Result<(), XlsxError> {
+        self.write_package()?;
+        self.rewrite_parts()
+    }
+
+    /// Write the file with libxlsxwriter and free the libxlsxwriter workbook.
+    fn write_package(&mut self) -> Result<(), XlsxError> {
+        let workbook = std::mem::replace(&mut self.workbook, std::ptr::null_mut());
+        if workbook.is_null() {
+            return Err(XlsxErrorSource::WorkbookClosed.into());
+        }
         unsafe {
-            let result = libxlsxwriter_sys::workbook_close(self.workbook);
-            self.workbook = std::ptr::null_mut();
-            match result {
-                libxlsxwriter_sys::lxw_error_LXW_NO_ERROR => Ok(()),
-                _ => Err(XlsxError::new(result)),
+            let result = libxlsxwriter_sys::workbook_close(workbook);
+            if result != libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                return Err(XlsxError::new(result));
+            }
+        }
+        Ok(())
+    }
+
+    /// Free the libxlsxwriter workbook without writing the file.
+    fn discard(self) {
+        drop(self);
+    }
+
+    /// Apply the settings which libxlsxwriter doesn't support to the written file.
+    fn rewrite_parts(&self) -> Result<(), XlsxError> {
+        let calc_options = &self.calc_options;
+        let hidden_names = &self.hidden_names;
+        let worksheet_order = &self.worksheet_order;
+        if calc_options.is_some() || !hidden_names.is_empty() || worksheet_order.is_some() {
+            let parts = ["xl/workbook.xml", "docProps/app.xml"];
+            crate::package::rewrite_parts(Path::new(&self.filename), &parts, |name, content| {
+                if name == "docProps/app.xml" {
+                    return worksheet_order
+                        .as_ref()
+                        .map(|order| crate::package::reorder_titles(content, order));
+                }
+                let mut content = defined_name::hide_defined_names(content, hidden_names);
+                if let Some(order) = worksheet_order.as_ref() {
+                    content = crate::package::reorder_sheets(&content, order);
+                }
+                if let Some(calc_options) = calc_options.as_ref() {
+                    content = crate::package::replace_empty_element(
+                        &content,
+                        "calcPr",
+                        &calc_options.to_xml(),
+                        "workbook",
+                    );
+                }
+                Some(content)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PendingClose {
+    fn drop(&mut self) {
+        let workbook = std::mem::replace(&mut self.workbook, std::ptr::null_mut());
+        if !workbook.is_null() {
+            unsafe {
+                libxlsxwriter_sys::lxw_workbook_free(workbook);
             }
         }
     }
 }
 
+#[cfg(feature = "tokio")]
+impl Workbook {
+    /// Same as [`Workbook::close`], but writes the file on the blocking thread pool of tokio.
+    /// This function is available with `tokio` feature.
+    ///
+    /// The workbook is closed when this function is called, and worksheets and charts of the workbook return an error afterwards.
+    /// The returned future is [`Send`] and can be awaited in a task spawned on a multi-threaded runtime.
+    /// Temporary files are created in the `tmpdir` given to [`Workbook::new_with_options`], as [`Workbook::close`] does.
+    /// Once the future is polled, the file is written even if the future is dropped before it completes.
+    /// A future which is dropped without being polled discards the workbook without writing the file.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    /// # runtime.block_on(async {
+    /// let workbook = Workbook::new("test-workbook-close_async.xlsx")?;
+    /// workbook.add_worksheet(None)?.write_string(0, 0, "Hello Excel", None)?;
+    /// workbook.close_async().await
+    /// # })
+    /// # }
+    /// ```
+    pub fn close_async(
+        self,
+    ) -> impl std::future::Future<Output = Result<(), XlsxError>> + Send + 'static {
+        let pending = self.begin_close();
+        async move {
+            let pending = pending?;
+            tokio::task::spawn_blocking(move || pending.finish())
+                .await
+                .map_err(|e| XlsxError::from(XlsxErrorSource::Io(e.to_string())))?
+        }
+    }
+
+    /// Close the workbook as [`Workbook::close_async`] does, then copy the closed file into `writer` and remove the file.
+    /// Returns the number of bytes written. This function is available with `tokio` feature.
+    ///
+    /// libxlsxwriter writes to the file given to [`Workbook::new`] when the workbook is created, so the whole file is written there
+    /// before it is copied. Create the workbook with a path in a temporary directory, such as [`std::env::temp_dir`], to keep the
+    /// file out of other directories. The file is removed after the copy even if the copy fails, and a failure to remove it is ignored.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    /// # runtime.block_on(async {
+    /// let path = std::env::temp_dir().join("test-workbook-close_async_to.xlsx");
+    /// let workbook = Workbook::new(path.to_str().unwrap())?;
+    /// workbook.add_worksheet(None)?.write_string(0, 0, "Hello Excel", None)?;
+    /// let mut response = Vec::new();
+    /// let length = workbook.close_async_to(&mut response).await?;
+    /// assert_eq!(length, response.len() as u64);
+    /// assert!(response.starts_with(b"PK"));
+    /// assert!(!path.exists());
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub fn close_async_to<'w, W>(
+        self,
+        writer: &'w mut W,
+    ) -> impl std::future::Future<Output = Result<u64, XlsxError>> + Send + 'w
+    where
+        W: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        let filename = self.inner.filename.clone();
+        let close = self.close_async();
+        async move {
+            close.await?;
+            let mut file = tokio::fs::File::open(&filename).await?;
+            let copied = tokio::io::copy(&mut file, writer).await;
+            drop(file);
+            // A file left behind doesn't change the result of the copy
+            let _ = tokio::fs::remove_file(&filename).await;
+            Ok(copied?)
+        }
+    }
+}
+
+/// Remove a written file and the temporary file of [`PendingClose::rewrite_parts`].
+fn remove_output(filename: &str) -> Result<(), XlsxError> {
+    for path in [filename.to_string(), format!("{}.tmp", filename)] {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+unsafe fn worksheet_name(worksheet: *mut libxlsxwriter_sys::lxw_worksheet) -> String {
+    std::ffi::CStr::from_ptr((*worksheet).name)
+        .to_string_lossy()
+        .into_owned()
+}
+
 impl Drop for Workbook {
     fn drop(&mut self) {
-        unsafe {
-            if !self.workbook.is_null() {
-                libxlsxwriter_sys::workbook_close(self.workbook);
-            }
+        if Rc::strong_count(&self.inner) != 1 || self.is_closed() {
+            return;
+        }
+        let policy = self.inner.drop_policy.get();
+        let result = match policy {
+            DropPolicy::Close | DropPolicy::PanicInDebug => self.close_internal(),
+            DropPolicy::Skip => self.begin_close().map(PendingClose::discard),
+        };
+        let dropped = DroppedWorkbook {
+            filename: self.inner.filename.clone(),
+            policy,
+            result,
+        };
+        if let Some(callback) = self.inner.drop_callback.borrow().as_ref() {
+            callback(&dropped);
+        }
+        if cfg!(debug_assertions) && policy == DropPolicy::PanicInDebug && !std::thread::panicking()
+        {
+            panic!(
+                "Workbook {} was dropped without close: {:?}",
+                dropped.filename, dropped.result
+            );
         }
     }
 }
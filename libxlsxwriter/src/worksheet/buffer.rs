@@ -0,0 +1,577 @@
+use super::conditional_format::ConditionalFormat;
+use super::validation::DataValidation;
+use super::{CellError, DateTime, ImageOptions, WorksheetCol, WorksheetRow};
+use crate::formula;
+use crate::{Format, Worksheet, XlsxError};
+
+/// A log of worksheet operations which can be built on any thread and written to a [`Worksheet`] later.
+///
+/// [`Workbook`](crate::Workbook) and [`Worksheet`] are bound to the thread which created them, but a `SheetBuffer`
+/// only holds plain values, so it is [`Send`] and [`Sync`]. Sheet contents can be generated in parallel on worker threads
+/// and written with [`Worksheet::write_buffer`] on the thread owning the workbook. The operations are replayed in the order
+/// they were recorded, and the formats are deduplicated by the workbook as if they were passed to the worksheet directly.
+///
+/// Operations are recorded with the [`SheetWriter`](super::SheetWriter) methods, which take the same arguments as the [`Worksheet`]
+/// methods of the same name and always succeed on a buffer. The arguments are checked when the buffer is written, and an error has
+/// the same context as an error of the [`Worksheet`] method.
+/// ```rust
+/// use xlsxwriter::prelude::*;
+/// # fn main() -> Result<(), XlsxError> {
+/// let regions = ["North", "South", "East", "West"];
+/// let workers: Vec<_> = regions
+///     .iter()
+///     .map(|region| {
+///         let region = region.to_string();
+///         std::thread::spawn(move || -> Result<_, XlsxError> {
+///             let bold = Format::new().set_bold().clone();
+///             let mut buffer = SheetBuffer::new();
+///             buffer.write_string(0, 0, &region, Some(&bold))?;
+///             for row in 1..100 {
+///                 buffer.write_number(row, 0, f64::from(row), None)?;
+///             }
+///             buffer.write_formula(100, 0, "=SUM(A2:A100)", Some(&bold))?;
+///             Ok((region, buffer))
+///         })
+///     })
+///     .collect();
+///
+/// let workbook = Workbook::new("test-sheet_buffer.xlsx")?;
+/// for worker in workers {
+///     let (region, buffer) = worker.join().expect("worker panicked")?;
+///     workbook.add_worksheet(Some(&region))?.write_buffer(&buffer)?;
+/// }
+/// workbook.close()
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SheetBuffer {
+    pub(crate) ops: Vec<SheetOp>,
+}
+
+/// A worksheet operation recorded by [`SheetBuffer`], or performed through [`SheetWriter`](super::SheetWriter).
+///
+/// Each variant holds the arguments of the [`Worksheet`] method of the same name.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SheetOp {
+    /// [`Worksheet::write_number`].
+    Number {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        number: f64,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_string`].
+    String {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        text: String,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_formula`].
+    Formula {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: String,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_formula_num`].
+    FormulaNum {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: String,
+        format: Option<Format>,
+        number: f64,
+    },
+    /// [`Worksheet::write_formula_str`].
+    FormulaStr {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: String,
+        format: Option<Format>,
+        result: String,
+    },
+    /// [`Worksheet::write_datetime`].
+    DateTime {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        datetime: DateTime,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_url`].
+    Url {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        url: String,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_boolean`].
+    Boolean {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        value: bool,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_blank`].
+    Blank {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_error`].
+    Error {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        error: CellError,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::write_rich_string`].
+    RichString {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        text: Vec<(String, Option<Format>)>,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::merge_range`].
+    MergeRange {
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        string: String,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::set_row`].
+    Row {
+        row: WorksheetRow,
+        height: f64,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::set_column`].
+    Column {
+        first_col: WorksheetCol,
+        last_col: WorksheetCol,
+        width: f64,
+        format: Option<Format>,
+    },
+    /// [`Worksheet::data_validation_cell`].
+    DataValidationCell {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        validation: DataValidation,
+    },
+    /// [`Worksheet::data_validation_range`].
+    DataValidationRange {
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        validation: DataValidation,
+    },
+    /// [`Worksheet::conditional_format_cell`].
+    ConditionalFormatCell {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        conditional_format: ConditionalFormat,
+    },
+    /// [`Worksheet::conditional_format_range`].
+    ConditionalFormatRange {
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        conditional_format: ConditionalFormat,
+    },
+    /// [`Worksheet::insert_image`] or [`Worksheet::insert_image_opt`].
+    Image {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        filename: String,
+        options: Option<ImageOptions>,
+    },
+    /// [`Worksheet::insert_image_buffer`] or [`Worksheet::insert_image_buffer_opt`].
+    ImageBuffer {
+        row: WorksheetRow,
+        col: WorksheetCol,
+        buffer: Vec<u8>,
+        options: Option<ImageOptions>,
+    },
+}
+
+impl SheetOp {
+    pub(crate) fn apply(&self, worksheet: &mut Worksheet) -> Result<(), XlsxError> {
+        match self {
+            SheetOp::Number {
+                row,
+                col,
+                number,
+                format,
+            } => worksheet.write_number(*row, *col, *number, format.as_ref()),
+            SheetOp::String {
+                row,
+                col,
+                text,
+                format,
+            } => worksheet.write_string(*row, *col, text, format.as_ref()),
+            SheetOp::Formula {
+                row,
+                col,
+                formula,
+                format,
+            } => worksheet.write_formula(*row, *col, formula, format.as_ref()),
+            SheetOp::FormulaNum {
+                row,
+                col,
+                formula,
+                format,
+                number,
+            } => worksheet.write_formula_num(*row, *col, formula, format.as_ref(), *number),
+            SheetOp::FormulaStr {
+                row,
+                col,
+                formula,
+                format,
+                result,
+            } => worksheet.write_formula_str(*row, *col, formula, format.as_ref(), result),
+            SheetOp::DateTime {
+                row,
+                col,
+                datetime,
+                format,
+            } => worksheet.write_datetime(*row, *col, datetime, format.as_ref()),
+            SheetOp::Url {
+                row,
+                col,
+                url,
+                format,
+            } => worksheet.write_url(*row, *col, url, format.as_ref()),
+            SheetOp::Boolean {
+                row,
+                col,
+                value,
+                format,
+            } => worksheet.write_boolean(*row, *col, *value, format.as_ref()),
+            SheetOp::Blank { row, col, format } => {
+                worksheet.write_blank(*row, *col, format.as_ref())
+            }
+            SheetOp::Error {
+                row,
+                col,
+                error,
+                format,
+            } => worksheet.write_error(*row, *col, *error, format.as_ref()),
+            SheetOp::RichString {
+                row,
+                col,
+                text,
+                format,
+            } => {
+                let text: Vec<(&str, Option<&Format>)> = text
+                    .iter()
+                    .map(|(text, format)| (text.as_str(), format.as_ref()))
+                    .collect();
+                worksheet.write_rich_string(*row, *col, &text, format.as_ref())
+            }
+            SheetOp::MergeRange {
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                string,
+                format,
+            } => worksheet.merge_range(
+                *first_row,
+                *first_col,
+                *last_row,
+                *last_col,
+                string,
+                format.as_ref(),
+            ),
+            SheetOp::Row {
+                row,
+                height,
+                format,
+            } => worksheet.set_row(*row, *height, format.as_ref()),
+            SheetOp::Column {
+                first_col,
+                last_col,
+                width,
+                format,
+            } => worksheet.set_column(*first_col, *last_col, *width, format.as_ref()),
+            SheetOp::DataValidationCell {
+                row,
+                col,
+                validation,
+            } => worksheet.data_validation_cell(*row, *col, validation),
+            SheetOp::DataValidationRange {
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                validation,
+            } => worksheet
+                .data_validation_range(*first_row, *first_col, *last_row, *last_col, validation),
+            SheetOp::ConditionalFormatCell {
+                row,
+                col,
+                conditional_format,
+            } => worksheet.conditional_format_cell(*row, *col, conditional_format),
+            SheetOp::ConditionalFormatRange {
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                conditional_format,
+            } => worksheet.conditional_format_range(
+                *first_row,
+                *first_col,
+                *last_row,
+                *last_col,
+                conditional_format,
+            ),
+            SheetOp::Image {
+                row,
+                col,
+                filename,
+                options: None,
+            } => worksheet.insert_image(*row, *col, filename),
+            SheetOp::Image {
+                row,
+                col,
+                filename,
+                options: Some(options),
+            } => worksheet.insert_image_opt(*row, *col, filename, options),
+            SheetOp::ImageBuffer {
+                row,
+                col,
+                buffer,
+                options: None,
+            } => worksheet.insert_image_buffer(*row, *col, buffer),
+            SheetOp::ImageBuffer {
+                row,
+                col,
+                buffer,
+                options: Some(options),
+            } => worksheet.insert_image_buffer_opt(*row, *col, buffer, options),
+        }
+    }
+
+    /// The cell written by the operation.
+    fn cell(&self) -> Option<(WorksheetRow, WorksheetCol)> {
+        match self {
+            SheetOp::Number { row, col, .. }
+            | SheetOp::String { row, col, .. }
+            | SheetOp::Formula { row, col, .. }
+            | SheetOp::FormulaNum { row, col, .. }
+            | SheetOp::FormulaStr { row, col, .. }
+            | SheetOp::DateTime { row, col, .. }
+            | SheetOp::Url { row, col, .. }
+            | SheetOp::Boolean { row, col, .. }
+            | SheetOp::Blank { row, col, .. }
+            | SheetOp::Error { row, col, .. }
+            | SheetOp::RichString { row, col, .. } => Some((*row, *col)),
+            SheetOp::MergeRange {
+                first_row,
+                first_col,
+                ..
+            } => Some((*first_row, *first_col)),
+            _ => None,
+        }
+    }
+
+    /// Returns the operation moved by `rows` and `cols`. The relative references in formulas are moved as Excel does
+    /// when a formula is copied, including the formulas of data validations and conditional formats.
+    pub(crate) fn offset(
+        &self,
+        rows: WorksheetRow,
+        cols: WorksheetCol,
+    ) -> Result<SheetOp, XlsxError> {
+        let mut op = self.clone();
+        if rows == 0 && cols == 0 {
+            return Ok(op);
+        }
+        // A position beyond the limits is reported by the worksheet method
+        let move_row = |row: &mut WorksheetRow| *row = row.saturating_add(rows);
+        let move_col = |col: &mut WorksheetCol| *col = col.saturating_add(cols);
+        match &mut op {
+            SheetOp::Number { row, col, .. }
+            | SheetOp::String { row, col, .. }
+            | SheetOp::DateTime { row, col, .. }
+            | SheetOp::Url { row, col, .. }
+            | SheetOp::Boolean { row, col, .. }
+            | SheetOp::Blank { row, col, .. }
+            | SheetOp::Error { row, col, .. }
+            | SheetOp::RichString { row, col, .. }
+            | SheetOp::Image { row, col, .. }
+            | SheetOp::ImageBuffer { row, col, .. } => {
+                move_row(row);
+                move_col(col);
+            }
+            SheetOp::Formula {
+                row, col, formula, ..
+            }
+            | SheetOp::FormulaNum {
+                row, col, formula, ..
+            }
+            | SheetOp::FormulaStr {
+                row, col, formula, ..
+            } => {
+                move_row(row);
+                move_col(col);
+                *formula = formula::offset_references(formula, rows, cols)?;
+            }
+            SheetOp::DataValidationCell {
+                row,
+                col,
+                validation,
+            } => {
+                move_row(row);
+                move_col(col);
+                validation.offset_formula(rows, cols)?;
+            }
+            SheetOp::ConditionalFormatCell {
+                row,
+                col,
+                conditional_format,
+            } => {
+                move_row(row);
+                move_col(col);
+                conditional_format.offset_formulas(rows, cols)?;
+            }
+            SheetOp::DataValidationRange {
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                validation,
+            } => {
+                move_row(first_row);
+                move_col(first_col);
+                move_row(last_row);
+                move_col(last_col);
+                validation.offset_formula(rows, cols)?;
+            }
+            SheetOp::ConditionalFormatRange {
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                conditional_format,
+            } => {
+                move_row(first_row);
+                move_col(first_col);
+                move_row(last_row);
+                move_col(last_col);
+                conditional_format.offset_formulas(rows, cols)?;
+            }
+            SheetOp::MergeRange {
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                ..
+            } => {
+                move_row(first_row);
+                move_col(first_col);
+                move_row(last_row);
+                move_col(last_col);
+            }
+            SheetOp::Row { row, .. } => move_row(row),
+            SheetOp::Column {
+                first_col,
+                last_col,
+                ..
+            } => {
+                move_col(first_col);
+                move_col(last_col);
+            }
+        }
+        Ok(op)
+    }
+}
+
+impl SheetBuffer {
+    /// Returns an empty buffer.
+    pub fn new() -> SheetBuffer {
+        SheetBuffer::default()
+    }
+
+    /// Returns the number of recorded operations.
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if no operation is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns the recorded operations in the order they were recorded.
+    pub fn ops(&self) -> &[SheetOp] {
+        &self.ops
+    }
+
+    /// Returns the last operation which wrote the cell at `row` and `col`, including the first cell of a merged range.
+    /// See [`SheetWriter`](super::SheetWriter) for an example.
+    pub fn cell(&self, row: WorksheetRow, col: WorksheetCol) -> Option<&SheetOp> {
+        self.ops
+            .iter()
+            .rev()
+            .find(|op| op.cell() == Some((row, col)))
+    }
+
+    /// Append the operations of `other` after the operations of this buffer.
+    pub fn append(&mut self, mut other: SheetBuffer) {
+        self.ops.append(&mut other.ops);
+    }
+}
+
+impl Worksheet {
+    /// Write the operations recorded in `buffer` to the worksheet. See [`SheetBuffer`].
+    ///
+    /// Writing stops at the first operation which returns an error. In `constant_memory` mode, the operations should be
+    /// recorded in row order, as if they were written to the worksheet directly.
+    pub fn write_buffer(&mut self, buffer: &SheetBuffer) -> Result<(), XlsxError> {
+        for op in &buffer.ops {
+            op.apply(self)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::worksheet::SheetWriter;
+
+    #[test]
+    fn test_sheet_buffer() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SheetBuffer>();
+
+        let bold = Format::new().set_bold().clone();
+        let mut buffer = SheetBuffer::new();
+        buffer.write_string(0, 0, "Total", Some(&bold)).unwrap();
+        let mut other = SheetBuffer::new();
+        other.write_number(0, 1, 1.5, None).unwrap();
+        buffer.append(other);
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(
+            buffer.ops,
+            [
+                SheetOp::String {
+                    row: 0,
+                    col: 0,
+                    text: "Total".to_string(),
+                    format: Some(bold),
+                },
+                SheetOp::Number {
+                    row: 0,
+                    col: 1,
+                    number: 1.5,
+                    format: None,
+                },
+            ]
+        );
+    }
+}
@@ -48,6 +48,9 @@ pub use three_color::*;
 pub use time_period::*;
 pub use two_color::*;
 
+use crate::error::XlsxErrorSource;
+use crate::formula;
+use crate::utility::{CellRange, CellRef};
 use crate::{
     convert_bool, CStringHelper, Format, StringOrFloat, Workbook, Worksheet, WorksheetCol,
     WorksheetRow, XlsxError,
@@ -198,6 +201,7 @@ impl From<ConditionalFormatCellCriteria> for ConditionalFormatTypes {
 impl ConditionalFormatTypes {
     pub(crate) fn to_internal_value(
         &self,
+        workbook: &Workbook,
         c_string_helper: &mut CStringHelper,
         conditional_format: &mut libxlsxwriter_sys::lxw_conditional_format,
     ) -> Result<(), XlsxError> {
@@ -279,7 +283,8 @@ impl ConditionalFormatTypes {
                 conditional_format.type_ =
                     libxlsxwriter_sys::lxw_conditional_format_types_LXW_CONDITIONAL_TYPE_FORMULA
                         as u8;
-                conditional_format.value_string = c_string_helper.add(formula)? as *mut c_char;
+                conditional_format.value_string =
+                    c_string_helper.add(&workbook.prepare_formula(formula))? as *mut c_char;
             }
         };
         Ok(())
@@ -289,7 +294,7 @@ impl ConditionalFormatTypes {
 /// Conditional Format Criteria and Format.
 ///
 /// Read methods' description in this enum to learn usage.
-#[derive(Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum ConditionalFormat {
     ConditionType {
         criteria: ConditionalFormatTypes,
@@ -593,6 +598,107 @@ impl ConditionalFormat {
         }
     }
 
+    /// Move the relative cell references in the formulas of the conditional format by `rows` and `cols`.
+    /// Criteria which are text to be matched are kept.
+    pub(crate) fn offset_formulas(
+        &mut self,
+        rows: WorksheetRow,
+        cols: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        let mut values: Vec<&mut StringOrFloat> = Vec::new();
+        match self {
+            ConditionalFormat::ConditionType { criteria, .. } => match criteria {
+                ConditionalFormatTypes::Formula(formula) => {
+                    *formula = formula::offset_references(formula, rows, cols)?;
+                }
+                ConditionalFormatTypes::Cell(criteria) => match criteria {
+                    ConditionalFormatCellCriteria::EqualTo(value)
+                    | ConditionalFormatCellCriteria::NotEqualTo(value)
+                    | ConditionalFormatCellCriteria::GreaterThan(value)
+                    | ConditionalFormatCellCriteria::LessThan(value)
+                    | ConditionalFormatCellCriteria::GreaterThanOrEqualTo(value)
+                    | ConditionalFormatCellCriteria::LessThanOrEqualTo(value) => values.push(value),
+                    ConditionalFormatCellCriteria::Between { min, max }
+                    | ConditionalFormatCellCriteria::NotBetween { min, max } => {
+                        values.push(min);
+                        values.push(max);
+                    }
+                },
+                _ => {}
+            },
+            ConditionalFormat::TwoColorScale(criteria) => {
+                values.push(&mut criteria.min_value);
+                values.push(&mut criteria.max_value);
+            }
+            ConditionalFormat::ThreeColorScale(criteria) => {
+                values.push(&mut criteria.min_value);
+                values.push(&mut criteria.mid_value);
+                values.push(&mut criteria.max_value);
+            }
+            ConditionalFormat::DataBar(data_bar) => {
+                values.push(&mut data_bar.min_value);
+                values.push(&mut data_bar.max_value);
+            }
+            ConditionalFormat::IconSet(_) => {}
+        }
+        for value in values {
+            if let StringOrFloat::String(formula) = value {
+                *formula = formula::offset_references(formula, rows, cols)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an error for a NaN or infinite number in the criteria, which Excel doesn't support.
+    pub(crate) fn check_numbers(&self) -> Result<(), XlsxError> {
+        let mut values: Vec<&StringOrFloat> = Vec::new();
+        let mut numbers: Vec<f64> = Vec::new();
+        match self {
+            ConditionalFormat::ConditionType { criteria, .. } => match criteria {
+                ConditionalFormatTypes::Cell(criteria) => match criteria {
+                    ConditionalFormatCellCriteria::EqualTo(value)
+                    | ConditionalFormatCellCriteria::NotEqualTo(value)
+                    | ConditionalFormatCellCriteria::GreaterThan(value)
+                    | ConditionalFormatCellCriteria::LessThan(value)
+                    | ConditionalFormatCellCriteria::GreaterThanOrEqualTo(value)
+                    | ConditionalFormatCellCriteria::LessThanOrEqualTo(value) => values.push(value),
+                    ConditionalFormatCellCriteria::Between { min, max }
+                    | ConditionalFormatCellCriteria::NotBetween { min, max } => {
+                        values.push(min);
+                        values.push(max);
+                    }
+                },
+                ConditionalFormatTypes::Top(TopOrBottomCriteria::TopOrBottomPercent(percent))
+                | ConditionalFormatTypes::Bottom(TopOrBottomCriteria::TopOrBottomPercent(
+                    percent,
+                )) => numbers.push(*percent),
+                _ => {}
+            },
+            ConditionalFormat::TwoColorScale(criteria) => {
+                values.push(&criteria.min_value);
+                values.push(&criteria.max_value);
+            }
+            ConditionalFormat::ThreeColorScale(criteria) => {
+                values.push(&criteria.min_value);
+                values.push(&criteria.mid_value);
+                values.push(&criteria.max_value);
+            }
+            ConditionalFormat::DataBar(data_bar) => {
+                values.push(&data_bar.min_value);
+                values.push(&data_bar.max_value);
+            }
+            ConditionalFormat::IconSet(_) => {}
+        }
+        numbers.extend(values.into_iter().filter_map(|value| match value {
+            StringOrFloat::Float(x) => Some(*x),
+            StringOrFloat::String(_) => None,
+        }));
+        match numbers.into_iter().find(|x| !x.is_finite()) {
+            Some(x) => Err(XlsxErrorSource::NonFiniteNumber(x).into()),
+            None => Ok(()),
+        }
+    }
+
     pub(crate) fn to_internal_type(
         &self,
         workbook: &Workbook,
@@ -639,7 +745,7 @@ impl ConditionalFormat {
             ConditionalFormat::ConditionType { criteria, format } => {
                 let internal_format = workbook.get_internal_format(format)?;
                 conditional_format.format = internal_format;
-                criteria.to_internal_value(c_string_helper, &mut conditional_format)?;
+                criteria.to_internal_value(workbook, c_string_helper, &mut conditional_format)?;
             }
             ConditionalFormat::TwoColorScale(criteria) => {
                 criteria.to_internal_value(c_string_helper, &mut conditional_format)?;
@@ -659,19 +765,24 @@ impl ConditionalFormat {
     }
 }
 
-impl<'a> Worksheet<'a> {
+impl Worksheet {
     pub fn conditional_format_cell(
         &mut self,
         row: WorksheetRow,
         col: WorksheetCol,
         conditional_format: &ConditionalFormat,
     ) -> Result<(), XlsxError> {
+        self.check_cell("conditional_format_cell", row, col)?;
+        conditional_format
+            .check_numbers()
+            .map_err(|e| self.cell_error(e, "conditional_format_cell", row, col))?;
         let mut c_string_helper = CStringHelper::new();
         unsafe {
-            let mut conditional_format =
-                conditional_format.to_internal_type(self._workbook, &mut c_string_helper)?;
+            let mut conditional_format = conditional_format
+                .to_internal_type(&self._workbook, &mut c_string_helper)
+                .map_err(|e| self.cell_error(e, "conditional_format_cell", row, col))?;
             let result = libxlsxwriter_sys::worksheet_conditional_format_cell(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 &mut conditional_format,
@@ -681,11 +792,20 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "conditional_format_cell", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::conditional_format_cell`], with the cell given as a [`CellRef`].
+    pub fn conditional_format_cell_ref(
+        &mut self,
+        cell: &CellRef,
+        conditional_format: &ConditionalFormat,
+    ) -> Result<(), XlsxError> {
+        self.conditional_format_cell(cell.row, cell.col, conditional_format)
+    }
+
     pub fn conditional_format_range(
         &mut self,
         first_row: WorksheetRow,
@@ -694,12 +814,39 @@ impl<'a> Worksheet<'a> {
         last_col: WorksheetCol,
         conditional_format: &ConditionalFormat,
     ) -> Result<(), XlsxError> {
+        self.check_range(
+            "conditional_format_range",
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+        )?;
+        conditional_format.check_numbers().map_err(|e| {
+            self.range_error(
+                e,
+                "conditional_format_range",
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            )
+        })?;
         let mut c_string_helper = CStringHelper::new();
         unsafe {
-            let mut conditional_format =
-                conditional_format.to_internal_type(self._workbook, &mut c_string_helper)?;
+            let mut conditional_format = conditional_format
+                .to_internal_type(&self._workbook, &mut c_string_helper)
+                .map_err(|e| {
+                    self.range_error(
+                        e,
+                        "conditional_format_range",
+                        first_row,
+                        first_col,
+                        last_row,
+                        last_col,
+                    )
+                })?;
             let result = libxlsxwriter_sys::worksheet_conditional_format_range(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
@@ -711,10 +858,28 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "conditional_format_range",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
+
+    /// Same as [`Worksheet::conditional_format_range`], with the range given as a [`CellRange`].
+    pub fn conditional_format_range_ref(
+        &mut self,
+        range: &CellRange,
+        conditional_format: &ConditionalFormat,
+    ) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) =
+            self.range_bounds(range, "conditional_format_range")?;
+        self.conditional_format_range(first_row, first_col, last_row, last_col, conditional_format)
+    }
 }
 
 #[cfg(test)]
@@ -745,4 +910,39 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_worksheet_conditional_format_non_finite() -> Result<(), XlsxError> {
+        let workbook = Workbook::new("test-worksheet_conditional-format-non_finite.xlsx")?;
+        let mut worksheet = workbook.add_worksheet(None)?;
+        let format = Format::new();
+        for conditional_format in [
+            ConditionalFormat::top_percent(f64::NAN, &format),
+            ConditionalFormat::cell_greater_than(f64::INFINITY, &format),
+            ConditionalFormat::cell_between(0., f64::NEG_INFINITY, &format),
+        ] {
+            assert_eq!(
+                worksheet
+                    .conditional_format_range(0, 0, 10, 0, &conditional_format)
+                    .unwrap_err()
+                    .kind(),
+                XlsxErrorKind::NonFiniteNumber
+            );
+            assert_eq!(
+                worksheet
+                    .conditional_format_cell(0, 0, &conditional_format)
+                    .unwrap_err()
+                    .kind(),
+                XlsxErrorKind::NonFiniteNumber
+            );
+        }
+        worksheet.conditional_format_range(
+            0,
+            0,
+            10,
+            0,
+            &ConditionalFormat::cell_greater_than("=$B$1", &format),
+        )?;
+        workbook.close()
+    }
 }
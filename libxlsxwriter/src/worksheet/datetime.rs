@@ -3,6 +3,93 @@ use chrono::{Datelike, Timelike};
 
 use super::DateTime;
 
+/// Date system of a workbook. Excel for Windows uses the 1900 epoch and older Excel for Mac used the 1904 epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum DateEpoch {
+    /// Serial `1` is 1900-01-01. Excel incorrectly treats 1900 as a leap year, so serial `60` is 1900-02-29.
+    #[default]
+    Excel1900,
+    /// Serial `0` is 1904-01-01.
+    Excel1904,
+}
+
+/// How [`Worksheet::write_datetime_tz`](super::Worksheet::write_datetime_tz) converts a time zone aware datetime into the wall clock time stored in a cell.
+/// Excel has no notion of time zones.
+///
+/// `Utc` and `WallClock` have no target, so their type parameter cannot be inferred from the value and is left at its default
+/// when written as `TimeZonePolicy::<Utc>::Utc` or bound to a variable of type `TimeZonePolicy`.
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeZonePolicy<Target: chrono::TimeZone = chrono::Utc> {
+    /// Convert to UTC.
+    Utc,
+    /// Convert to the given time zone. The offset is that of the time zone at each value, so daylight saving time is applied
+    /// by time zones such as [`chrono::Local`], and not by a [`chrono::FixedOffset`].
+    Convert(Target),
+    /// Keep the wall clock time in the time zone of the value.
+    WallClock,
+}
+
+/// A span of time written by [`Worksheet::write_duration`](super::Worksheet::write_duration) as a number of days.
+/// It is converted from a [`std::time::Duration`] or, with the `chrono` feature, from a [`chrono::Duration`], which can be negative.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct DurationValue {
+    pub(crate) days: f64,
+}
+
+impl From<std::time::Duration> for DurationValue {
+    fn from(duration: std::time::Duration) -> Self {
+        DurationValue {
+            days: duration.as_secs_f64() / SECONDS_PER_DAY,
+        }
+    }
+}
+
+/// The duration is taken with microsecond precision, or millisecond precision beyond about 290,000 years.
+#[cfg(feature = "chrono")]
+impl From<chrono::Duration> for DurationValue {
+    fn from(duration: chrono::Duration) -> Self {
+        let seconds = match duration.num_microseconds() {
+            Some(micros) => micros as f64 / 1_000_000.,
+            None => duration.num_milliseconds() as f64 / 1000.,
+        };
+        DurationValue {
+            days: seconds / SECONDS_PER_DAY,
+        }
+    }
+}
+
+const SECONDS_PER_DAY: f64 = 24. * 60. * 60.;
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 > 0 || year % 400 == 0)
+}
+
+/// Number of days since 1970-01-01 of the given proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Proleptic Gregorian date of the given number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
 impl DateTime {
     #[must_use]
     pub fn new(year: i16, month: i8, day: i8, hour: i8, min: i8, second: f64) -> DateTime {
@@ -39,6 +126,132 @@ impl DateTime {
             second,
         }
     }
+
+    /// Returns `false` for a time only value, which has year `0` or is the Excel date 1900-01-00 used by the `chrono` and `time` conversions.
+    pub fn has_date(&self) -> bool {
+        !(self.year == 0 || (self.year == 1900 && self.month == 1 && self.day == 0))
+    }
+
+    /// Returns `true` if any of hour, minute or second is not zero.
+    pub fn has_time(&self) -> bool {
+        self.hour != 0 || self.min != 0 || self.second != 0.
+    }
+
+    /// Convert to an Excel serial date, the number of days since the epoch with the time as a fraction of a day.
+    /// A time only value, such as the one created by [`DateTime::time`] or converted from `chrono` or `time`, is converted to a fraction
+    /// of a day in both epochs. See [`DateTime::has_date`].
+    ///
+    /// The conversion is the same as the one used by [`Worksheet::write_datetime`](super::Worksheet::write_datetime), including the 1900 leap year bug of Excel.
+    /// ```rust
+    /// use xlsxwriter::worksheet::{DateEpoch, DateTime};
+    /// assert_eq!(DateTime::date(1900, 1, 1).to_excel_serial(DateEpoch::Excel1900), 1.);
+    /// assert_eq!(DateTime::date(1900, 3, 1).to_excel_serial(DateEpoch::Excel1900), 61.);
+    /// assert_eq!(DateTime::new(2013, 1, 1, 12, 0, 0.).to_excel_serial(DateEpoch::Excel1900), 41275.5);
+    /// assert_eq!(DateTime::date(2013, 1, 1).to_excel_serial(DateEpoch::Excel1904), 39813.);
+    /// assert_eq!(DateTime::time(6, 0, 0.).to_excel_serial(DateEpoch::Excel1900), 0.25);
+    /// assert_eq!(DateTime::time(6, 0, 0.).to_excel_serial(DateEpoch::Excel1904), 0.25);
+    /// ```
+    pub fn to_excel_serial(&self, epoch: DateEpoch) -> f64 {
+        let date_1904 = epoch == DateEpoch::Excel1904;
+        let seconds = f64::from(self.hour) * 60. * 60. + f64::from(self.min) * 60. + self.second;
+        if !self.has_date() {
+            return seconds / SECONDS_PER_DAY;
+        }
+        let (year, month, day): (i64, i64, i64) =
+            (self.year.into(), self.month.into(), self.day.into());
+
+        if !date_1904 {
+            if (year, month, day) == (1899, 12, 31) {
+                return seconds / SECONDS_PER_DAY;
+            }
+            if (year, month, day) == (1900, 2, 29) {
+                return 60. + seconds / SECONDS_PER_DAY;
+            }
+        }
+
+        let (epoch_year, offset, norm): (i64, i64, i64) = if date_1904 {
+            (1904, 4, 300)
+        } else {
+            (1900, 0, 300)
+        };
+        let leap = is_leap_year(year);
+        let days_in_month = [
+            0,
+            31,
+            if leap { 29 } else { 28 },
+            31,
+            30,
+            31,
+            30,
+            31,
+            31,
+            30,
+            31,
+            30,
+            31,
+        ];
+        let range = year - epoch_year;
+
+        let mut days: i64 = days_in_month.iter().take(month.clamp(0, 13) as usize).sum();
+        days += day;
+        days += range * 365;
+        days += range / 4;
+        days -= (range + offset) / 100;
+        days += (range + offset + norm) / 400;
+        if leap {
+            days -= 1;
+        }
+        if !date_1904 && days > 59 {
+            days += 1;
+        }
+        days as f64 + seconds / SECONDS_PER_DAY
+    }
+
+    /// Convert from an Excel serial date. This is the inverse of [`DateTime::to_excel_serial`].
+    /// Seconds are rounded to milliseconds, which is the precision Excel displays.
+    ///
+    /// With the 1900 epoch, a serial less than `1` is converted to a time only value with year `0`, and serial `60` is converted to the nonexistent date 1900-02-29.
+    /// `None` is returned for negative or non-finite serials and for dates after 9999-12-31.
+    /// ```rust
+    /// use xlsxwriter::worksheet::{DateEpoch, DateTime};
+    /// assert_eq!(DateTime::from_excel_serial(41275.5, DateEpoch::Excel1900), Some(DateTime::new(2013, 1, 1, 12, 0, 0.)));
+    /// assert_eq!(DateTime::from_excel_serial(60., DateEpoch::Excel1900), Some(DateTime::date(1900, 2, 29)));
+    /// assert_eq!(DateTime::from_excel_serial(0.25, DateEpoch::Excel1900), Some(DateTime::time(6, 0, 0.)));
+    /// assert_eq!(DateTime::from_excel_serial(0., DateEpoch::Excel1904), Some(DateTime::date(1904, 1, 1)));
+    /// assert_eq!(DateTime::from_excel_serial(-1., DateEpoch::Excel1900), None);
+    /// ```
+    pub fn from_excel_serial(serial: f64, epoch: DateEpoch) -> Option<DateTime> {
+        if !(0. ..3_000_000.).contains(&serial) {
+            return None;
+        }
+        let total_millis = (serial * SECONDS_PER_DAY * 1000.).round() as i64;
+        let days = total_millis / (86_400 * 1000);
+        let millis = total_millis % (86_400 * 1000);
+        let hour = (millis / (60 * 60 * 1000)) as i8;
+        let min = (millis / (60 * 1000) % 60) as i8;
+        let second = (millis % (60 * 1000)) as f64 / 1000.;
+
+        let (year, month, day) = match epoch {
+            DateEpoch::Excel1900 if days == 0 => return Some(DateTime::time(hour, min, second)),
+            DateEpoch::Excel1900 if days == 60 => (1900, 2, 29),
+            DateEpoch::Excel1900 if days < 60 => {
+                civil_from_days(days_from_civil(1899, 12, 31) + days)
+            }
+            DateEpoch::Excel1900 => civil_from_days(days_from_civil(1899, 12, 31) + days - 1),
+            DateEpoch::Excel1904 => civil_from_days(days_from_civil(1904, 1, 1) + days),
+        };
+        if year > 9999 {
+            return None;
+        }
+        Some(DateTime::new(
+            year as i16,
+            month as i8,
+            day as i8,
+            hour,
+            min,
+            second,
+        ))
+    }
 }
 
 #[cfg(feature = "chrono")]
@@ -83,15 +296,147 @@ impl From<chrono::naive::NaiveTime> for DateTime {
     }
 }
 
+#[cfg(feature = "time")]
+impl From<time::PrimitiveDateTime> for DateTime {
+    fn from(datetime: time::PrimitiveDateTime) -> Self {
+        DateTime {
+            year: datetime.year() as i16,
+            month: u8::from(datetime.month()) as i8,
+            day: datetime.day() as i8,
+            hour: datetime.hour() as i8,
+            min: datetime.minute() as i8,
+            second: datetime.second().into(),
+        }
+    }
+}
+
+/// The date and time are taken as the wall clock time at the offset of the value.
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for DateTime {
+    fn from(datetime: time::OffsetDateTime) -> Self {
+        time::PrimitiveDateTime::new(datetime.date(), datetime.time()).into()
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Date> for DateTime {
+    fn from(datetime: time::Date) -> Self {
+        DateTime {
+            year: datetime.year() as i16,
+            month: u8::from(datetime.month()) as i8,
+            day: datetime.day() as i8,
+            hour: 0,
+            min: 0,
+            second: 0.,
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Time> for DateTime {
+    fn from(datetime: time::Time) -> Self {
+        DateTime {
+            year: 1900,
+            month: 1,
+            day: 0,
+            hour: datetime.hour() as i8,
+            min: datetime.minute() as i8,
+            second: datetime.second().into(),
+        }
+    }
+}
+
+/// A time only value is passed with year `0`, which libxlsxwriter converts to a fraction of a day in both epochs.
+/// The date 1900-01-00 is special cased by libxlsxwriter only in the 1900 epoch.
 impl From<&DateTime> for libxlsxwriter_sys::lxw_datetime {
     fn from(datetime: &DateTime) -> Self {
+        let (year, month, day) = if datetime.has_date() {
+            (datetime.year, datetime.month, datetime.day)
+        } else {
+            (0, 0, 0)
+        };
         libxlsxwriter_sys::lxw_datetime {
-            year: datetime.year.into(),
-            month: datetime.month.into(),
-            day: datetime.day.into(),
+            year: year.into(),
+            month: month.into(),
+            day: day.into(),
             hour: datetime.hour.into(),
             min: datetime.min.into(),
             sec: datetime.second,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_time_only_epochs() {
+        let time = DateTime::time(6, 0, 0.);
+        assert_eq!(time.to_excel_serial(DateEpoch::Excel1900), 0.25);
+        assert_eq!(time.to_excel_serial(DateEpoch::Excel1904), 0.25);
+
+        let datetime = libxlsxwriter_sys::lxw_datetime::from(&time);
+        assert_eq!((datetime.year, datetime.month, datetime.day), (0, 0, 0));
+        assert_eq!(datetime.hour, 6);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_time_only_epochs() {
+        let time: DateTime = chrono::NaiveTime::from_hms_opt(6, 0, 0).unwrap().into();
+        assert!(!time.has_date());
+        assert_eq!(time.to_excel_serial(DateEpoch::Excel1900), 0.25);
+        assert_eq!(time.to_excel_serial(DateEpoch::Excel1904), 0.25);
+
+        let datetime = libxlsxwriter_sys::lxw_datetime::from(&time);
+        assert_eq!((datetime.year, datetime.month, datetime.day), (0, 0, 0));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_from_time() {
+        let date = time::Date::from_calendar_date(2024, time::Month::July, 15).unwrap();
+        let clock = time::Time::from_hms(14, 30, 15).unwrap();
+        let datetime = time::PrimitiveDateTime::new(date, clock);
+        assert_eq!(
+            DateTime::from(datetime),
+            DateTime::new(2024, 7, 15, 14, 30, 15.)
+        );
+        assert_eq!(DateTime::from(date), DateTime::date(2024, 7, 15));
+        let clock = DateTime::from(clock);
+        assert!(!clock.has_date());
+        assert_eq!((clock.hour, clock.min, clock.second), (14, 30, 15.));
+        assert_eq!(
+            clock.to_excel_serial(DateEpoch::Excel1904),
+            DateTime::time(14, 30, 15.).to_excel_serial(DateEpoch::Excel1904)
+        );
+
+        // The wall clock time at the offset, not UTC
+        let offset = datetime.assume_offset(time::UtcOffset::from_hms(9, 0, 0).unwrap());
+        assert_eq!(
+            DateTime::from(offset),
+            DateTime::new(2024, 7, 15, 14, 30, 15.)
+        );
+        assert_eq!(
+            DateTime::from(offset.to_offset(time::UtcOffset::UTC)),
+            DateTime::new(2024, 7, 15, 5, 30, 15.)
+        );
+
+        let date = DateTime::from(date);
+        assert_eq!(date.to_excel_serial(DateEpoch::Excel1900), 45488.);
+        assert_eq!(date.to_excel_serial(DateEpoch::Excel1904), 44026.);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time_crate_time_only_epochs() {
+        let time: DateTime = time::Time::from_hms(6, 0, 0).unwrap().into();
+        assert!(!time.has_date());
+        assert_eq!(time.to_excel_serial(DateEpoch::Excel1900), 0.25);
+        assert_eq!(time.to_excel_serial(DateEpoch::Excel1904), 0.25);
+
+        let datetime = libxlsxwriter_sys::lxw_datetime::from(&time);
+        assert_eq!((datetime.year, datetime.month, datetime.day), (0, 0, 0));
+    }
+}
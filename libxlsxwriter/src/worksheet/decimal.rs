@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::error::XlsxErrorSource;
+use crate::utility::CellRef;
+use crate::{Format, Worksheet, XlsxError};
+
+use super::{WorksheetCol, WorksheetRow};
+
+/// How [`Worksheet::write_decimal_opt`] handles a decimal which cannot be represented exactly as a number cell.
+///
+/// Excel stores all numbers as IEEE-754 double precision floats, which hold about 15 significant digits.
+/// A decimal is regarded as exact when the shortest decimal representation of the nearest float is equal to the decimal,
+/// for example `0.1` or `12345.678`. A decimal like `0.1234567890123456789` is not exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum DecimalPolicy {
+    /// Write the nearest float to the decimal as a number.
+    #[default]
+    Round,
+    /// Write an inexact decimal as a string, keeping all digits. Exact decimals are written as numbers.
+    String,
+    /// Return an error for an inexact decimal. Exact decimals are written as numbers.
+    Error,
+}
+
+fn is_exact(value: &Decimal, number: f64) -> bool {
+    number.is_finite()
+        && Decimal::from_str(&number.to_string())
+            .map(|x| x == *value)
+            .unwrap_or(false)
+}
+
+impl Worksheet {
+    /// This function writes a [`Decimal`] to the cell specified by row and column as a number.
+    /// The decimal is rounded to the nearest float. See [`Worksheet::write_decimal_opt`] to handle decimals which cannot be represented exactly.
+    /// This function is available with `rust_decimal` feature.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # use std::str::FromStr;
+    /// use rust_decimal::Decimal;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_decimal-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.write_decimal(0, 0, &Decimal::from_str("1234.56").unwrap(), None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn write_decimal(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        value: &Decimal,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_decimal_opt(row, col, value, format, DecimalPolicy::Round)
+    }
+
+    /// Same as [`Worksheet::write_decimal`], with the cell given as a [`CellRef`].
+    pub fn write_decimal_ref(
+        &mut self,
+        cell: &CellRef,
+        value: &Decimal,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_decimal(cell.row, cell.col, value, format)
+    }
+
+    /// This function writes a [`Decimal`] to the cell specified by row and column.
+    /// `policy` decides how a decimal which cannot be represented exactly as a number is written.
+    /// This function is available with `rust_decimal` feature.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # use std::str::FromStr;
+    /// use rust_decimal::Decimal;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_decimal_opt-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// // Written as number
+    /// worksheet.write_decimal_opt(0, 0, &Decimal::from_str("0.1").unwrap(), None, DecimalPolicy::String)?;
+    /// // Written as string
+    /// worksheet.write_decimal_opt(1, 0, &Decimal::from_str("0.1234567890123456789").unwrap(), None, DecimalPolicy::String)?;
+    /// // Error
+    /// assert!(worksheet.write_decimal_opt(2, 0, &Decimal::from_str("0.1234567890123456789").unwrap(), None, DecimalPolicy::Error).is_err());
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn write_decimal_opt(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        value: &Decimal,
+        format: Option<&Format>,
+        policy: DecimalPolicy,
+    ) -> Result<(), XlsxError> {
+        let number = value.to_f64().ok_or_else(XlsxError::unknown_error)?;
+        if policy == DecimalPolicy::Round || is_exact(value, number) {
+            return self.write_number(row, col, number, format);
+        }
+        match policy {
+            DecimalPolicy::String => self.write_string(row, col, &value.to_string(), format),
+            _ => Err(self.cell_error(
+                XlsxErrorSource::InexactDecimal(*value).into(),
+                "write_decimal_opt",
+                row,
+                col,
+            )),
+        }
+    }
+
+    /// Same as [`Worksheet::write_decimal_opt`], with the cell given as a [`CellRef`].
+    pub fn write_decimal_opt_ref(
+        &mut self,
+        cell: &CellRef,
+        value: &Decimal,
+        format: Option<&Format>,
+        policy: DecimalPolicy,
+    ) -> Result<(), XlsxError> {
+        self.write_decimal_opt(cell.row, cell.col, value, format, policy)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_exact() {
+        for (text, exact) in [
+            ("0", true),
+            ("0.1", true),
+            ("-12345.678", true),
+            ("1.50", true),
+            ("123456789012345", true),
+            ("0.1234567890123456789", false),
+            ("12345678901234567890", false),
+        ] {
+            let value = Decimal::from_str(text).unwrap();
+            assert_eq!(is_exact(&value, value.to_f64().unwrap()), exact, "{}", text);
+        }
+    }
+}
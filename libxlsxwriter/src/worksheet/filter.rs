@@ -1,3 +1,4 @@
+use crate::utility::CellRange;
 use crate::{
     try_to_vec, CStringHelper, StringOrFloat, Worksheet, WorksheetCol, WorksheetRow, XlsxError,
 };
@@ -102,7 +103,7 @@ impl FilterRule {
     }
 }
 
-impl<'a> Worksheet<'a> {
+impl Worksheet {
     /// This function allows an autofilter to be added to a worksheet.
     ///
     /// An autofilter is a way of adding drop down lists to the headers of a 2D range of worksheet data.
@@ -115,9 +116,10 @@ impl<'a> Worksheet<'a> {
         last_row: WorksheetRow,
         last_col: WorksheetCol,
     ) -> Result<(), XlsxError> {
+        self.check_range("autofilter", first_row, first_col, last_row, last_col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_autofilter(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
@@ -126,11 +128,24 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "autofilter",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
 
+    /// Same as [`Worksheet::autofilter`], with the range given as a [`CellRange`].
+    pub fn autofilter_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) = self.range_bounds(range, "autofilter")?;
+        self.autofilter(first_row, first_col, last_row, last_col)
+    }
+
     /// This function can be used to filter columns in a autofilter range based on single rule conditions.
     ///
     /// ```rust
@@ -164,14 +179,16 @@ impl<'a> Worksheet<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             let mut c_string_helper = CStringHelper::new();
-            let mut rule_converted = rule.to_internal(&mut c_string_helper)?;
+            let mut rule_converted = rule
+                .to_internal(&mut c_string_helper)
+                .map_err(|e| self.columns_error(e, "filter_column", col, col))?;
             let e = libxlsxwriter_sys::worksheet_filter_column(
-                self.worksheet,
+                self.raw_worksheet()?,
                 col,
                 &mut rule_converted,
             );
             if e != libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
-                return Err(XlsxError::new(e));
+                return Err(self.columns_error(XlsxError::new(e), "filter_column", col, col));
             }
         }
         Ok(())
@@ -211,17 +228,21 @@ impl<'a> Worksheet<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             let mut c_string_helper = CStringHelper::new();
-            let mut rule_converted1 = rule1.to_internal(&mut c_string_helper)?;
-            let mut rule_converted2 = rule2.to_internal(&mut c_string_helper)?;
+            let mut rule_converted1 = rule1
+                .to_internal(&mut c_string_helper)
+                .map_err(|e| self.columns_error(e, "filter_column2", col, col))?;
+            let mut rule_converted2 = rule2
+                .to_internal(&mut c_string_helper)
+                .map_err(|e| self.columns_error(e, "filter_column2", col, col))?;
             let e = libxlsxwriter_sys::worksheet_filter_column2(
-                self.worksheet,
+                self.raw_worksheet()?,
                 col,
                 &mut rule_converted1,
                 &mut rule_converted2,
                 and_or.into_internal() as u8,
             );
             if e != libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
-                return Err(XlsxError::new(e));
+                return Err(self.columns_error(XlsxError::new(e), "filter_column2", col, col));
             }
         }
         Ok(())
@@ -259,16 +280,17 @@ impl<'a> Worksheet<'a> {
         list: &[&str],
     ) -> Result<(), XlsxError> {
         let mut cstring_helper = crate::CStringHelper::new();
-        let mut cstr_list: Vec<_> = try_to_vec(list.iter().map(|x| Ok(cstring_helper.add(x)?)))?;
+        let mut cstr_list: Vec<_> = try_to_vec(list.iter().map(|x| Ok(cstring_helper.add(x)?)))
+            .map_err(|e| self.columns_error(e, "filter_list", col, col))?;
         cstr_list.push(std::ptr::null());
         unsafe {
             let result = libxlsxwriter_sys::worksheet_filter_list(
-                self.worksheet,
+                self.raw_worksheet()?,
                 col,
                 cstr_list.as_mut_ptr(),
             );
             if result != libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
-                return Err(XlsxError::new(result));
+                return Err(self.columns_error(XlsxError::new(result), "filter_list", col, col));
             }
         }
         Ok(())
@@ -1,16 +1,34 @@
+mod buffer;
 pub mod conditional_format;
 mod datetime;
+#[cfg(feature = "rust_decimal")]
+mod decimal;
 pub mod filter;
+#[cfg(feature = "arrow")]
+pub mod record_batch;
 pub mod table;
+mod template;
 pub mod validation;
+mod writer;
 
+use crate::error::ErrorContext;
+use crate::error::XlsxErrorSource;
+use crate::evaluator::FormulaValue;
+use crate::limits::{self, NonFiniteNumberPolicy, OverflowPolicy, OverflowWarning};
+use crate::utility::{CellRange, CellRef};
 use crate::CStringHelper;
 
 use super::{convert_bool, Chart, Format, FormatColor, Workbook, XlsxError};
+use std::borrow::Cow;
 use std::ffi::CString;
 use std::os::raw::c_char;
 
+pub use buffer::{SheetBuffer, SheetOp};
 pub use datetime::*;
+#[cfg(feature = "rust_decimal")]
+pub use decimal::*;
+pub use template::{ReplayOptions, SheetTemplate, TemplateValue};
+pub use writer::{SheetWriter, WorkbookWriter};
 
 /// Integer data type to represent a column value. Equivalent to `u16`.
 ///
@@ -338,6 +356,46 @@ impl Default for CommentOptions {
     }
 }
 
+/// Excel error values which can be written with [`Worksheet::write_error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CellError {
+    /// `#NULL!`
+    Null,
+    /// `#DIV/0!`
+    Div0,
+    /// `#VALUE!`
+    Value,
+    /// `#REF!`
+    Ref,
+    /// `#NAME?`
+    Name,
+    /// `#NUM!`
+    Num,
+    /// `#N/A`
+    NA,
+}
+
+impl CellError {
+    /// Text of the error value as displayed by Excel, such as `#N/A`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CellError::Null => "#NULL!",
+            CellError::Div0 => "#DIV/0!",
+            CellError::Value => "#VALUE!",
+            CellError::Ref => "#REF!",
+            CellError::Name => "#NAME?",
+            CellError::Num => "#NUM!",
+            CellError::NA => "#N/A",
+        }
+    }
+}
+
+impl std::fmt::Display for CellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Default Excel row height in character units.
 pub const LXW_DEF_ROW_HEIGHT: f64 = 15.0;
 /// Default Excel row height in pixels.
@@ -361,12 +419,290 @@ pub const LXW_DEF_COL_WIDTH_PIXELS: u32 = 64;
 /// ```
 /// Please read [original libxlsxwriter document](https://libxlsxwriter.github.io/worksheet_8h.html) for description missing functions.
 /// Most of this document is based on libxlsxwriter document.
-pub struct Worksheet<'a> {
-    pub(crate) _workbook: &'a Workbook,
+pub struct Worksheet {
+    pub(crate) _workbook: Workbook,
     pub(crate) worksheet: *mut libxlsxwriter_sys::lxw_worksheet,
+    name: String,
 }
 
-impl<'a> Worksheet<'a> {
+impl Drop for Worksheet {
+    fn drop(&mut self) {
+        self._workbook.release_worksheet(self.worksheet);
+    }
+}
+
+impl Worksheet {
+    /// Returns the name of the worksheet.
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet-name.xlsx")?;
+    /// let worksheet = workbook.add_worksheet(None)?;
+    /// assert_eq!(worksheet.name(), "Sheet1");
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub(crate) fn new(
+        workbook: Workbook,
+        worksheet: *mut libxlsxwriter_sys::lxw_worksheet,
+        name: String,
+    ) -> Worksheet {
+        Worksheet {
+            _workbook: workbook,
+            worksheet,
+            name,
+        }
+    }
+
+    /// Returns the libxlsxwriter worksheet, or an error if the workbook is already closed.
+    pub(crate) fn raw_worksheet(&self) -> Result<*mut libxlsxwriter_sys::lxw_worksheet, XlsxError> {
+        if self._workbook.is_closed() {
+            Err(
+                XlsxError::from(XlsxErrorSource::WorkbookClosed).with_context(ErrorContext {
+                    sheet_name: Some(self.name()),
+                    ..ErrorContext::default()
+                }),
+            )
+        } else {
+            Ok(self.worksheet)
+        }
+    }
+
+    pub(crate) fn sheet_error(&self, error: XlsxError, method: &'static str) -> XlsxError {
+        error.with_context(ErrorContext {
+            sheet_name: Some(self.name()),
+            method: Some(method),
+            ..ErrorContext::default()
+        })
+    }
+
+    pub(crate) fn cell_error(
+        &self,
+        error: XlsxError,
+        method: &'static str,
+        row: WorksheetRow,
+        col: WorksheetCol,
+    ) -> XlsxError {
+        self.range_error(error, method, row, col, row, col)
+    }
+
+    pub(crate) fn cell_context(
+        &self,
+        method: &'static str,
+        row: WorksheetRow,
+        col: WorksheetCol,
+    ) -> ErrorContext {
+        ErrorContext {
+            sheet_name: Some(self.name()),
+            first_cell: Some((row, col)),
+            last_cell: Some((row, col)),
+            method: Some(method),
+        }
+    }
+
+    pub(crate) fn check_cell(
+        &self,
+        method: &'static str,
+        row: WorksheetRow,
+        col: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        self.check_range(method, row, col, row, col)
+    }
+
+    pub(crate) fn check_range(
+        &self,
+        method: &'static str,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        if limits::is_cell_in_range(first_row, first_col)
+            && limits::is_cell_in_range(last_row, last_col)
+        {
+            Ok(())
+        } else {
+            Err(self.range_error(
+                XlsxError::new(libxlsxwriter_sys::lxw_error_LXW_ERROR_WORKSHEET_INDEX_OUT_OF_RANGE),
+                method,
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            ))
+        }
+    }
+
+    /// Returns the first row, first column, last row and last column of `range`, or an error if the range refers to another sheet.
+    pub(crate) fn range_bounds(
+        &self,
+        range: &CellRange,
+        method: &'static str,
+    ) -> Result<(WorksheetRow, WorksheetCol, WorksheetRow, WorksheetCol), XlsxError> {
+        let bounds = (
+            range.first.row,
+            range.first.col,
+            range.last.row,
+            range.last.col,
+        );
+        match &range.sheet_name {
+            Some(sheet_name) if sheet_name.to_lowercase() != self.name().to_lowercase() => {
+                Err(self.range_error(
+                    XlsxErrorSource::InvalidCellReference(range.to_string()).into(),
+                    method,
+                    bounds.0,
+                    bounds.1,
+                    bounds.2,
+                    bounds.3,
+                ))
+            }
+            _ => Ok(bounds),
+        }
+    }
+
+    pub(crate) fn fit_cell_text<'t>(
+        &self,
+        text: &'t str,
+        limit: usize,
+        error: libxlsxwriter_sys::lxw_error,
+        method: &'static str,
+        row: WorksheetRow,
+        col: WorksheetCol,
+    ) -> Result<Cow<'t, str>, XlsxError> {
+        self._workbook
+            .fit_text(text, limit, error, self.cell_context(method, row, col))
+    }
+
+    /// Returns the result of `formula` if the formula evaluator of the workbook is enabled.
+    fn evaluate_formula(&self, formula: &str) -> Option<FormulaValue> {
+        let evaluator = self._workbook.evaluator().borrow();
+        evaluator
+            .as_ref()
+            .map(|evaluator| evaluator.evaluate(&self.name(), formula))
+    }
+
+    /// Tell the value written to a cell to the formula evaluator of the workbook, if it is enabled.
+    fn record_value<F: FnOnce() -> FormulaValue>(
+        &self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        value: F,
+    ) {
+        if let Some(evaluator) = self._workbook.evaluator().borrow_mut().as_mut() {
+            evaluator.set_value(&self.name(), row, col, value());
+        }
+    }
+
+    /// Write a NaN or infinite number with the policy of the workbook.
+    /// If `formula` is given, the formula is written with a cached result decided by the policy.
+    fn write_non_finite_number(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: Option<&str>,
+        number: f64,
+        format: Option<&Format>,
+        method: &'static str,
+    ) -> Result<(), XlsxError> {
+        let result = match self._workbook.non_finite_number_policy() {
+            NonFiniteNumberPolicy::Error => {
+                return Err(self.cell_error(
+                    XlsxErrorSource::NonFiniteNumber(number).into(),
+                    method,
+                    row,
+                    col,
+                ))
+            }
+            NonFiniteNumberPolicy::Blank => "".to_string(),
+            NonFiniteNumberPolicy::NumError => CellError::Num.to_string(),
+            NonFiniteNumberPolicy::NaError => CellError::NA.to_string(),
+            NonFiniteNumberPolicy::String => number.to_string(),
+        };
+        match (formula, self._workbook.non_finite_number_policy()) {
+            (Some(formula), _) => self.write_formula_str(row, col, formula, format, &result),
+            (None, NonFiniteNumberPolicy::Blank) => self.write_blank(row, col, format),
+            (None, NonFiniteNumberPolicy::String) => self.write_string(row, col, &result, format),
+            (None, NonFiniteNumberPolicy::NumError) => {
+                self.write_error(row, col, CellError::Num, format)
+            }
+            (None, _) => self.write_error(row, col, CellError::NA, format),
+        }
+    }
+
+    fn spill_string(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        text: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        let chunks = limits::split_chunks(text, limits::MAX_STRING_LENGTH);
+        let last_col = u32::from(col) + chunks.len() as u32 - 1;
+        if last_col >= limits::MAX_COLUMNS {
+            return Err(self.range_error(
+                XlsxError::new(libxlsxwriter_sys::lxw_error_LXW_ERROR_MAX_STRING_LENGTH_EXCEEDED),
+                "write_string",
+                row,
+                col,
+                row,
+                last_col as WorksheetCol,
+            ));
+        }
+        self._workbook.warn_overflow(&OverflowWarning {
+            sheet_name: Some(self.name()),
+            cell: Some((row, col)),
+            method: "write_string",
+            length: text.chars().count(),
+            limit: limits::MAX_STRING_LENGTH,
+            policy: OverflowPolicy::Spill,
+        });
+        for (chunk_col, chunk) in (col..).zip(chunks) {
+            self.write_string(row, chunk_col, chunk, format)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn range_error(
+        &self,
+        error: XlsxError,
+        method: &'static str,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+    ) -> XlsxError {
+        error.with_context(ErrorContext {
+            sheet_name: Some(self.name()),
+            first_cell: Some((first_row, first_col)),
+            last_cell: Some((last_row, last_col)),
+            method: Some(method),
+        })
+    }
+
+    pub(crate) fn rows_error(
+        &self,
+        error: XlsxError,
+        method: &'static str,
+        first_row: WorksheetRow,
+        last_row: WorksheetRow,
+    ) -> XlsxError {
+        self.range_error(error, method, first_row, 0, last_row, limits::LAST_COL)
+    }
+
+    pub(crate) fn columns_error(
+        &self,
+        error: XlsxError,
+        method: &'static str,
+        first_col: WorksheetCol,
+        last_col: WorksheetCol,
+    ) -> XlsxError {
+        self.range_error(error, method, 0, first_col, limits::LAST_ROW, last_col)
+    }
+
     /// This function writes the comment of a cell
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -384,21 +720,30 @@ impl<'a> Worksheet<'a> {
         col: WorksheetCol,
         text: &str,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_comment", row, col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_comment(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                CString::new(text)?.as_c_str().as_ptr(),
+                CString::new(text)
+                    .map_err(|e| self.cell_error(e.into(), "write_comment", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_comment", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_comment`], with the cell given as a [`CellRef`].
+    pub fn write_comment_ref(&mut self, cell: &CellRef, text: &str) -> Result<(), XlsxError> {
+        self.write_comment(cell.row, cell.col, text)
+    }
+
     pub fn write_comment_opt(
         &mut self,
         row: WorksheetRow,
@@ -406,23 +751,38 @@ impl<'a> Worksheet<'a> {
         text: &str,
         options: &CommentOptions,
     ) -> Result<(), XlsxError> {
-        let mut options = options.to_internal(self._workbook)?;
+        self.check_cell("write_comment_opt", row, col)?;
+        let mut options = options
+            .to_internal(&self._workbook)
+            .map_err(|e| self.cell_error(e, "write_comment_opt", row, col))?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_comment_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                self._workbook.register_str(text)?,
+                self._workbook
+                    .register_str(text)
+                    .map_err(|e| self.cell_error(e, "write_comment_opt", row, col))?,
                 &mut options,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_comment_opt", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_comment_opt`], with the cell given as a [`CellRef`].
+    pub fn write_comment_opt_ref(
+        &mut self,
+        cell: &CellRef,
+        text: &str,
+        options: &CommentOptions,
+    ) -> Result<(), XlsxError> {
+        self.write_comment_opt(cell.row, cell.col, text, options)
+    }
+
     /// This function writes numeric types to the cell specified by row and column:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -452,7 +812,8 @@ impl<'a> Worksheet<'a> {
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-worksheet-write_number-2.png)
     ///
     /// ### Note
-    /// Excel doesn't support NaN, Inf or -Inf as a number value. If you are writing data that contains these values then your application should convert them to a string or handle them in some other way.
+    /// Excel doesn't support NaN, Inf or -Inf as a number value. These values are written as decided by [`Workbook::set_non_finite_number_policy`],
+    /// which returns an error by default.
     pub fn write_number(
         &mut self,
         row: WorksheetRow,
@@ -460,22 +821,37 @@ impl<'a> Worksheet<'a> {
         number: f64,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_number", row, col)?;
+        if !number.is_finite() {
+            return self.write_non_finite_number(row, col, None, number, format, "write_number");
+        }
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_number(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 number,
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || FormulaValue::Number(number));
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_number", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_number`], with the cell given as a [`CellRef`].
+    pub fn write_number_ref(
+        &mut self,
+        cell: &CellRef,
+        number: f64,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_number(cell.row, cell.col, number, format)
+    }
+
     /// This function writes a string to the cell specified by row and column:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -519,23 +895,59 @@ impl<'a> Worksheet<'a> {
         text: &str,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_string", row, col)?;
+        if self._workbook.overflow_policy() == OverflowPolicy::Spill
+            && limits::truncate(text, limits::MAX_STRING_LENGTH).is_some()
+        {
+            return self.spill_string(row, col, text, format);
+        }
+        let text = self.fit_cell_text(
+            text,
+            limits::MAX_STRING_LENGTH,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_MAX_STRING_LENGTH_EXCEEDED,
+            "write_string",
+            row,
+            col,
+        )?;
         let mut c_string_helper = CStringHelper::new();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_string(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                c_string_helper.add(text)?,
+                c_string_helper
+                    .add(&text)
+                    .map_err(|e| self.cell_error(e, "write_string", row, col))?,
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || FormulaValue::String(text.into_owned()));
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_string", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_string`], with the cell given as a [`CellRef`]:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_string_ref-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.write_string_ref(&"B3".parse()?, "Hello", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn write_string_ref(
+        &mut self,
+        cell: &CellRef,
+        text: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_string(cell.row, cell.col, text, format)
+    }
+
     /// This function writes a formula or function to the cell specified by row and column:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -571,6 +983,11 @@ impl<'a> Worksheet<'a> {
     /// # }
     /// ```
     /// See also [Working with Formulas](https://libxlsxwriter.github.io/working_with_formulas.html).
+    ///
+    /// When [`Workbook::enable_formula_evaluator`] is called, the result of the formula is computed and stored instead of `0`.
+    /// libxlsxwriter can only store a number or a string as the result, so a boolean or an error result is stored as the text
+    /// `TRUE`, `FALSE` or `#N/A`, and readers which don't recalculate see a string. Use [`EvaluatorOptions::fallback`](crate::evaluator::EvaluatorOptions::fallback)
+    /// or [`Worksheet::write_formula_num`] where this matters.
     pub fn write_formula(
         &mut self,
         row: WorksheetRow,
@@ -578,23 +995,56 @@ impl<'a> Worksheet<'a> {
         formula: &str,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_formula", row, col)?;
+        match self.evaluate_formula(formula) {
+            None => (),
+            Some(FormulaValue::Number(number)) => {
+                return self.write_formula_num(row, col, formula, format, number)
+            }
+            Some(FormulaValue::String(result)) => {
+                return self.write_formula_str(row, col, formula, format, &result)
+            }
+            Some(value) => {
+                let result = match &value {
+                    FormulaValue::Boolean(true) => "TRUE",
+                    FormulaValue::Boolean(false) => "FALSE",
+                    FormulaValue::Error(e) => e.as_str(),
+                    _ => "",
+                };
+                self.write_formula_str(row, col, formula, format, result)?;
+                self.record_value(row, col, || value);
+                return Ok(());
+            }
+        }
         let mut c_string_helper = CStringHelper::new();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_formula(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                c_string_helper.add(formula)?,
+                c_string_helper
+                    .add(&self._workbook.prepare_formula(formula))
+                    .map_err(|e| self.cell_error(e, "write_formula", row, col))?,
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_formula", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_formula`], with the cell given as a [`CellRef`].
+    pub fn write_formula_ref(
+        &mut self,
+        cell: &CellRef,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_formula(cell.row, cell.col, formula, format)
+    }
+
     /// This function writes an array formula to a cell range. In Excel an array formula is a formula that performs a calculation on a set of values.
     /// In Excel an array formula is indicated by a pair of braces around the formula: `{=SUM(A1:B1*A2:B2)}`.
     ///
@@ -618,6 +1068,7 @@ impl<'a> Worksheet<'a> {
     /// # workbook.close()
     /// # }
     /// ```
+    /// See [`Worksheet::write_dynamic_array_formula`] for the dynamic array formulas of Excel 365.
     pub fn write_array_formula(
         &mut self,
         first_row: WorksheetRow,
@@ -627,25 +1078,193 @@ impl<'a> Worksheet<'a> {
         formula: &str,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_range(
+            "write_array_formula",
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+        )?;
         let mut c_string_helper = CStringHelper::new();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_array_formula(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
                 last_col,
-                c_string_helper.add(formula)?,
+                c_string_helper
+                    .add(&self._workbook.prepare_formula(formula))
+                    .map_err(|e| {
+                        self.range_error(
+                            e,
+                            "write_array_formula",
+                            first_row,
+                            first_col,
+                            last_row,
+                            last_col,
+                        )
+                    })?,
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "write_array_formula",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_array_formula`], with the range given as a [`CellRange`].
+    pub fn write_array_formula_ref(
+        &mut self,
+        range: &CellRange,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) =
+            self.range_bounds(range, "write_array_formula")?;
+        self.write_array_formula(first_row, first_col, last_row, last_col, formula, format)
+    }
+
+    /// This function writes a dynamic array formula to a cell range. Dynamic array formulas, such as `FILTER`, `UNIQUE`, `SORT` and `SEQUENCE`,
+    /// were added in Excel 365 and spill their results to the cells around the formula.
+    /// The range from first_ to last_ row/column is the spill range of the result:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_dynamic_array_formula-1.xlsx")?;
+    /// # workbook.use_future_functions(true);
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.write_dynamic_array_formula(0, 1, 4, 1, "=SEQUENCE(5)", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    /// The functions added in Excel 365 require the `_xlfn.` prefix, like `=_xlfn.SEQUENCE(5)`, unless [`Workbook::use_future_functions`] is enabled.
+    pub fn write_dynamic_array_formula(
+        &mut self,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.check_range(
+            "write_dynamic_array_formula",
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+        )?;
+        let mut c_string_helper = CStringHelper::new();
+        unsafe {
+            let result = libxlsxwriter_sys::worksheet_write_dynamic_array_formula(
+                self.raw_worksheet()?,
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+                c_string_helper
+                    .add(&self._workbook.prepare_formula(formula))
+                    .map_err(|e| {
+                        self.range_error(
+                            e,
+                            "write_dynamic_array_formula",
+                            first_row,
+                            first_col,
+                            last_row,
+                            last_col,
+                        )
+                    })?,
+                self._workbook.get_internal_option_format(format)?,
+            );
+            if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                Ok(())
+            } else {
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "write_dynamic_array_formula",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
+            }
+        }
+    }
+
+    /// Same as [`Worksheet::write_dynamic_array_formula`], with the spill range given as a [`CellRange`].
+    pub fn write_dynamic_array_formula_ref(
+        &mut self,
+        range: &CellRange,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) =
+            self.range_bounds(range, "write_dynamic_array_formula")?;
+        self.write_dynamic_array_formula(first_row, first_col, last_row, last_col, formula, format)
+    }
+
+    /// This function writes a dynamic array formula to a single cell. Excel spills the result of the formula from the cell.
+    /// It is the same as [`Worksheet::write_dynamic_array_formula`] with a range of one cell:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_dynamic_formula-1.xlsx")?;
+    /// # workbook.use_future_functions(true);
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// # worksheet.write_string(0, 0, "a", None)?;
+    /// # worksheet.write_string(1, 0, "b", None)?;
+    /// # worksheet.write_string(2, 0, "a", None)?;
+    /// worksheet.write_dynamic_formula(0, 1, "=UNIQUE(A1:A3)", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn write_dynamic_formula(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.check_cell("write_dynamic_formula", row, col)?;
+        let mut c_string_helper = CStringHelper::new();
+        unsafe {
+            let result = libxlsxwriter_sys::worksheet_write_dynamic_formula(
+                self.raw_worksheet()?,
+                row,
+                col,
+                c_string_helper
+                    .add(&self._workbook.prepare_formula(formula))
+                    .map_err(|e| self.cell_error(e, "write_dynamic_formula", row, col))?,
+                self._workbook.get_internal_option_format(format)?,
+            );
+            if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                Ok(())
+            } else {
+                Err(self.cell_error(XlsxError::new(result), "write_dynamic_formula", row, col))
+            }
+        }
+    }
+
+    /// Same as [`Worksheet::write_dynamic_formula`], with the cell given as a [`CellRef`].
+    pub fn write_dynamic_formula_ref(
+        &mut self,
+        cell: &CellRef,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_dynamic_formula(cell.row, cell.col, formula, format)
+    }
+
     /// This function can be used to write a date or time to the cell specified by row and column:
     /// ```rust
     /// use xlsxwriter::prelude::*;
@@ -659,7 +1278,9 @@ impl<'a> Worksheet<'a> {
     /// ```
     /// ![Result Image](https://github.com/informationsea/xlsxwriter-rs/raw/master/images/test-worksheet-write_datetime-1.png)
     ///
-    /// The `format` parameter should be used to apply formatting to the cell using a [`Format`] object as shown above. Without a date format the datetime will appear as a number only.
+    /// The `format` parameter should be used to apply formatting to the cell using a [`Format`] object as shown above.
+    /// If `format` is `None`, the default date, time or datetime format of the workbook is applied depending on whether the datetime has a date part, a time part or both.
+    /// See [`Workbook::set_default_date_format`] to change them.
     ///
     /// See [Working with Dates and Times](https://libxlsxwriter.github.io/working_with_dates.html) for more information about handling dates and times in libxlsxwriter.
     pub fn write_datetime(
@@ -669,23 +1290,146 @@ impl<'a> Worksheet<'a> {
         datetime: &DateTime,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_datetime", row, col)?;
+        let default_format;
+        let format = match format {
+            Some(format) => format,
+            None => {
+                default_format = self._workbook.default_datetime_format(datetime);
+                &default_format
+            }
+        };
         unsafe {
             let mut xls_datetime: libxlsxwriter_sys::lxw_datetime = datetime.into();
             let result = libxlsxwriter_sys::worksheet_write_datetime(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 &mut xls_datetime,
-                self._workbook.get_internal_option_format(format)?,
+                self._workbook.get_internal_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || {
+                    FormulaValue::Number(datetime.to_excel_serial(self._workbook.date_epoch()))
+                });
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_datetime", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_datetime`], with the cell given as a [`CellRef`].
+    pub fn write_datetime_ref(
+        &mut self,
+        cell: &CellRef,
+        datetime: &DateTime,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_datetime(cell.row, cell.col, datetime, format)
+    }
+
+    /// This function writes a time zone aware [`chrono::DateTime`] to the cell specified by row and column.
+    /// Excel stores dates and times without a time zone, so `policy` decides which wall clock time is written.
+    /// This function is available with `chrono` feature.
+    /// ```rust
+    /// use xlsxwriter::prelude::*;
+    /// use chrono::{FixedOffset, Local, TimeZone, Utc};
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_datetime_tz-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+    /// let datetime = tokyo.with_ymd_and_hms(2013, 2, 28, 12, 0, 0).unwrap();
+    /// let format = Format::new().set_num_format("yyyy-mm-dd hh:mm").clone();
+    /// // 2013-02-28 03:00
+    /// worksheet.write_datetime_tz(0, 0, &datetime, Some(&format), TimeZonePolicy::<Utc>::Utc)?;
+    /// // 2013-02-27 22:00
+    /// let new_york = FixedOffset::west_opt(5 * 3600).unwrap();
+    /// worksheet.write_datetime_tz(1, 0, &datetime, Some(&format), TimeZonePolicy::Convert(new_york))?;
+    /// // The local time, with the offset of daylight saving time if the local time zone has one at that instant
+    /// worksheet.write_datetime_tz(2, 0, &datetime, Some(&format), TimeZonePolicy::Convert(Local))?;
+    /// // 2013-02-28 12:00
+    /// worksheet.write_datetime_tz(3, 0, &datetime, Some(&format), TimeZonePolicy::<Utc>::WallClock)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn write_datetime_tz<Tz: chrono::TimeZone, Target: chrono::TimeZone>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        datetime: &chrono::DateTime<Tz>,
+        format: Option<&Format>,
+        policy: TimeZonePolicy<Target>,
+    ) -> Result<(), XlsxError> {
+        let naive = match policy {
+            TimeZonePolicy::Utc => datetime.naive_utc(),
+            TimeZonePolicy::Convert(target) => datetime.with_timezone(&target).naive_local(),
+            TimeZonePolicy::WallClock => datetime.naive_local(),
+        };
+        self.write_datetime(row, col, &naive.into(), format)
+    }
+
+    /// Same as [`Worksheet::write_datetime_tz`], with the cell given as a [`CellRef`].
+    #[cfg(feature = "chrono")]
+    pub fn write_datetime_tz_ref<Tz: chrono::TimeZone, Target: chrono::TimeZone>(
+        &mut self,
+        cell: &CellRef,
+        datetime: &chrono::DateTime<Tz>,
+        format: Option<&Format>,
+        policy: TimeZonePolicy<Target>,
+    ) -> Result<(), XlsxError> {
+        self.write_datetime_tz(cell.row, cell.col, datetime, format, policy)
+    }
+
+    /// This function writes a [`Duration`](std::time::Duration), or a [`chrono::Duration`] with `chrono` feature,
+    /// to the cell specified by row and column as a fraction of days.
+    ///
+    /// If `format` is `None`, the duration is formatted with `[h]:mm:ss`, which shows the total number of hours even for spans over 24 hours.
+    /// Use a format like `[mm]:ss` or `[h]:mm:ss.000` for other units.
+    /// ```rust
+    /// use xlsxwriter::prelude::*;
+    /// use std::time::Duration;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_duration-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// // 27:46:40
+    /// worksheet.write_duration(0, 0, Duration::from_secs(100_000), None)?;
+    /// // 1666:40
+    /// worksheet.write_duration(1, 0, Duration::from_secs(100_000), Some(Format::new().set_num_format("[mm]:ss")))?;
+    /// // 1:30:00
+    /// worksheet.write_duration(2, 0, chrono::Duration::minutes(90), None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn write_duration<D: Into<DurationValue>>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        duration: D,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        let default_format;
+        let format = match format {
+            Some(format) => format,
+            None => {
+                default_format = Format::new().set_num_format("[h]:mm:ss").clone();
+                &default_format
+            }
+        };
+        self.write_number(row, col, duration.into().days, Some(format))
+    }
+
+    /// Same as [`Worksheet::write_duration`], with the cell given as a [`CellRef`].
+    pub fn write_duration_ref<D: Into<DurationValue>>(
+        &mut self,
+        cell: &CellRef,
+        duration: D,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_duration(cell.row, cell.col, duration, format)
+    }
+
     /// This function is used to write a URL/hyperlink to a worksheet cell specified by row and column.
     /// The format parameter is used to apply formatting to the cell. This parameter can be `None` to indicate no formatting or it can be a [Format](struct.Format.html) object. The typical worksheet format for a hyperlink is a blue underline:
     /// ```rust
@@ -759,22 +1503,44 @@ impl<'a> Worksheet<'a> {
         url: &str,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_url", row, col)?;
+        let url = self.fit_cell_text(
+            url,
+            limits::MAX_URL_LENGTH,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_WORKSHEET_MAX_URL_LENGTH_EXCEEDED,
+            "write_url",
+            row,
+            col,
+        )?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_url(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                CString::new(url)?.as_c_str().as_ptr(),
+                CString::new(url.as_ref())
+                    .map_err(|e| self.cell_error(e.into(), "write_url", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_url", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_url`], with the cell given as a [`CellRef`].
+    pub fn write_url_ref(
+        &mut self,
+        cell: &CellRef,
+        url: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_url(cell.row, cell.col, url, format)
+    }
+
     /// Write an Excel boolean to the cell specified by row and column:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -793,22 +1559,34 @@ impl<'a> Worksheet<'a> {
         value: bool,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_boolean", row, col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_boolean(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 value.into(),
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || FormulaValue::Boolean(value));
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_boolean", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_boolean`], with the cell given as a [`CellRef`].
+    pub fn write_boolean_ref(
+        &mut self,
+        cell: &CellRef,
+        value: bool,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_boolean(cell.row, cell.col, value, format)
+    }
+
     /// Write a blank cell specified by row and column:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -830,21 +1608,69 @@ impl<'a> Worksheet<'a> {
         col: WorksheetCol,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_blank", row, col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_blank(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || FormulaValue::Empty);
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_blank", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_blank`], with the cell given as a [`CellRef`].
+    pub fn write_blank_ref(
+        &mut self,
+        cell: &CellRef,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_blank(cell.row, cell.col, format)
+    }
+
+    /// This function writes an Excel error value such as `#N/A` to the cell specified by row and column:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_error-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.write_error(0, 0, CellError::NA, None)?;
+    /// worksheet.write_formula(0, 1, "=IFNA(A1, \"missing\")", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    /// The error is written as a formula which evaluates to the error, such as `=#N/A`, with the error as its cached result.
+    /// The cell shows the error before the workbook is recalculated, and formulas such as `IFNA` and `ISERROR` treat it as an error.
+    pub fn write_error(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        error: CellError,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.check_cell("write_error", row, col)?;
+        let formula = format!("={}", error);
+        self.write_formula_str(row, col, &formula, format, error.as_str())?;
+        self.record_value(row, col, || FormulaValue::Error(error));
+        Ok(())
+    }
+
+    /// Same as [`Worksheet::write_error`], with the cell given as a [`CellRef`].
+    pub fn write_error_ref(
+        &mut self,
+        cell: &CellRef,
+        error: CellError,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_error(cell.row, cell.col, error, format)
+    }
+
     /// This function writes a formula or Excel function to the cell specified by row and column with a user defined numeric result:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -876,23 +1702,50 @@ impl<'a> Worksheet<'a> {
         format: Option<&Format>,
         number: f64,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_formula_num", row, col)?;
+        if !number.is_finite() {
+            return self.write_non_finite_number(
+                row,
+                col,
+                Some(formula),
+                number,
+                format,
+                "write_formula_num",
+            );
+        }
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_formula_num(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                CString::new(formula)?.as_c_str().as_ptr(),
+                CString::new(self._workbook.prepare_formula(formula).as_ref())
+                    .map_err(|e| self.cell_error(e.into(), "write_formula_num", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
                 self._workbook.get_internal_option_format(format)?,
                 number,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || FormulaValue::Number(number));
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_formula_num", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_formula_num`], with the cell given as a [`CellRef`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_formula_num_ref(
+        &mut self,
+        cell: &CellRef,
+        formula: &str,
+        format: Option<&Format>,
+        number: f64,
+    ) -> Result<(), XlsxError> {
+        self.write_formula_num(cell.row, cell.col, formula, format, number)
+    }
+
     /// This function writes a formula or Excel function to the cell specified by row and column with a user defined string result:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -918,23 +1771,43 @@ impl<'a> Worksheet<'a> {
         format: Option<&Format>,
         result: &str,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_formula_str", row, col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_formula_str(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                CString::new(formula)?.as_c_str().as_ptr(),
+                CString::new(self._workbook.prepare_formula(formula).as_ref())
+                    .map_err(|e| self.cell_error(e.into(), "write_formula_str", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
                 self._workbook.get_internal_option_format(format)?,
-                CString::new(result)?.as_c_str().as_ptr(),
+                CString::new(result)
+                    .map_err(|e| self.cell_error(e.into(), "write_formula_str", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || FormulaValue::String(result.to_string()));
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_formula_str", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_formula_str`], with the cell given as a [`CellRef`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_formula_str_ref(
+        &mut self,
+        cell: &CellRef,
+        formula: &str,
+        format: Option<&Format>,
+        result: &str,
+    ) -> Result<(), XlsxError> {
+        self.write_formula_str(cell.row, cell.col, formula, format, result)
+    }
+
     /// This function is used to write strings with multiple formats. For example to write the string 'This is bold and this is italic' you would use the following:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -978,9 +1851,16 @@ impl<'a> Worksheet<'a> {
         text: &[(&str, Option<&Format>)],
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_cell("write_rich_string", row, col)?;
         let mut c_str: Vec<Vec<u8>> = text
             .iter()
-            .map(|x| Ok(CString::new(x.0)?.as_c_str().to_bytes_with_nul().to_vec()))
+            .map(|x| {
+                Ok(CString::new(x.0)
+                    .map_err(|e| self.cell_error(e.into(), "write_rich_string", row, col))?
+                    .as_c_str()
+                    .to_bytes_with_nul()
+                    .to_vec())
+            })
             .collect::<Result<_, XlsxError>>()?;
 
         let mut rich_text: Vec<_> = text
@@ -1001,37 +1881,50 @@ impl<'a> Worksheet<'a> {
 
         unsafe {
             let result = libxlsxwriter_sys::worksheet_write_rich_string(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 rich_text_ptr.as_mut_ptr(),
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
+                self.record_value(row, col, || {
+                    FormulaValue::String(text.iter().map(|x| x.0).collect())
+                });
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "write_rich_string", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::write_rich_string`], with the cell given as a [`CellRef`].
+    pub fn write_rich_string_ref(
+        &mut self,
+        cell: &CellRef,
+        text: &[(&str, Option<&Format>)],
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_rich_string(cell.row, cell.col, text, format)
+    }
+
     pub fn set_row(
         &mut self,
         row: WorksheetRow,
         height: f64,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.rows_error(e, "set_row", row, row))?;
         unsafe {
-            let result = libxlsxwriter_sys::worksheet_set_row(
-                self.worksheet,
-                row,
-                height,
-                self._workbook.get_internal_option_format(format)?,
-            );
+            let result =
+                libxlsxwriter_sys::worksheet_set_row(self.raw_worksheet()?, row, height, format);
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.rows_error(XlsxError::new(result), "set_row", row, row))
             }
         }
     }
@@ -1043,19 +1936,23 @@ impl<'a> Worksheet<'a> {
         format: Option<&Format>,
         options: &RowColOptions,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.rows_error(e, "set_row_opt", row, row))?;
         unsafe {
             let mut options = options.to_internal();
             let result = libxlsxwriter_sys::worksheet_set_row_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 height,
-                self._workbook.get_internal_option_format(format)?,
+                format,
                 &mut options,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.rows_error(XlsxError::new(result), "set_row_opt", row, row))
             }
         }
     }
@@ -1069,17 +1966,21 @@ impl<'a> Worksheet<'a> {
         pixels: u32,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.rows_error(e, "set_row_pixels", row, row))?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_row_pixels(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 pixels,
-                self._workbook.get_internal_option_format(format)?,
+                format,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.rows_error(XlsxError::new(result), "set_row_pixels", row, row))
             }
         }
     }
@@ -1091,19 +1992,23 @@ impl<'a> Worksheet<'a> {
         format: Option<&Format>,
         options: &RowColOptions,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.rows_error(e, "set_row_pixels_opt", row, row))?;
         let mut options = options.to_internal();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_row_pixels_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 pixels,
-                self._workbook.get_internal_option_format(format)?,
+                format,
                 &mut options,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.rows_error(XlsxError::new(result), "set_row_pixels_opt", row, row))
             }
         }
     }
@@ -1115,18 +2020,22 @@ impl<'a> Worksheet<'a> {
         width: f64,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.columns_error(e, "set_column", first_col, last_col))?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_column(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_col,
                 last_col,
                 width,
-                self._workbook.get_internal_option_format(format)?,
+                format,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.columns_error(XlsxError::new(result), "set_column", first_col, last_col))
             }
         }
     }
@@ -1139,20 +2048,29 @@ impl<'a> Worksheet<'a> {
         format: Option<&Format>,
         options: &RowColOptions,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.columns_error(e, "set_column_opt", first_col, last_col))?;
         let mut options = options.to_internal();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_column_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_col,
                 last_col,
                 width,
-                self._workbook.get_internal_option_format(format)?,
+                format,
                 &mut options,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.columns_error(
+                    XlsxError::new(result),
+                    "set_column_opt",
+                    first_col,
+                    last_col,
+                ))
             }
         }
     }
@@ -1164,18 +2082,27 @@ impl<'a> Worksheet<'a> {
         pixels: u32,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.columns_error(e, "set_column_pixels", first_col, last_col))?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_column_pixels(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_col,
                 last_col,
                 pixels,
-                self._workbook.get_internal_option_format(format)?,
+                format,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.columns_error(
+                    XlsxError::new(result),
+                    "set_column_pixels",
+                    first_col,
+                    last_col,
+                ))
             }
         }
     }
@@ -1188,20 +2115,29 @@ impl<'a> Worksheet<'a> {
         format: Option<&Format>,
         options: &mut RowColOptions,
     ) -> Result<(), XlsxError> {
+        let format = self
+            ._workbook
+            .get_internal_option_format(format)
+            .map_err(|e| self.columns_error(e, "set_column_pixels_opt", first_col, last_col))?;
         let mut options = options.to_internal();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_column_pixels_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_col,
                 last_col,
                 pixels,
-                self._workbook.get_internal_option_format(format)?,
+                format,
                 &mut options,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.columns_error(
+                    XlsxError::new(result),
+                    "set_column_pixels_opt",
+                    first_col,
+                    last_col,
+                ))
             }
         }
     }
@@ -1233,21 +2169,39 @@ impl<'a> Worksheet<'a> {
         col: WorksheetCol,
         filename: &str,
     ) -> Result<(), XlsxError> {
+        self.check_cell("insert_image", row, col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_insert_image(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                CString::new(filename)?.as_c_str().as_ptr(),
+                CString::new(filename)
+                    .map_err(|e| self.cell_error(e.into(), "insert_image", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "insert_image", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::insert_image`], with the cell given as a [`CellRef`]:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_insert_image_ref-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.insert_image_ref(&"B3".parse()?, "../images/simple1.png")?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn insert_image_ref(&mut self, cell: &CellRef, filename: &str) -> Result<(), XlsxError> {
+        self.insert_image(cell.row, cell.col, filename)
+    }
+
     /// This function is like `Worksheet.insert_image` function except that it takes an optional `ImageOptions` struct to scale and position the image:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -1278,23 +2232,37 @@ impl<'a> Worksheet<'a> {
         filename: &str,
         opt: &ImageOptions,
     ) -> Result<(), XlsxError> {
+        self.check_cell("insert_image_opt", row, col)?;
         let mut opt_struct = opt.into();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_insert_image_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
-                CString::new(filename)?.as_c_str().as_ptr(),
+                CString::new(filename)
+                    .map_err(|e| self.cell_error(e.into(), "insert_image_opt", row, col))?
+                    .as_c_str()
+                    .as_ptr(),
                 &mut opt_struct,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "insert_image_opt", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::insert_image_opt`], with the cell given as a [`CellRef`].
+    pub fn insert_image_opt_ref(
+        &mut self,
+        cell: &CellRef,
+        filename: &str,
+        opt: &ImageOptions,
+    ) -> Result<(), XlsxError> {
+        self.insert_image_opt(cell.row, cell.col, filename, opt)
+    }
+
     /// This function can be used to insert a image into a worksheet from a memory buffer:
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -1313,9 +2281,10 @@ impl<'a> Worksheet<'a> {
         col: WorksheetCol,
         buffer: &[u8],
     ) -> Result<(), XlsxError> {
+        self.check_cell("insert_image_buffer", row, col)?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_insert_image_buffer(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 buffer.as_ptr(),
@@ -1324,11 +2293,20 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "insert_image_buffer", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::insert_image_buffer`], with the cell given as a [`CellRef`].
+    pub fn insert_image_buffer_ref(
+        &mut self,
+        cell: &CellRef,
+        buffer: &[u8],
+    ) -> Result<(), XlsxError> {
+        self.insert_image_buffer(cell.row, cell.col, buffer)
+    }
+
     pub fn insert_image_buffer_opt(
         &mut self,
         row: WorksheetRow,
@@ -1336,10 +2314,11 @@ impl<'a> Worksheet<'a> {
         buffer: &[u8],
         opt: &ImageOptions,
     ) -> Result<(), XlsxError> {
+        self.check_cell("insert_image_buffer_opt", row, col)?;
         let mut opt_struct = opt.into();
         unsafe {
             let result = libxlsxwriter_sys::worksheet_insert_image_buffer_opt(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 buffer.as_ptr(),
@@ -1349,28 +2328,48 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "insert_image_buffer_opt", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::insert_image_buffer_opt`], with the cell given as a [`CellRef`].
+    pub fn insert_image_buffer_opt_ref(
+        &mut self,
+        cell: &CellRef,
+        buffer: &[u8],
+        opt: &ImageOptions,
+    ) -> Result<(), XlsxError> {
+        self.insert_image_buffer_opt(cell.row, cell.col, buffer, opt)
+    }
+
     pub fn insert_chart(
         &mut self,
         row: WorksheetRow,
         column: WorksheetCol,
         chart: &Chart,
     ) -> Result<(), XlsxError> {
+        self.check_cell("insert_chart", row, column)?;
         unsafe {
-            let result =
-                libxlsxwriter_sys::worksheet_insert_chart(self.worksheet, row, column, chart.chart);
+            let result = libxlsxwriter_sys::worksheet_insert_chart(
+                self.raw_worksheet()?,
+                row,
+                column,
+                chart.raw_chart()?,
+            );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "insert_chart", row, column))
             }
         }
     }
 
+    /// Same as [`Worksheet::insert_chart`], with the cell given as a [`CellRef`].
+    pub fn insert_chart_ref(&mut self, cell: &CellRef, chart: &Chart) -> Result<(), XlsxError> {
+        self.insert_chart(cell.row, cell.col, chart)
+    }
+
     /// The [`Worksheet::merge_range`] function allows cells to be merged together so that they act as a single area.
     pub fn merge_range(
         &mut self,
@@ -1381,39 +2380,89 @@ impl<'a> Worksheet<'a> {
         string: &str,
         format: Option<&Format>,
     ) -> Result<(), XlsxError> {
+        self.check_range("merge_range", first_row, first_col, last_row, last_col)?;
+        let string = self.fit_cell_text(
+            string,
+            limits::MAX_STRING_LENGTH,
+            libxlsxwriter_sys::lxw_error_LXW_ERROR_MAX_STRING_LENGTH_EXCEEDED,
+            "merge_range",
+            first_row,
+            first_col,
+        )?;
         unsafe {
             let result = libxlsxwriter_sys::worksheet_merge_range(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
                 last_col,
-                CString::new(string)?.as_c_str().as_ptr(),
+                CString::new(string.as_ref())
+                    .map_err(|e| {
+                        self.range_error(
+                            e.into(),
+                            "merge_range",
+                            first_row,
+                            first_col,
+                            last_row,
+                            last_col,
+                        )
+                    })?
+                    .as_c_str()
+                    .as_ptr(),
                 self._workbook.get_internal_option_format(format)?,
             );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "merge_range",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
 
+    /// Same as [`Worksheet::merge_range`], with the range given as a [`CellRange`]:
+    /// ```rust
+    /// # use xlsxwriter::prelude::*;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_merge_range_ref-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// worksheet.merge_range_ref(&"B2:D3".parse()?, "Merged", None)?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    pub fn merge_range_ref(
+        &mut self,
+        range: &CellRange,
+        string: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) = self.range_bounds(range, "merge_range")?;
+        self.merge_range(first_row, first_col, last_row, last_col, string, format)
+    }
+
     /// The [`Worksheet::activate`] function is used to specify which worksheet is initially visible in a multi-sheet workbook.
-    pub fn activate(&mut self) {
+    pub fn activate(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_activate(self.worksheet);
+            libxlsxwriter_sys::worksheet_activate(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
     /// The [`Worksheet::select`] function is used to indicate that a worksheet is selected in a multi-sheet workbook.
     ///
     /// A selected worksheet has its tab highlighted. Selecting worksheets is a way of grouping them together so that, for example, several worksheets could be printed in one go.
     /// A worksheet that has been activated via the [`Worksheet::activate`] function will also appear as selected.
-    pub fn select(&mut self) {
+    pub fn select(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_select(self.worksheet);
+            libxlsxwriter_sys::worksheet_select(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
     /// The [`Worksheet::hide`] function is used to hide a worksheet.
@@ -1423,10 +2472,11 @@ impl<'a> Worksheet<'a> {
     /// A hidden worksheet can not be activated or selected so this function is mutually exclusive with the [`Worksheet::activate`]
     /// and [`Worksheet::select`] functions. In addition, since the first worksheet will default to being the active worksheet,
     /// you cannot hide the first worksheet without activating another sheet.
-    pub fn hide(&mut self) {
+    pub fn hide(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_hide(self.worksheet);
+            libxlsxwriter_sys::worksheet_hide(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
     /// The [`Worksheet::activate`] function determines which worksheet is initially selected. However,
@@ -1434,10 +2484,11 @@ impl<'a> Worksheet<'a> {
     /// To avoid this you can select the leftmost visible worksheet tab using [`Worksheet::set_first_sheet`]
     ///
     /// This function is not required very often. The default value is the first worksheet.
-    pub fn set_first_sheet(&mut self) {
+    pub fn set_first_sheet(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_first_sheet(self.worksheet);
+            libxlsxwriter_sys::worksheet_set_first_sheet(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
     /// The [`Worksheet::freeze_panes`] function can be used to divide a worksheet into horizontal or
@@ -1448,10 +2499,11 @@ impl<'a> Worksheet<'a> {
     /// to freeze the first row of a worksheet it is necessary to specify the split at row 2 (which is 1 as the zero-based index).
     ///
     /// You can set one of the row and col parameters as zero if you do not want either a vertical or horizontal split.
-    pub fn freeze_panes(&mut self, row: WorksheetRow, col: WorksheetCol) {
+    pub fn freeze_panes(&mut self, row: WorksheetRow, col: WorksheetCol) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_freeze_panes(self.worksheet, row, col);
+            libxlsxwriter_sys::worksheet_freeze_panes(self.raw_worksheet()?, row, col);
         }
+        Ok(())
     }
 
     /// The [`Worksheet::split_panes`] function can be used to divide a worksheet into horizontal or vertical regions known as panes.
@@ -1462,10 +2514,11 @@ impl<'a> Worksheet<'a> {
     /// vertical and horizontal are the same as those used by Excel to specify row height and column width. However, the vertical
     /// and horizontal units are different from each other. Therefore you must specify the vertical and horizontal parameters in
     /// terms of the row heights and column widths that you have set or the default values which are 15 for a row and 8.43 for a column.
-    pub fn split_panes(&mut self, vertical: f64, horizontal: f64) {
+    pub fn split_panes(&mut self, vertical: f64, horizontal: f64) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_split_panes(self.worksheet, vertical, horizontal);
+            libxlsxwriter_sys::worksheet_split_panes(self.raw_worksheet()?, vertical, horizontal);
         }
+        Ok(())
     }
 
     /// The [`Worksheet::set_selection`] function can be used to specify which cell or range of cells is selected in a worksheet:
@@ -1478,53 +2531,68 @@ impl<'a> Worksheet<'a> {
         first_col: WorksheetCol,
         last_row: WorksheetRow,
         last_col: WorksheetCol,
-    ) {
+    ) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::worksheet_set_selection(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
                 last_col,
             );
         }
+        Ok(())
+    }
+
+    /// Same as [`Worksheet::set_selection`], with the range given as a [`CellRange`].
+    pub fn set_selection_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) =
+            self.range_bounds(range, "set_selection")?;
+        self.set_selection(first_row, first_col, last_row, last_col)
     }
 
-    pub fn set_landscape(&mut self) {
+    pub fn set_landscape(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_landscape(self.worksheet);
+            libxlsxwriter_sys::worksheet_set_landscape(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn set_portrait(&mut self) {
+    pub fn set_portrait(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_portrait(self.worksheet);
+            libxlsxwriter_sys::worksheet_set_portrait(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn set_page_view(&mut self) {
+    pub fn set_page_view(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_page_view(self.worksheet);
+            libxlsxwriter_sys::worksheet_set_page_view(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn set_paper(&mut self, paper: PaperType) {
+    pub fn set_paper(&mut self, paper: PaperType) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_paper(self.worksheet, paper.value());
+            libxlsxwriter_sys::worksheet_set_paper(self.raw_worksheet()?, paper.value());
         }
+        Ok(())
     }
 
     pub fn set_header(&mut self, header: &str) -> Result<(), XlsxError> {
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_header(
-                self.worksheet,
-                CString::new(header)?.as_c_str().as_ptr(),
+                self.raw_worksheet()?,
+                CString::new(header)
+                    .map_err(|e| self.sheet_error(e.into(), "set_header"))?
+                    .as_c_str()
+                    .as_ptr(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_header"))
             }
         }
     }
@@ -1532,14 +2600,17 @@ impl<'a> Worksheet<'a> {
     pub fn set_footer(&mut self, footer: &str) -> Result<(), XlsxError> {
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_footer(
-                self.worksheet,
-                CString::new(footer)?.as_c_str().as_ptr(),
+                self.raw_worksheet()?,
+                CString::new(footer)
+                    .map_err(|e| self.sheet_error(e.into(), "set_footer"))?
+                    .as_c_str()
+                    .as_ptr(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_footer"))
             }
         }
     }
@@ -1551,15 +2622,18 @@ impl<'a> Worksheet<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_header_opt(
-                self.worksheet,
-                CString::new(header)?.as_c_str().as_ptr(),
+                self.raw_worksheet()?,
+                CString::new(header)
+                    .map_err(|e| self.sheet_error(e.into(), "set_header_opt"))?
+                    .as_c_str()
+                    .as_ptr(),
                 &mut options.into(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_header_opt"))
             }
         }
     }
@@ -1571,15 +2645,18 @@ impl<'a> Worksheet<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_footer_opt(
-                self.worksheet,
-                CString::new(footer)?.as_c_str().as_ptr(),
+                self.raw_worksheet()?,
+                CString::new(footer)
+                    .map_err(|e| self.sheet_error(e.into(), "set_footer_opt"))?
+                    .as_c_str()
+                    .as_ptr(),
                 &mut options.into(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_footer_opt"))
             }
         }
     }
@@ -1589,14 +2666,14 @@ impl<'a> Worksheet<'a> {
         breaks_vec.push(0);
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_h_pagebreaks(
-                self.worksheet,
+                self.raw_worksheet()?,
                 breaks_vec.as_mut_ptr(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_h_pagebreaks"))
             }
         }
     }
@@ -1606,52 +2683,58 @@ impl<'a> Worksheet<'a> {
         breaks_vec.push(0);
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_v_pagebreaks(
-                self.worksheet,
+                self.raw_worksheet()?,
                 breaks_vec.as_mut_ptr(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_v_pagebreaks"))
             }
         }
     }
 
-    pub fn print_across(&mut self) {
+    pub fn print_across(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_print_across(self.worksheet);
+            libxlsxwriter_sys::worksheet_print_across(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn set_zoom(&mut self, scale: u16) {
+    pub fn set_zoom(&mut self, scale: u16) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_zoom(self.worksheet, scale);
+            libxlsxwriter_sys::worksheet_set_zoom(self.raw_worksheet()?, scale);
         }
+        Ok(())
     }
 
-    pub fn gridlines(&mut self, option: GridLines) {
+    pub fn gridlines(&mut self, option: GridLines) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_gridlines(self.worksheet, option.value());
+            libxlsxwriter_sys::worksheet_gridlines(self.raw_worksheet()?, option.value());
         }
+        Ok(())
     }
 
-    pub fn center_horizontally(&mut self) {
+    pub fn center_horizontally(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_center_horizontally(self.worksheet);
+            libxlsxwriter_sys::worksheet_center_horizontally(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn center_vertically(&mut self) {
+    pub fn center_vertically(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_center_vertically(self.worksheet);
+            libxlsxwriter_sys::worksheet_center_vertically(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn print_row_col_headers(&mut self) {
+    pub fn print_row_col_headers(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_print_row_col_headers(self.worksheet);
+            libxlsxwriter_sys::worksheet_print_row_col_headers(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
     pub fn repeat_rows(
@@ -1660,12 +2743,15 @@ impl<'a> Worksheet<'a> {
         last_row: WorksheetRow,
     ) -> Result<(), XlsxError> {
         unsafe {
-            let result =
-                libxlsxwriter_sys::worksheet_repeat_rows(self.worksheet, first_row, last_row);
+            let result = libxlsxwriter_sys::worksheet_repeat_rows(
+                self.raw_worksheet()?,
+                first_row,
+                last_row,
+            );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.rows_error(XlsxError::new(result), "repeat_rows", first_row, last_row))
             }
         }
     }
@@ -1676,12 +2762,20 @@ impl<'a> Worksheet<'a> {
         last_col: WorksheetCol,
     ) -> Result<(), XlsxError> {
         unsafe {
-            let result =
-                libxlsxwriter_sys::worksheet_repeat_columns(self.worksheet, first_col, last_col);
+            let result = libxlsxwriter_sys::worksheet_repeat_columns(
+                self.raw_worksheet()?,
+                first_col,
+                last_col,
+            );
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.columns_error(
+                    XlsxError::new(result),
+                    "repeat_columns",
+                    first_col,
+                    last_col,
+                ))
             }
         }
     }
@@ -1695,7 +2789,7 @@ impl<'a> Worksheet<'a> {
     ) -> Result<(), XlsxError> {
         unsafe {
             let result = libxlsxwriter_sys::worksheet_print_area(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
@@ -1704,55 +2798,78 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "print_area",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
 
-    pub fn fit_to_pages(&mut self, width: u16, height: u16) {
+    /// Same as [`Worksheet::print_area`], with the range given as a [`CellRange`].
+    pub fn print_area_ref(&mut self, range: &CellRange) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) = self.range_bounds(range, "print_area")?;
+        self.print_area(first_row, first_col, last_row, last_col)
+    }
+
+    pub fn fit_to_pages(&mut self, width: u16, height: u16) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_fit_to_pages(self.worksheet, width, height);
+            libxlsxwriter_sys::worksheet_fit_to_pages(self.raw_worksheet()?, width, height);
         }
+        Ok(())
     }
 
-    pub fn set_start_page(&mut self, start_page: u16) {
+    pub fn set_start_page(&mut self, start_page: u16) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_start_page(self.worksheet, start_page);
+            libxlsxwriter_sys::worksheet_set_start_page(self.raw_worksheet()?, start_page);
         }
+        Ok(())
     }
 
-    pub fn set_print_scale(&mut self, scale: u16) {
+    pub fn set_print_scale(&mut self, scale: u16) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_print_scale(self.worksheet, scale);
+            libxlsxwriter_sys::worksheet_set_print_scale(self.raw_worksheet()?, scale);
         }
+        Ok(())
     }
 
-    pub fn set_right_to_left(&mut self) {
+    pub fn set_right_to_left(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_right_to_left(self.worksheet);
+            libxlsxwriter_sys::worksheet_right_to_left(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn set_hide_zero(&mut self) {
+    pub fn set_hide_zero(&mut self) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_hide_zero(self.worksheet);
+            libxlsxwriter_sys::worksheet_hide_zero(self.raw_worksheet()?);
         }
+        Ok(())
     }
 
-    pub fn set_tab_color(&mut self, color: FormatColor) {
+    pub fn set_tab_color(&mut self, color: FormatColor) -> Result<(), XlsxError> {
         unsafe {
-            libxlsxwriter_sys::worksheet_set_tab_color(self.worksheet, color.value());
+            libxlsxwriter_sys::worksheet_set_tab_color(self.raw_worksheet()?, color.value());
         }
+        Ok(())
     }
 
-    pub fn protect(&mut self, password: &str, protection: &Protection) {
+    pub fn protect(&mut self, password: &str, protection: &Protection) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::worksheet_protect(
-                self.worksheet,
-                CString::new(password).unwrap().as_c_str().as_ptr(),
+                self.raw_worksheet()?,
+                CString::new(password)
+                    .map_err(|e| self.sheet_error(e.into(), "protect"))?
+                    .as_c_str()
+                    .as_ptr(),
                 &mut protection.into(),
             );
         }
+        Ok(())
     }
 
     pub fn outline_settings(
@@ -1761,39 +2878,48 @@ impl<'a> Worksheet<'a> {
         symbols_below: bool,
         symbols_right: bool,
         auto_style: bool,
-    ) {
+    ) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::worksheet_outline_settings(
-                self.worksheet,
+                self.raw_worksheet()?,
                 convert_bool(visible),
                 convert_bool(symbols_below),
                 convert_bool(symbols_right),
                 convert_bool(auto_style),
             );
         }
+        Ok(())
     }
 
-    pub fn set_default_row(&mut self, height: f64, hide_unused_rows: bool) {
+    pub fn set_default_row(
+        &mut self,
+        height: f64,
+        hide_unused_rows: bool,
+    ) -> Result<(), XlsxError> {
         unsafe {
             libxlsxwriter_sys::worksheet_set_default_row(
-                self.worksheet,
+                self.raw_worksheet()?,
                 height,
                 convert_bool(hide_unused_rows),
             );
         }
+        Ok(())
     }
 
     pub fn set_vba_name(&mut self, name: &str) -> Result<(), XlsxError> {
         unsafe {
             let result = libxlsxwriter_sys::worksheet_set_vba_name(
-                self.worksheet,
-                CString::new(name)?.as_c_str().as_ptr(),
+                self.raw_worksheet()?,
+                CString::new(name)
+                    .map_err(|e| self.sheet_error(e.into(), "set_vba_name"))?
+                    .as_c_str()
+                    .as_ptr(),
             );
 
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.sheet_error(XlsxError::new(result), "set_vba_name"))
             }
         }
     }
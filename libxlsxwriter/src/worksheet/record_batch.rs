@@ -0,0 +1,645 @@
+use std::convert::TryFrom;
+
+use arrow_array::cast::AsArray;
+use arrow_array::temporal_conversions::{as_date, as_datetime, as_datetime_with_timezone, as_time};
+use arrow_array::timezone::Tz;
+use arrow_array::types::*;
+use arrow_array::{Array, ArrowPrimitiveType, PrimitiveArray, RecordBatch};
+use arrow_schema::{DataType, TimeUnit};
+#[cfg(feature = "rust_decimal")]
+use rust_decimal::Decimal;
+
+use crate::error::XlsxErrorSource;
+use crate::{Format, Worksheet, XlsxError};
+
+use super::table::{TableColumn, TableOptions};
+#[cfg(feature = "rust_decimal")]
+use super::DecimalPolicy;
+use super::{WorksheetCol, WorksheetRow};
+
+/// How null values in a record batch are written.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
+pub enum NullHandling {
+    /// Leave the cell empty.
+    #[default]
+    Skip,
+    /// Write a blank cell. Blank cells keep the column format of an enclosing table.
+    Blank,
+    /// Write the given string, such as `"N/A"`.
+    String(String),
+}
+
+/// Options for [`Worksheet::write_record_batch`].
+pub struct RecordBatchOptions {
+    /// Write the field names of the schema as a header row. It is on by default.
+    pub header: bool,
+
+    /// Format of the header row.
+    pub header_format: Option<Format>,
+
+    /// How null values are written.
+    pub null_handling: NullHandling,
+
+    /// Format used for `Date32` and `Date64` columns. Default is `yyyy-mm-dd`.
+    pub date_format: Format,
+
+    /// Format used for `Timestamp` columns. Default is `yyyy-mm-dd hh:mm:ss`.
+    pub datetime_format: Format,
+
+    /// Format used for `Time32` and `Time64` columns. Default is `hh:mm:ss`.
+    pub time_format: Format,
+
+    /// How `Decimal128` values which cannot be represented exactly as a number cell are written.
+    /// Default is [`DecimalPolicy::Round`]. This field is available with `rust_decimal` feature.
+    #[cfg(feature = "rust_decimal")]
+    pub decimal_policy: DecimalPolicy,
+
+    /// Wrap the written range in an Excel table with [`Worksheet::add_table`].
+    /// If `columns` is `None`, table headers are taken from the schema.
+    pub table: Option<TableOptions>,
+}
+
+impl Default for RecordBatchOptions {
+    fn default() -> Self {
+        RecordBatchOptions {
+            header: true,
+            header_format: None,
+            null_handling: NullHandling::Skip,
+            date_format: Format::new().set_num_format("yyyy-mm-dd").clone(),
+            datetime_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss").clone(),
+            time_format: Format::new().set_num_format("hh:mm:ss").clone(),
+            #[cfg(feature = "rust_decimal")]
+            decimal_policy: DecimalPolicy::Round,
+            table: None,
+        }
+    }
+}
+
+fn arrow_error(message: String) -> XlsxError {
+    XlsxErrorSource::Arrow(message).into()
+}
+
+fn out_of_range_error() -> XlsxError {
+    XlsxError::new(libxlsxwriter_sys::lxw_error_LXW_ERROR_WORKSHEET_INDEX_OUT_OF_RANGE)
+}
+
+/// Converts a `Decimal128` value with `scale` to a [`Decimal`], or `None` if it does not fit in 96 bits with a scale of at most 28.
+#[cfg(feature = "rust_decimal")]
+fn arrow_decimal(value: i128, scale: i8) -> Option<Decimal> {
+    let mantissa = if scale < 0 {
+        value.checked_mul(10i128.checked_pow(scale.unsigned_abs().into())?)?
+    } else {
+        value
+    };
+    Decimal::try_from_i128_with_scale(mantissa, scale.max(0).unsigned_abs().into()).ok()
+}
+
+impl Worksheet {
+    /// Write an Arrow [`RecordBatch`] starting at the cell specified by row and column.
+    /// This function is available with `arrow` feature.
+    ///
+    /// Each column of the batch is written with the writer matching its Arrow type:
+    ///
+    /// * Integer and float columns are written with [`Worksheet::write_number`].
+    /// * `Decimal128` columns are written with [`Worksheet::write_decimal_opt`] and [`RecordBatchOptions::decimal_policy`]
+    ///   if `rust_decimal` feature is enabled. Otherwise they are converted to `f64` and written with [`Worksheet::write_number`],
+    ///   which loses digits beyond the precision of a float, about 15 significant digits.
+    /// * `Utf8` and `LargeUtf8` columns are written with [`Worksheet::write_string`].
+    /// * `Boolean` columns are written with [`Worksheet::write_boolean`].
+    /// * `Date32`, `Date64`, `Time32`, `Time64` and `Timestamp` columns are written with [`Worksheet::write_datetime`]
+    ///   and the date formats in [`RecordBatchOptions`]. Timestamps with a time zone are written as wall clock time in that time zone.
+    ///
+    /// Other Arrow types are rejected with an error.
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+    /// use xlsxwriter::prelude::*;
+    /// use xlsxwriter::worksheet::record_batch::RecordBatchOptions;
+    /// use xlsxwriter::worksheet::table::TableOptions;
+    /// # fn main() -> Result<(), XlsxError> {
+    /// # let workbook = Workbook::new("test-worksheet_write_record_batch-1.xlsx")?;
+    /// # let mut worksheet = workbook.add_worksheet(None)?;
+    /// let batch = RecordBatch::try_from_iter(vec![
+    ///     ("name", Arc::new(StringArray::from(vec!["apple", "orange"])) as ArrayRef),
+    ///     ("price", Arc::new(Float64Array::from(vec![Some(1.5), None])) as ArrayRef),
+    /// ]).unwrap();
+    /// worksheet.write_record_batch(0, 0, &batch, RecordBatchOptions {
+    ///     table: Some(TableOptions::default()),
+    ///     ..RecordBatchOptions::default()
+    /// })?;
+    /// # workbook.close()
+    /// # }
+    /// ```
+    ///
+    /// Arrow data produced by other libraries, such as a Polars `DataFrame`, can be written once it is exported as a [`RecordBatch`].
+    pub fn write_record_batch(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        batch: &RecordBatch,
+        options: RecordBatchOptions,
+    ) -> Result<(), XlsxError> {
+        let num_columns =
+            WorksheetCol::try_from(batch.num_columns()).map_err(|_| out_of_range_error())?;
+        let num_rows =
+            WorksheetRow::try_from(batch.num_rows()).map_err(|_| out_of_range_error())?;
+        if num_columns == 0 {
+            return Ok(());
+        }
+        let last_col = col
+            .checked_add(num_columns - 1)
+            .ok_or_else(out_of_range_error)?;
+
+        let schema = batch.schema();
+        let data_row = if options.header {
+            for (c, field) in (col..=last_col).zip(schema.fields().iter()) {
+                self.write_string(row, c, field.name(), options.header_format.as_ref())?;
+            }
+            row.checked_add(1).ok_or_else(out_of_range_error)?
+        } else {
+            row
+        };
+
+        for (c, array) in (col..=last_col).zip(batch.columns().iter()) {
+            self.write_arrow_array(data_row, c, array.as_ref(), &options)?;
+        }
+
+        let header_format = &options.header_format;
+        if let Some(mut table) = options.table {
+            if !options.header {
+                table.no_header_row = true;
+            } else if table.columns.is_none() {
+                table.columns = Some(
+                    schema
+                        .fields()
+                        .iter()
+                        .map(|field| TableColumn {
+                            header: Some(field.name().to_string()),
+                            header_format: header_format.clone(),
+                            ..TableColumn::default()
+                        })
+                        .collect(),
+                );
+            }
+            let last_row = data_row
+                .checked_add(num_rows.max(1) - 1)
+                .ok_or_else(out_of_range_error)?;
+            self.add_table(row, col, last_row, last_col, Some(table))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_arrow_array(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &dyn Array,
+        options: &RecordBatchOptions,
+    ) -> Result<(), XlsxError> {
+        match array.data_type() {
+            DataType::Null => {
+                for r in (row..).take(array.len()) {
+                    self.write_arrow_null(r, col, &options.null_handling)?;
+                }
+                Ok(())
+            }
+            DataType::Boolean => {
+                self.write_arrow_values(row, col, array.as_boolean(), options, |ws, r, v| {
+                    ws.write_boolean(r, col, v, None)
+                })
+            }
+            DataType::Int8 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<Int8Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::Int16 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<Int16Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::Int32 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<Int32Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::Int64 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<Int64Type>(),
+                options,
+                |v| v as f64,
+            ),
+            DataType::UInt8 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<UInt8Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::UInt16 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<UInt16Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::UInt32 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<UInt32Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::UInt64 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<UInt64Type>(),
+                options,
+                |v| v as f64,
+            ),
+            DataType::Float32 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<Float32Type>(),
+                options,
+                f64::from,
+            ),
+            DataType::Float64 => self.write_arrow_numbers(
+                row,
+                col,
+                array.as_primitive::<Float64Type>(),
+                options,
+                |v| v,
+            ),
+            DataType::Decimal128(_, scale) => self.write_arrow_decimals(
+                row,
+                col,
+                array.as_primitive::<Decimal128Type>(),
+                *scale,
+                options,
+            ),
+            DataType::Utf8 => {
+                self.write_arrow_values(row, col, array.as_string::<i32>(), options, |ws, r, v| {
+                    ws.write_string(r, col, v, None)
+                })
+            }
+            DataType::LargeUtf8 => {
+                self.write_arrow_values(row, col, array.as_string::<i64>(), options, |ws, r, v| {
+                    ws.write_string(r, col, v, None)
+                })
+            }
+            DataType::Date32 => {
+                self.write_arrow_dates(row, col, array.as_primitive::<Date32Type>(), options)
+            }
+            DataType::Date64 => {
+                self.write_arrow_dates(row, col, array.as_primitive::<Date64Type>(), options)
+            }
+            DataType::Time32(TimeUnit::Second) => {
+                self.write_arrow_times(row, col, array.as_primitive::<Time32SecondType>(), options)
+            }
+            DataType::Time32(TimeUnit::Millisecond) => self.write_arrow_times(
+                row,
+                col,
+                array.as_primitive::<Time32MillisecondType>(),
+                options,
+            ),
+            DataType::Time64(TimeUnit::Microsecond) => self.write_arrow_times(
+                row,
+                col,
+                array.as_primitive::<Time64MicrosecondType>(),
+                options,
+            ),
+            DataType::Time64(TimeUnit::Nanosecond) => self.write_arrow_times(
+                row,
+                col,
+                array.as_primitive::<Time64NanosecondType>(),
+                options,
+            ),
+            DataType::Timestamp(unit, tz) => {
+                let tz = tz
+                    .as_deref()
+                    .map(str::parse::<Tz>)
+                    .transpose()
+                    .map_err(|e| arrow_error(e.to_string()))?;
+                match unit {
+                    TimeUnit::Second => self.write_arrow_timestamps(
+                        row,
+                        col,
+                        array.as_primitive::<TimestampSecondType>(),
+                        tz,
+                        options,
+                    ),
+                    TimeUnit::Millisecond => self.write_arrow_timestamps(
+                        row,
+                        col,
+                        array.as_primitive::<TimestampMillisecondType>(),
+                        tz,
+                        options,
+                    ),
+                    TimeUnit::Microsecond => self.write_arrow_timestamps(
+                        row,
+                        col,
+                        array.as_primitive::<TimestampMicrosecondType>(),
+                        tz,
+                        options,
+                    ),
+                    TimeUnit::Nanosecond => self.write_arrow_timestamps(
+                        row,
+                        col,
+                        array.as_primitive::<TimestampNanosecondType>(),
+                        tz,
+                        options,
+                    ),
+                }
+            }
+            data_type => Err(arrow_error(format!(
+                "Unsupported Arrow data type: {}",
+                data_type
+            ))),
+        }
+    }
+
+    fn write_arrow_null(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        null_handling: &NullHandling,
+    ) -> Result<(), XlsxError> {
+        match null_handling {
+            NullHandling::Skip => Ok(()),
+            NullHandling::Blank => self.write_blank(row, col, None),
+            NullHandling::String(s) => self.write_string(row, col, s, None),
+        }
+    }
+
+    fn write_arrow_values<I, T, F>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        values: I,
+        options: &RecordBatchOptions,
+        mut write: F,
+    ) -> Result<(), XlsxError>
+    where
+        I: IntoIterator<Item = Option<T>>,
+        F: FnMut(&mut Self, WorksheetRow, T) -> Result<(), XlsxError>,
+    {
+        for (r, value) in (row..).zip(values) {
+            match value {
+                Some(value) => write(self, r, value)?,
+                None => self.write_arrow_null(r, col, &options.null_handling)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_arrow_numbers<T: ArrowPrimitiveType>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &PrimitiveArray<T>,
+        options: &RecordBatchOptions,
+        convert: impl Fn(T::Native) -> f64,
+    ) -> Result<(), XlsxError> {
+        self.write_arrow_values(row, col, array, options, |ws, r, v| {
+            ws.write_number(r, col, convert(v), None)
+        })
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    fn write_arrow_decimals(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &PrimitiveArray<Decimal128Type>,
+        scale: i8,
+        options: &RecordBatchOptions,
+    ) -> Result<(), XlsxError> {
+        self.write_arrow_values(row, col, array, options, |ws, r, v| {
+            let value = arrow_decimal(v, scale).ok_or_else(|| {
+                arrow_error(format!(
+                    "Decimal128 value out of range: {}e{}",
+                    v,
+                    -i32::from(scale)
+                ))
+            })?;
+            ws.write_decimal_opt(r, col, &value, None, options.decimal_policy)
+        })
+    }
+
+    /// Without `rust_decimal` feature, decimals are converted to the float nearest to `value / 10^scale`,
+    /// losing digits beyond the precision of a float.
+    #[cfg(not(feature = "rust_decimal"))]
+    fn write_arrow_decimals(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &PrimitiveArray<Decimal128Type>,
+        scale: i8,
+        options: &RecordBatchOptions,
+    ) -> Result<(), XlsxError> {
+        let divisor = 10f64.powi(scale.into());
+        self.write_arrow_numbers(row, col, array, options, |v| v as f64 / divisor)
+    }
+
+    fn write_arrow_dates<T: ArrowPrimitiveType>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &PrimitiveArray<T>,
+        options: &RecordBatchOptions,
+    ) -> Result<(), XlsxError>
+    where
+        T::Native: Into<i64>,
+    {
+        self.write_arrow_values(row, col, array, options, |ws, r, v| {
+            let value: i64 = v.into();
+            let date = as_date::<T>(value)
+                .ok_or_else(|| arrow_error(format!("Date value out of range: {}", value)))?;
+            ws.write_datetime(r, col, &date.into(), Some(&options.date_format))
+        })
+    }
+
+    fn write_arrow_times<T: ArrowPrimitiveType>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &PrimitiveArray<T>,
+        options: &RecordBatchOptions,
+    ) -> Result<(), XlsxError>
+    where
+        T::Native: Into<i64>,
+    {
+        self.write_arrow_values(row, col, array, options, |ws, r, v| {
+            let value: i64 = v.into();
+            let time = as_time::<T>(value)
+                .ok_or_else(|| arrow_error(format!("Time value out of range: {}", value)))?;
+            ws.write_datetime(r, col, &time.into(), Some(&options.time_format))
+        })
+    }
+
+    fn write_arrow_timestamps<T: ArrowTimestampType>(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        array: &PrimitiveArray<T>,
+        tz: Option<Tz>,
+        options: &RecordBatchOptions,
+    ) -> Result<(), XlsxError> {
+        self.write_arrow_values(row, col, array, options, |ws, r, v| {
+            let datetime = match tz {
+                Some(tz) => as_datetime_with_timezone::<T>(v, tz).map(|x| x.naive_local()),
+                None => as_datetime::<T>(v),
+            }
+            .ok_or_else(|| arrow_error(format!("Timestamp value out of range: {}", v)))?;
+            ws.write_datetime(r, col, &datetime.into(), Some(&options.datetime_format))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        ArrayRef, BooleanArray, Date32Array, Decimal128Array, Int32Array, StringArray,
+        TimestampSecondArray,
+    };
+
+    use super::*;
+    use crate::evaluator::FormulaValue;
+    use crate::testing::XlsxFile;
+    use crate::Workbook;
+
+    #[test]
+    fn test_write_record_batch() -> Result<(), XlsxError> {
+        let workbook = Workbook::new("test-worksheet_write_record_batch-2.xlsx")?;
+        let mut worksheet = workbook.add_worksheet(None)?;
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "id",
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+            ),
+            (
+                "name",
+                Arc::new(StringArray::from(vec![Some("a"), Some("b"), None])) as ArrayRef,
+            ),
+            (
+                "flag",
+                Arc::new(BooleanArray::from(vec![true, false, true])) as ArrayRef,
+            ),
+            (
+                "date",
+                Arc::new(Date32Array::from(vec![19000, 19001, 19002])) as ArrayRef,
+            ),
+            (
+                "timestamp",
+                Arc::new(
+                    TimestampSecondArray::from(vec![0, 86400, 1_700_000_000])
+                        .with_timezone("+09:00"),
+                ) as ArrayRef,
+            ),
+            (
+                "price",
+                Arc::new(
+                    Decimal128Array::from(vec![12345, 67890, -100])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        worksheet.write_record_batch(
+            0,
+            0,
+            &batch,
+            RecordBatchOptions {
+                null_handling: NullHandling::String("N/A".to_string()),
+                table: Some(TableOptions::default()),
+                ..RecordBatchOptions::default()
+            },
+        )?;
+        worksheet.write_record_batch(
+            5,
+            0,
+            &batch,
+            RecordBatchOptions {
+                header: false,
+                ..RecordBatchOptions::default()
+            },
+        )?;
+        workbook.close()?;
+
+        let sheet =
+            XlsxFile::open("test-worksheet_write_record_batch-2.xlsx")?.worksheet("Sheet1")?;
+        for (c, name) in (0..).zip(["id", "name", "flag", "date", "timestamp", "price"]) {
+            assert_eq!(sheet.value(0, c), FormulaValue::String(name.to_string()));
+        }
+        assert_eq!(sheet.value(1, 0), FormulaValue::Number(1.));
+        assert_eq!(sheet.value(2, 0), FormulaValue::String("N/A".to_string()));
+        assert_eq!(sheet.value(3, 1), FormulaValue::String("N/A".to_string()));
+        assert_eq!(sheet.value(6, 0), FormulaValue::Empty);
+        assert_eq!(sheet.value(7, 1), FormulaValue::Empty);
+        assert_eq!(sheet.value(2, 2), FormulaValue::Boolean(false));
+
+        let date = sheet.cell(1, 3).unwrap();
+        assert_eq!(date.value, FormulaValue::Number(44569.));
+        assert_eq!(date.style.num_format, "yyyy-mm-dd");
+        let timestamp = sheet.cell(1, 4).unwrap();
+        assert_eq!(timestamp.value, FormulaValue::Number(25569.375));
+        assert_eq!(timestamp.style.num_format, "yyyy-mm-dd hh:mm:ss");
+
+        assert_eq!(sheet.value(1, 5), FormulaValue::Number(123.45));
+        assert_eq!(sheet.value(3, 5), FormulaValue::Number(-1.));
+        assert_eq!(sheet.value(5, 5), FormulaValue::Number(123.45));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rust_decimal")]
+    fn test_write_record_batch_decimal_policy() -> Result<(), XlsxError> {
+        let workbook = Workbook::new("test-worksheet_write_record_batch-3.xlsx")?;
+        let mut worksheet = workbook.add_worksheet(None)?;
+        let batch = RecordBatch::try_from_iter(vec![(
+            "value",
+            Arc::new(
+                Decimal128Array::from(vec![1, 1_234_567_890_123_456_789])
+                    .with_precision_and_scale(38, 19)
+                    .unwrap(),
+            ) as ArrayRef,
+        )])
+        .unwrap();
+        let options = |decimal_policy| RecordBatchOptions {
+            header: false,
+            decimal_policy,
+            ..RecordBatchOptions::default()
+        };
+        worksheet.write_record_batch(0, 0, &batch, options(DecimalPolicy::String))?;
+        assert!(worksheet
+            .write_record_batch(0, 1, &batch, options(DecimalPolicy::Error))
+            .is_err());
+        assert_eq!(
+            arrow_decimal(-12, -3),
+            Decimal::from_str_exact("-12000").ok()
+        );
+        assert_eq!(arrow_decimal(i128::MAX, 0), None);
+        workbook.close()?;
+
+        let sheet =
+            XlsxFile::open("test-worksheet_write_record_batch-3.xlsx")?.worksheet("Sheet1")?;
+        assert_eq!(sheet.value(0, 0), FormulaValue::Number(1e-19));
+        assert_eq!(
+            sheet.value(1, 0),
+            FormulaValue::String("0.1234567890123456789".to_string())
+        );
+        Ok(())
+    }
+}
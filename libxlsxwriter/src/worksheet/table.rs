@@ -1,5 +1,6 @@
 use std::os::raw::c_char;
 
+use crate::utility::CellRange;
 use crate::{
     convert_bool, error::XlsxErrorSource, try_to_vec, CStringHelper, WorksheetCol, WorksheetRow,
     XlsxError,
@@ -187,7 +188,7 @@ pub struct TableOptions {
     pub columns: Option<Vec<TableColumn>>,
 }
 
-impl<'a> Worksheet<'a> {
+impl Worksheet {
     /// This function is used to add a table to a worksheet. Tables in Excel are a way of grouping a
     /// range of cells into a single entity that has common formatting or that can be referenced
     /// from formulas. Tables can have column headers, autofilters, total rows, column formulas and
@@ -220,6 +221,7 @@ impl<'a> Worksheet<'a> {
         last_col: WorksheetCol,
         options: Option<TableOptions>,
     ) -> Result<(), XlsxError> {
+        self.check_range("add_table", first_row, first_col, last_row, last_col)?;
         let mut cstring_helper = CStringHelper::new();
 
         if options
@@ -229,9 +231,14 @@ impl<'a> Worksheet<'a> {
                 x.len() as WorksheetCol != last_col - first_col + 1
             })
         {
-            return Err(XlsxError {
-                source: XlsxErrorSource::NumberOfColumnsIsNotMatched,
-            });
+            return Err(self.range_error(
+                XlsxErrorSource::NumberOfColumnsIsNotMatched.into(),
+                "add_table",
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            ));
         }
 
         let columns: Option<Vec<_>> = options
@@ -245,8 +252,12 @@ impl<'a> Worksheet<'a> {
                                 Ok(libxlsxwriter_sys::lxw_table_column {
                                     header: cstring_helper.add_opt(y.header.as_deref())?
                                         as *mut c_char,
-                                    formula: cstring_helper.add_opt(y.formula.as_deref())?
-                                        as *mut c_char,
+                                    formula: cstring_helper.add_opt(
+                                        y.formula
+                                            .as_deref()
+                                            .map(|x| self._workbook.prepare_formula(x))
+                                            .as_deref(),
+                                    )? as *mut c_char,
                                     total_string: cstring_helper
                                         .add_opt(y.total_string.as_deref())?
                                         as *mut c_char,
@@ -264,7 +275,10 @@ impl<'a> Worksheet<'a> {
                         .map(|x| x.map(Box::pin)),
                 )
             })
-            .transpose()?;
+            .transpose()
+            .map_err(|e| {
+                self.range_error(e, "add_table", first_row, first_col, last_row, last_col)
+            })?;
 
         let columns_ptr: Option<Vec<_>> = columns.as_ref().map(|x| {
             let mut p: Vec<_> = x
@@ -278,7 +292,11 @@ impl<'a> Worksheet<'a> {
 
         let mut options = if let Some(options) = options {
             Some(libxlsxwriter_sys::lxw_table_options {
-                name: cstring_helper.add_opt(options.name.as_deref())? as *mut c_char,
+                name: cstring_helper
+                    .add_opt(options.name.as_deref())
+                    .map_err(|e| {
+                        self.range_error(e, "add_table", first_row, first_col, last_row, last_col)
+                    })? as *mut c_char,
                 no_header_row: convert_bool(options.no_header_row),
                 no_autofilter: convert_bool(options.no_autofilter),
                 no_banded_rows: convert_bool(options.no_banded_rows),
@@ -298,7 +316,7 @@ impl<'a> Worksheet<'a> {
 
         unsafe {
             let result = libxlsxwriter_sys::worksheet_add_table(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
@@ -316,8 +334,25 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "add_table",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
+
+    /// Same as [`Worksheet::add_table`], with the range given as a [`CellRange`].
+    pub fn add_table_ref(
+        &mut self,
+        range: &CellRange,
+        options: Option<TableOptions>,
+    ) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) = self.range_bounds(range, "add_table")?;
+        self.add_table(first_row, first_col, last_row, last_col, options)
+    }
 }
@@ -0,0 +1,468 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::buffer::{SheetBuffer, SheetOp};
+use super::{DateTime, WorksheetCol, WorksheetRow};
+use crate::error::XlsxErrorSource;
+use crate::{Worksheet, XlsxError};
+
+/// A worksheet layout which is recorded once and replayed onto any number of worksheets with [`Worksheet::write_template`].
+///
+/// libxlsxwriter cannot copy a worksheet, so a template is recorded as a [`SheetBuffer`], including writes, row and column formats,
+/// merged ranges, data validations, conditional formats and images. Each replay can move the layout by [`ReplayOptions::offset`]
+/// and substitute values for placeholders.
+///
+/// A placeholder is a key in braces, such as `{region}`, in the text of a string cell, a rich string, a merged range or a URL.
+/// A string cell which is just a placeholder is written as the value given for the key, so a number stays a number.
+/// Other placeholders are replaced with the text of the value. `{{` and `}}` are written as `{` and `}`.
+/// Formulas are not substituted, but their relative references are moved with the offset as Excel does when a formula is copied.
+/// ```rust
+/// use xlsxwriter::prelude::*;
+/// # fn main() -> Result<(), XlsxError> {
+/// let bold = Format::new().set_bold().clone();
+/// let mut layout = SheetBuffer::new();
+/// layout.merge_range(0, 0, 0, 1, "Sales of {region}", Some(&bold))?;
+/// layout.write_string(1, 0, "Target", None)?;
+/// layout.write_string(1, 1, "{target}", None)?;
+/// layout.write_string(2, 0, "Actual", None)?;
+/// layout.write_formula(2, 1, "=SUM(D2:D100)", None)?;
+/// let template = SheetTemplate::new(layout);
+/// assert_eq!(template.placeholders(), ["region", "target"]);
+///
+/// let workbook = Workbook::new("test-sheet_template.xlsx")?;
+/// let mut worksheet = workbook.add_worksheet(None)?;
+/// for (i, (region, target)) in [("North", 1200.), ("South", 900.)].iter().enumerate() {
+///     // Written to A1:B3 and A5:B7. The formula of the second one is =SUM(D6:D104)
+///     let options = ReplayOptions::new()
+///         .offset(i as u32 * 4, 0)
+///         .value("region", *region)
+///         .value("target", *target);
+///     worksheet.write_template(&template, &options)?;
+/// }
+/// // The same layout on another worksheet
+/// workbook
+///     .add_worksheet(Some("West"))?
+///     .write_template(&template, &ReplayOptions::new().value("region", "West").value("target", 700.))?;
+/// workbook.close()
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SheetTemplate {
+    ops: Vec<SheetOp>,
+}
+
+/// A value substituted for a placeholder of a [`SheetTemplate`].
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum TemplateValue {
+    Number(f64),
+    String(String),
+    Boolean(bool),
+    DateTime(DateTime),
+    /// A blank cell, or an empty string in a text.
+    Blank,
+}
+
+impl From<f64> for TemplateValue {
+    fn from(value: f64) -> Self {
+        TemplateValue::Number(value)
+    }
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        TemplateValue::String(value.to_string())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        TemplateValue::String(value)
+    }
+}
+
+impl From<bool> for TemplateValue {
+    fn from(value: bool) -> Self {
+        TemplateValue::Boolean(value)
+    }
+}
+
+impl From<DateTime> for TemplateValue {
+    fn from(value: DateTime) -> Self {
+        TemplateValue::DateTime(value)
+    }
+}
+
+impl TemplateValue {
+    /// Text of the value in a string. Booleans are written as Excel shows them, and dates in ISO 8601 format.
+    fn to_text(&self) -> String {
+        match self {
+            TemplateValue::Number(number) => number.to_string(),
+            TemplateValue::String(text) => text.clone(),
+            TemplateValue::Boolean(true) => "TRUE".to_string(),
+            TemplateValue::Boolean(false) => "FALSE".to_string(),
+            TemplateValue::DateTime(datetime) => {
+                let date = format!(
+                    "{:04}-{:02}-{:02}",
+                    datetime.year, datetime.month, datetime.day
+                );
+                let time = if datetime.second.fract() == 0. {
+                    format!(
+                        "{:02}:{:02}:{:02}",
+                        datetime.hour, datetime.min, datetime.second
+                    )
+                } else {
+                    format!(
+                        "{:02}:{:02}:{:06.3}",
+                        datetime.hour, datetime.min, datetime.second
+                    )
+                };
+                match (datetime.has_date(), datetime.has_time()) {
+                    (true, true) => format!("{} {}", date, time),
+                    (true, false) => date,
+                    (false, _) => time,
+                }
+            }
+            TemplateValue::Blank => String::new(),
+        }
+    }
+}
+
+/// Options of [`Worksheet::write_template`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplayOptions {
+    /// Number of rows to move the template down.
+    pub row_offset: WorksheetRow,
+    /// Number of columns to move the template right.
+    pub col_offset: WorksheetCol,
+    /// Values of the placeholders.
+    pub values: HashMap<String, TemplateValue>,
+}
+
+impl ReplayOptions {
+    /// Options without an offset and values.
+    pub fn new() -> Self {
+        ReplayOptions::default()
+    }
+
+    /// Move the template down by `rows` and right by `cols`.
+    pub fn offset(mut self, rows: WorksheetRow, cols: WorksheetCol) -> Self {
+        self.row_offset = rows;
+        self.col_offset = cols;
+        self
+    }
+
+    /// Substitute `value` for the placeholder `{key}`.
+    pub fn value<V: Into<TemplateValue>>(mut self, key: &str, value: V) -> Self {
+        self.values.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+impl From<SheetBuffer> for SheetTemplate {
+    fn from(buffer: SheetBuffer) -> Self {
+        SheetTemplate { ops: buffer.ops }
+    }
+}
+
+impl SheetTemplate {
+    /// A template replaying the operations recorded in `buffer`.
+    pub fn new(buffer: SheetBuffer) -> Self {
+        SheetTemplate::from(buffer)
+    }
+
+    /// Returns the keys of the placeholders in the template, in sorted order.
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut keys = BTreeSet::new();
+        for op in &self.ops {
+            for text in op_texts(op) {
+                for part in parse(text) {
+                    if let Part::Placeholder(key) = part {
+                        keys.insert(key.to_string());
+                    }
+                }
+            }
+        }
+        keys.into_iter().collect()
+    }
+}
+
+/// Texts of an operation which may contain placeholders.
+fn op_texts(op: &SheetOp) -> Vec<&str> {
+    match op {
+        SheetOp::String { text, .. } => vec![text.as_str()],
+        SheetOp::Url { url, .. } => vec![url.as_str()],
+        SheetOp::MergeRange { string, .. } => vec![string.as_str()],
+        SheetOp::RichString { text, .. } => text.iter().map(|(text, _)| text.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A part of a text with placeholders.
+#[derive(Debug, PartialEq)]
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn parse(text: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(i) = rest.find(['{', '}']) {
+        parts.push(Part::Text(&rest[..i]));
+        let brace = &rest[i..i + 1];
+        if rest[i + 1..].starts_with(brace) {
+            parts.push(Part::Text(brace));
+            rest = &rest[i + 2..];
+        } else if brace == "}" {
+            parts.push(Part::Text(brace));
+            rest = &rest[i + 1..];
+        } else if let Some(end) = rest[i + 1..].find('}') {
+            parts.push(Part::Placeholder(&rest[i + 1..i + 1 + end]));
+            rest = &rest[i + 2 + end..];
+        } else {
+            // An unclosed brace is written as it is
+            parts.push(Part::Text(&rest[i..]));
+            rest = "";
+        }
+    }
+    parts.push(Part::Text(rest));
+    parts.retain(|x| *x != Part::Text(""));
+    parts
+}
+
+fn lookup<'a>(
+    values: &'a HashMap<String, TemplateValue>,
+    key: &str,
+) -> Result<&'a TemplateValue, XlsxError> {
+    values
+        .get(key)
+        .ok_or_else(|| XlsxErrorSource::MissingTemplateValue(key.to_string()).into())
+}
+
+fn substitute(text: &str, values: &HashMap<String, TemplateValue>) -> Result<String, XlsxError> {
+    let mut result = String::with_capacity(text.len());
+    for part in parse(text) {
+        match part {
+            Part::Text(text) => result.push_str(text),
+            Part::Placeholder(key) => result.push_str(&lookup(values, key)?.to_text()),
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the operation with the placeholders replaced with `values`.
+fn substitute_op(
+    op: SheetOp,
+    values: &HashMap<String, TemplateValue>,
+) -> Result<SheetOp, XlsxError> {
+    Ok(match op {
+        SheetOp::String {
+            row,
+            col,
+            text,
+            format,
+        } => match parse(&text).as_slice() {
+            [Part::Placeholder(key)] => match lookup(values, key)?.clone() {
+                TemplateValue::Number(number) => SheetOp::Number {
+                    row,
+                    col,
+                    number,
+                    format,
+                },
+                TemplateValue::String(text) => SheetOp::String {
+                    row,
+                    col,
+                    text,
+                    format,
+                },
+                TemplateValue::Boolean(value) => SheetOp::Boolean {
+                    row,
+                    col,
+                    value,
+                    format,
+                },
+                TemplateValue::DateTime(datetime) => SheetOp::DateTime {
+                    row,
+                    col,
+                    datetime,
+                    format,
+                },
+                TemplateValue::Blank => SheetOp::Blank { row, col, format },
+            },
+            _ => SheetOp::String {
+                row,
+                col,
+                text: substitute(&text, values)?,
+                format,
+            },
+        },
+        SheetOp::Url {
+            row,
+            col,
+            url,
+            format,
+        } => SheetOp::Url {
+            row,
+            col,
+            url: substitute(&url, values)?,
+            format,
+        },
+        SheetOp::MergeRange {
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+            string,
+            format,
+        } => SheetOp::MergeRange {
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+            string: substitute(&string, values)?,
+            format,
+        },
+        SheetOp::RichString {
+            row,
+            col,
+            text,
+            format,
+        } => SheetOp::RichString {
+            row,
+            col,
+            text: text
+                .into_iter()
+                .map(|(text, format)| Ok((substitute(&text, values)?, format)))
+                .collect::<Result<_, XlsxError>>()?,
+            format,
+        },
+        op => op,
+    })
+}
+
+impl Worksheet {
+    /// Replay `template` onto the worksheet, moved by the offset and with the placeholders replaced with the values of `options`.
+    /// See [`SheetTemplate`].
+    ///
+    /// Writing stops at the first operation which returns an error, including a placeholder without a value.
+    pub fn write_template(
+        &mut self,
+        template: &SheetTemplate,
+        options: &ReplayOptions,
+    ) -> Result<(), XlsxError> {
+        for op in &template.ops {
+            let op = op.offset(options.row_offset, options.col_offset)?;
+            substitute_op(op, &options.values)?.apply(self)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::worksheet::SheetWriter;
+
+    #[test]
+    fn test_substitute() {
+        let values: HashMap<String, TemplateValue> = [
+            ("region", TemplateValue::from("North")),
+            ("target", TemplateValue::from(1200.5)),
+            ("open", TemplateValue::from(true)),
+            ("date", TemplateValue::from(DateTime::date(2024, 3, 1))),
+        ]
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+        for (text, expected) in [
+            ("{region}: {target}", "North: 1200.5"),
+            ("{{region}} {open} {date}", "{region} TRUE 2024-03-01"),
+            ("} {region", "} {region"),
+            ("", ""),
+        ] {
+            assert_eq!(substitute(text, &values).unwrap(), expected, "{}", text);
+        }
+        assert_eq!(
+            substitute("{missing}", &values).unwrap_err().kind(),
+            crate::XlsxErrorKind::MissingTemplateValue
+        );
+
+        let op = SheetOp::String {
+            row: 1,
+            col: 2,
+            text: "{target}".to_string(),
+            format: None,
+        };
+        assert_eq!(
+            substitute_op(op.offset(10, 1).unwrap(), &values).unwrap(),
+            SheetOp::Number {
+                row: 11,
+                col: 3,
+                number: 1200.5,
+                format: None,
+            }
+        );
+
+        let mut buffer = SheetBuffer::new();
+        buffer.write_string(0, 0, "{b} and {a}", None).unwrap();
+        buffer.merge_range(1, 0, 1, 1, "{a}{{c}}", None).unwrap();
+        buffer.write_formula(2, 0, "=\"{d}\"", None).unwrap();
+        assert_eq!(SheetTemplate::new(buffer).placeholders(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_offset_conditional_format() {
+        use crate::worksheet::conditional_format::ConditionalFormat;
+        use crate::worksheet::validation::{DataValidation, DataValidationType};
+        use crate::Format;
+
+        let format = Format::new();
+        let mut buffer = SheetBuffer::new();
+        buffer
+            .conditional_format_range(0, 0, 9, 3, &ConditionalFormat::formula("=$B1>100", &format))
+            .unwrap();
+        buffer
+            .data_validation_cell(
+                0,
+                1,
+                &DataValidation::new(
+                    DataValidationType::CustomFormula {
+                        ignore_blank: true,
+                        formula: "=B1<=$A$1".to_string(),
+                    },
+                    None,
+                    None,
+                ),
+            )
+            .unwrap();
+        let ops: Vec<SheetOp> = buffer
+            .ops()
+            .iter()
+            .map(|op| op.offset(10, 1).unwrap())
+            .collect();
+        assert_eq!(
+            ops[0],
+            SheetOp::ConditionalFormatRange {
+                first_row: 10,
+                first_col: 1,
+                last_row: 19,
+                last_col: 4,
+                conditional_format: ConditionalFormat::formula("=$B11>100", &format),
+            }
+        );
+        assert_eq!(
+            ops[1],
+            SheetOp::DataValidationCell {
+                row: 10,
+                col: 2,
+                validation: DataValidation::new(
+                    DataValidationType::CustomFormula {
+                        ignore_blank: true,
+                        formula: "=C11<=$A$1".to_string(),
+                    },
+                    None,
+                    None,
+                ),
+            }
+        );
+    }
+}
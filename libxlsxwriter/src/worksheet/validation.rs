@@ -1,9 +1,14 @@
+use crate::error::{ErrorContext, XlsxErrorSource};
+use crate::formula;
+use crate::limits::{self, OverflowPolicy, OverflowWarning};
+use crate::utility::{CellRange, CellRef};
 use crate::{
-    convert_validation_bool, try_to_vec, CStringHelper, Worksheet, WorksheetCol, WorksheetRow,
-    XlsxError,
+    convert_validation_bool, try_to_vec, CStringHelper, Workbook, Worksheet, WorksheetCol,
+    WorksheetRow, XlsxError,
 };
 
 use super::DateTime;
+use std::borrow::Cow;
 use std::ffi::CString;
 use std::os::raw::c_char;
 
@@ -65,6 +70,7 @@ pub enum DataValidationType {
     Any,
 }
 
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct DataValidation {
     input_message: Option<InputMessageOptions>,
     error_alert: Option<ErrorAlertOptions>,
@@ -146,6 +152,26 @@ pub enum DataValidationNumberOptions<T> {
     LessThanOrEqualTo(T),
 }
 
+impl DataValidationNumberOptions<f64> {
+    /// Returns an error for a NaN or infinite number, which Excel doesn't support.
+    fn check_numbers(&self) -> Result<(), XlsxError> {
+        let (first, second) = match self {
+            DataValidationNumberOptions::Between(first, second)
+            | DataValidationNumberOptions::NotBetween(first, second) => (*first, *second),
+            DataValidationNumberOptions::EqualTo(value)
+            | DataValidationNumberOptions::NotEqualTo(value)
+            | DataValidationNumberOptions::GreaterThan(value)
+            | DataValidationNumberOptions::LessThan(value)
+            | DataValidationNumberOptions::GreaterThanOrEqualTo(value)
+            | DataValidationNumberOptions::LessThanOrEqualTo(value) => (*value, *value),
+        };
+        match [first, second].iter().find(|x| !x.is_finite()) {
+            Some(x) => Err(XlsxErrorSource::NonFiniteNumber(*x).into()),
+            None => Ok(()),
+        }
+    }
+}
+
 impl<T> DataValidationNumberOptions<T> {
     fn value(&self) -> u8 {
         let value = match self {
@@ -199,14 +225,47 @@ impl DataValidation {
             validation_type,
         }
     }
+    /// Returns an error for a NaN or infinite number in the criteria of a decimal validation.
+    pub(crate) fn check_numbers(&self) -> Result<(), XlsxError> {
+        match &self.validation_type {
+            DataValidationType::Decimal { number_options, .. } => number_options.check_numbers(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move the relative cell references in the formula of the validation by `rows` and `cols`.
+    pub(crate) fn offset_formula(
+        &mut self,
+        rows: WorksheetRow,
+        cols: WorksheetCol,
+    ) -> Result<(), XlsxError> {
+        match &mut self.validation_type {
+            DataValidationType::IntegerFormula { formula, .. }
+            | DataValidationType::DecimalFormula { formula, .. }
+            | DataValidationType::ListFormula { formula, .. }
+            | DataValidationType::DateFormula { formula, .. }
+            | DataValidationType::TimeFormula { formula, .. }
+            | DataValidationType::LengthFormula { formula, .. }
+            | DataValidationType::CustomFormula { formula, .. } => {
+                *formula = formula::offset_references(formula, rows, cols)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// `max_list_values` is the number of values of a list validation to be written.
     pub(crate) fn to_c_struct(
         &self,
+        workbook: &Workbook,
         c_string_helper: &mut CStringHelper,
+        max_list_values: usize,
     ) -> Result<CDataValidation, XlsxError> {
         let mut _value_list: Option<Vec<Vec<u8>>> = match &self.validation_type {
             DataValidationType::List { values, .. } => {
                 let mapped_vec = values
                     .iter()
+                    .take(max_list_values)
                     .map(|y| CString::new(y as &str).unwrap().into_bytes_with_nul())
                     .collect();
                 Some(mapped_vec)
@@ -215,7 +274,10 @@ impl DataValidation {
         };
         let mut _value_list_ptr: Option<Vec<*const c_char>> = match &self.validation_type {
             DataValidationType::List { values, .. } => Some(try_to_vec(
-                values.iter().map(|y| Ok(c_string_helper.add(y)?)),
+                values
+                    .iter()
+                    .take(max_list_values)
+                    .map(|y| Ok(c_string_helper.add(y)?)),
             ))
             .transpose()?,
             _ => None,
@@ -330,16 +392,21 @@ impl DataValidation {
                     } => value as f64,
                     _ => 0.,
                 },
-                value_formula: c_string_helper.add_opt(match &self.validation_type {
-                    DataValidationType::IntegerFormula { formula, .. }
-                    | DataValidationType::DecimalFormula { formula, .. }
-                    | DataValidationType::ListFormula { formula, .. }
-                    | DataValidationType::TimeFormula { formula, .. }
-                    | DataValidationType::DateFormula { formula, .. }
-                    | DataValidationType::LengthFormula { formula, .. }
-                    | DataValidationType::CustomFormula { formula, .. } => Some(formula),
-                    _ => Some(""),
-                })? as *mut c_char,
+                value_formula: c_string_helper.add_opt(
+                    match &self.validation_type {
+                        DataValidationType::IntegerFormula { formula, .. }
+                        | DataValidationType::DecimalFormula { formula, .. }
+                        | DataValidationType::ListFormula { formula, .. }
+                        | DataValidationType::TimeFormula { formula, .. }
+                        | DataValidationType::DateFormula { formula, .. }
+                        | DataValidationType::LengthFormula { formula, .. }
+                        | DataValidationType::CustomFormula { formula, .. } => {
+                            Some(workbook.prepare_formula(formula))
+                        }
+                        _ => Some(Cow::Borrowed("")),
+                    }
+                    .as_deref(),
+                )? as *mut c_char,
                 value_list: _value_list_ptr
                     .as_mut()
                     .map_or_else(std::ptr::null_mut, Vec::as_mut_ptr),
@@ -397,7 +464,46 @@ pub(crate) struct CDataValidation {
     pub(crate) data_validation: libxlsxwriter_sys::lxw_data_validation,
 }
 
-impl<'a> Worksheet<'a> {
+impl Worksheet {
+    /// Apply the overflow policy to the values of a list validation and returns the number of values to be written.
+    /// If not even the first value fits, an error is returned whatever the policy.
+    fn fit_validation_list(
+        &self,
+        validation: &DataValidation,
+        context: ErrorContext,
+    ) -> Result<usize, XlsxError> {
+        let values = match &validation.validation_type {
+            DataValidationType::List { values, .. } => values,
+            _ => return Ok(0),
+        };
+        let count = limits::fitting_list_values(values);
+        if count == values.len() {
+            return Ok(count);
+        }
+        match self._workbook.overflow_policy() {
+            _ if count == 0 => Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_255_STRING_LENGTH_EXCEEDED,
+            )
+            .with_context(context)),
+            OverflowPolicy::Error => Err(XlsxError::new(
+                libxlsxwriter_sys::lxw_error_LXW_ERROR_255_STRING_LENGTH_EXCEEDED,
+            )
+            .with_context(context)),
+            policy => {
+                self._workbook.warn_overflow(&OverflowWarning {
+                    sheet_name: context.sheet_name,
+                    cell: context.first_cell,
+                    method: context.method.unwrap_or_default(),
+                    length: values.iter().map(|x| x.chars().count()).sum::<usize>() + values.len()
+                        - 1,
+                    limit: limits::MAX_LIST_LENGTH,
+                    policy,
+                });
+                Ok(count)
+            }
+        }
+    }
+
     /// This function is used to construct an Excel data validation or to limit the user input to a dropdown list of values
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -431,11 +537,21 @@ impl<'a> Worksheet<'a> {
         col: WorksheetCol,
         validation: &DataValidation,
     ) -> Result<(), XlsxError> {
+        self.check_cell("data_validation_cell", row, col)?;
+        validation
+            .check_numbers()
+            .map_err(|e| self.cell_error(e, "data_validation_cell", row, col))?;
+        let max_list_values = self.fit_validation_list(
+            validation,
+            self.cell_context("data_validation_cell", row, col),
+        )?;
         unsafe {
             let mut c_string_helper = CStringHelper::new();
-            let mut validation = validation.to_c_struct(&mut c_string_helper)?;
+            let mut validation = validation
+                .to_c_struct(&self._workbook, &mut c_string_helper, max_list_values)
+                .map_err(|e| self.cell_error(e, "data_validation_cell", row, col))?;
             let result = libxlsxwriter_sys::worksheet_data_validation_cell(
-                self.worksheet,
+                self.raw_worksheet()?,
                 row,
                 col,
                 &mut validation.data_validation,
@@ -444,11 +560,20 @@ impl<'a> Worksheet<'a> {
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.cell_error(XlsxError::new(result), "data_validation_cell", row, col))
             }
         }
     }
 
+    /// Same as [`Worksheet::data_validation_cell`], with the cell given as a [`CellRef`].
+    pub fn data_validation_cell_ref(
+        &mut self,
+        cell: &CellRef,
+        validation: &DataValidation,
+    ) -> Result<(), XlsxError> {
+        self.data_validation_cell(cell.row, cell.col, validation)
+    }
+
     /// The this function is the same as the `data_validation_cell()`, see above, except the data validation is applied to a range of cells.
     /// ```rust
     /// # use xlsxwriter::prelude::*;
@@ -482,26 +607,80 @@ impl<'a> Worksheet<'a> {
         last_col: WorksheetCol,
         validation: &DataValidation,
     ) -> Result<(), XlsxError> {
+        self.check_range(
+            "data_validation_range",
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+        )?;
+        validation.check_numbers().map_err(|e| {
+            self.range_error(
+                e,
+                "data_validation_range",
+                first_row,
+                first_col,
+                last_row,
+                last_col,
+            )
+        })?;
+        let max_list_values = self.fit_validation_list(
+            validation,
+            ErrorContext {
+                sheet_name: Some(self.name()),
+                first_cell: Some((first_row, first_col)),
+                last_cell: Some((last_row, last_col)),
+                method: Some("data_validation_range"),
+            },
+        )?;
         unsafe {
             let mut c_string_helper = CStringHelper::new();
             let result = libxlsxwriter_sys::worksheet_data_validation_range(
-                self.worksheet,
+                self.raw_worksheet()?,
                 first_row,
                 first_col,
                 last_row,
                 last_col,
                 &mut validation
-                    .to_c_struct(&mut c_string_helper)?
+                    .to_c_struct(&self._workbook, &mut c_string_helper, max_list_values)
+                    .map_err(|e| {
+                        self.range_error(
+                            e,
+                            "data_validation_range",
+                            first_row,
+                            first_col,
+                            last_row,
+                            last_col,
+                        )
+                    })?
                     .data_validation,
             );
             std::mem::drop(c_string_helper);
             if result == libxlsxwriter_sys::lxw_error_LXW_NO_ERROR {
                 Ok(())
             } else {
-                Err(XlsxError::new(result))
+                Err(self.range_error(
+                    XlsxError::new(result),
+                    "data_validation_range",
+                    first_row,
+                    first_col,
+                    last_row,
+                    last_col,
+                ))
             }
         }
     }
+
+    /// Same as [`Worksheet::data_validation_range`], with the range given as a [`CellRange`].
+    pub fn data_validation_range_ref(
+        &mut self,
+        range: &CellRange,
+        validation: &DataValidation,
+    ) -> Result<(), XlsxError> {
+        let (first_row, first_col, last_row, last_col) =
+            self.range_bounds(range, "data_validation_range")?;
+        self.data_validation_range(first_row, first_col, last_row, last_col, validation)
+    }
 }
 
 #[cfg(test)]
@@ -535,6 +714,67 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_validation_non_finite() -> Result<(), XlsxError> {
+        let workbook = Workbook::new("test-worksheet_validation-non_finite.xlsx")?;
+        let mut worksheet = workbook.add_worksheet(None)?;
+        for number_options in [
+            DataValidationNumberOptions::Between(0., f64::INFINITY),
+            DataValidationNumberOptions::LessThan(f64::NAN),
+        ] {
+            let validation = DataValidation::new(
+                DataValidationType::Decimal {
+                    ignore_blank: true,
+                    number_options,
+                },
+                None,
+                None,
+            );
+            assert_eq!(
+                worksheet
+                    .data_validation_cell(1, 0, &validation)
+                    .unwrap_err()
+                    .kind(),
+                crate::XlsxErrorKind::NonFiniteNumber
+            );
+            assert_eq!(
+                worksheet
+                    .data_validation_range(1, 0, 5, 0, &validation)
+                    .unwrap_err()
+                    .kind(),
+                crate::XlsxErrorKind::NonFiniteNumber
+            );
+        }
+        workbook.close()
+    }
+
+    #[test]
+    fn test_validation_list_overflow() -> Result<(), XlsxError> {
+        let workbook = Workbook::new("test-worksheet_validation-list_overflow.xlsx")?;
+        workbook.set_overflow_policy(OverflowPolicy::Truncate);
+        let mut worksheet = workbook.add_worksheet(None)?;
+        let list = |values: Vec<String>| {
+            DataValidation::new(
+                DataValidationType::List {
+                    ignore_blank: true,
+                    dropdown: true,
+                    values,
+                },
+                None,
+                None,
+            )
+        };
+        worksheet.data_validation_cell(0, 0, &list(vec!["a".repeat(200), "b".repeat(100)]))?;
+        assert_eq!(
+            worksheet
+                .data_validation_cell(1, 0, &list(vec!["a".repeat(300)]))
+                .unwrap_err()
+                .kind(),
+            crate::XlsxErrorKind::StringLengthExceeded255
+        );
+        workbook.close()
+    }
+
     #[test]
     fn test_validation2() -> Result<(), XlsxError> {
         let workbook = Workbook::new("test-worksheet_validation-cell-2.xlsx")?;
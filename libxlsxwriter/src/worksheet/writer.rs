@@ -0,0 +1,484 @@
+use super::buffer::{SheetBuffer, SheetOp};
+use super::conditional_format::ConditionalFormat;
+use super::validation::DataValidation;
+use super::{CellError, DateTime, ImageOptions, WorksheetCol, WorksheetRow};
+use crate::chart::{Chart, ChartType, ChartWriter};
+use crate::workbook::DefinedName;
+use crate::{Format, Workbook, Worksheet, XlsxError};
+
+/// Worksheet operations shared by [`Worksheet`], [`SheetBuffer`] and [`MockWorksheet`](crate::mock::MockWorksheet).
+///
+/// Code which builds a sheet through this trait can be unit tested without writing a file: pass a [`SheetBuffer`]
+/// or a [`MockWorkbook`](crate::mock::MockWorkbook) in tests, and inspect the recorded [`SheetOp`]s.
+/// The methods take the same arguments as the [`Worksheet`] methods of the same name.
+/// ```rust
+/// use xlsxwriter::prelude::*;
+///
+/// fn write_total<W: SheetWriter>(sheet: &mut W, total: f64) -> Result<(), XlsxError> {
+///     let bold = Format::new().set_bold().clone();
+///     sheet.write_string(0, 0, "Total", Some(&bold))?;
+///     sheet.write_number(0, 1, total, None)
+/// }
+///
+/// # fn main() -> Result<(), XlsxError> {
+/// let mut sheet = SheetBuffer::new();
+/// write_total(&mut sheet, 42.)?;
+/// assert_eq!(
+///     sheet.cell(0, 0),
+///     Some(&SheetOp::String {
+///         row: 0,
+///         col: 0,
+///         text: "Total".to_string(),
+///         format: Some(Format::new().set_bold().clone()),
+///     })
+/// );
+///
+/// // The same code writes a file
+/// let workbook = Workbook::new("test-sheet_writer.xlsx")?;
+/// write_total(&mut workbook.add_worksheet(None)?, 42.)?;
+/// workbook.close()
+/// # }
+/// ```
+/// Called through this trait, the arguments of a [`Worksheet`] method are copied into a [`SheetOp`] before they are written.
+pub trait SheetWriter {
+    /// Perform `op`. A [`Worksheet`] writes it, and a [`SheetBuffer`] records it.
+    fn write_op(&mut self, op: SheetOp) -> Result<(), XlsxError>;
+
+    /// Perform the operations recorded in `buffer`, in the order they were recorded.
+    fn write_buffer(&mut self, buffer: &SheetBuffer) -> Result<(), XlsxError> {
+        for op in buffer.ops() {
+            self.write_op(op.clone())?;
+        }
+        Ok(())
+    }
+
+    /// See [`Worksheet::write_number`].
+    fn write_number(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        number: f64,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Number {
+            row,
+            col,
+            number,
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_string`].
+    fn write_string(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        text: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::String {
+            row,
+            col,
+            text: text.to_string(),
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_formula`].
+    fn write_formula(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Formula {
+            row,
+            col,
+            formula: formula.to_string(),
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_formula_num`].
+    fn write_formula_num(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: &str,
+        format: Option<&Format>,
+        number: f64,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::FormulaNum {
+            row,
+            col,
+            formula: formula.to_string(),
+            format: format.cloned(),
+            number,
+        })
+    }
+
+    /// See [`Worksheet::write_formula_str`].
+    fn write_formula_str(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        formula: &str,
+        format: Option<&Format>,
+        result: &str,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::FormulaStr {
+            row,
+            col,
+            formula: formula.to_string(),
+            format: format.cloned(),
+            result: result.to_string(),
+        })
+    }
+
+    /// See [`Worksheet::write_datetime`].
+    fn write_datetime(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        datetime: &DateTime,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::DateTime {
+            row,
+            col,
+            datetime: datetime.clone(),
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_url`].
+    fn write_url(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        url: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Url {
+            row,
+            col,
+            url: url.to_string(),
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_boolean`].
+    fn write_boolean(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        value: bool,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Boolean {
+            row,
+            col,
+            value,
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_blank`].
+    fn write_blank(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Blank {
+            row,
+            col,
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_error`].
+    fn write_error(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        error: CellError,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Error {
+            row,
+            col,
+            error,
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::write_rich_string`].
+    fn write_rich_string(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        text: &[(&str, Option<&Format>)],
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::RichString {
+            row,
+            col,
+            text: text
+                .iter()
+                .map(|(text, format)| (text.to_string(), format.cloned()))
+                .collect(),
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::merge_range`].
+    fn merge_range(
+        &mut self,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        string: &str,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::MergeRange {
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+            string: string.to_string(),
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::set_row`].
+    fn set_row(
+        &mut self,
+        row: WorksheetRow,
+        height: f64,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Row {
+            row,
+            height,
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::set_column`].
+    fn set_column(
+        &mut self,
+        first_col: WorksheetCol,
+        last_col: WorksheetCol,
+        width: f64,
+        format: Option<&Format>,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Column {
+            first_col,
+            last_col,
+            width,
+            format: format.cloned(),
+        })
+    }
+
+    /// See [`Worksheet::data_validation_cell`].
+    fn data_validation_cell(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        validation: &DataValidation,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::DataValidationCell {
+            row,
+            col,
+            validation: validation.clone(),
+        })
+    }
+
+    /// See [`Worksheet::data_validation_range`].
+    fn data_validation_range(
+        &mut self,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        validation: &DataValidation,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::DataValidationRange {
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+            validation: validation.clone(),
+        })
+    }
+
+    /// See [`Worksheet::conditional_format_cell`].
+    fn conditional_format_cell(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        conditional_format: &ConditionalFormat,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::ConditionalFormatCell {
+            row,
+            col,
+            conditional_format: conditional_format.clone(),
+        })
+    }
+
+    /// See [`Worksheet::conditional_format_range`].
+    fn conditional_format_range(
+        &mut self,
+        first_row: WorksheetRow,
+        first_col: WorksheetCol,
+        last_row: WorksheetRow,
+        last_col: WorksheetCol,
+        conditional_format: &ConditionalFormat,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::ConditionalFormatRange {
+            first_row,
+            first_col,
+            last_row,
+            last_col,
+            conditional_format: conditional_format.clone(),
+        })
+    }
+
+    /// See [`Worksheet::insert_image`].
+    fn insert_image(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        filename: &str,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Image {
+            row,
+            col,
+            filename: filename.to_string(),
+            options: None,
+        })
+    }
+
+    /// See [`Worksheet::insert_image_opt`].
+    fn insert_image_opt(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        filename: &str,
+        opt: &ImageOptions,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::Image {
+            row,
+            col,
+            filename: filename.to_string(),
+            options: Some(opt.clone()),
+        })
+    }
+
+    /// See [`Worksheet::insert_image_buffer`].
+    fn insert_image_buffer(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        buffer: &[u8],
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::ImageBuffer {
+            row,
+            col,
+            buffer: buffer.to_vec(),
+            options: None,
+        })
+    }
+
+    /// See [`Worksheet::insert_image_buffer_opt`].
+    fn insert_image_buffer_opt(
+        &mut self,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        buffer: &[u8],
+        opt: &ImageOptions,
+    ) -> Result<(), XlsxError> {
+        self.write_op(SheetOp::ImageBuffer {
+            row,
+            col,
+            buffer: buffer.to_vec(),
+            options: Some(opt.clone()),
+        })
+    }
+}
+
+/// Workbook operations shared by [`Workbook`] and [`MockWorkbook`](crate::mock::MockWorkbook).
+pub trait WorkbookWriter {
+    /// Worksheet returned by [`WorkbookWriter::add_worksheet`].
+    type Sheet: SheetWriter;
+    /// Chart returned by [`WorkbookWriter::add_chart`].
+    type Chart: ChartWriter;
+
+    /// See [`Workbook::add_worksheet`].
+    fn add_worksheet(&self, sheet_name: Option<&str>) -> Result<Self::Sheet, XlsxError>;
+
+    /// See [`Workbook::add_chart`].
+    fn add_chart(&self, chart_type: ChartType) -> Self::Chart;
+
+    /// See [`Workbook::define_name`].
+    fn define_name(&self, name: &str, formula: &str) -> Result<(), XlsxError>;
+
+    /// See [`Workbook::add_defined_name`].
+    fn add_defined_name(&self, name: &DefinedName) -> Result<(), XlsxError>;
+
+    /// Insert `chart` into `worksheet`. See [`Worksheet::insert_chart`].
+    fn insert_chart(
+        &self,
+        worksheet: &mut Self::Sheet,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        chart: &Self::Chart,
+    ) -> Result<(), XlsxError>;
+}
+
+impl SheetWriter for Worksheet {
+    fn write_op(&mut self, op: SheetOp) -> Result<(), XlsxError> {
+        op.apply(self)
+    }
+}
+
+impl SheetWriter for SheetBuffer {
+    fn write_op(&mut self, op: SheetOp) -> Result<(), XlsxError> {
+        self.ops.push(op);
+        Ok(())
+    }
+}
+
+impl WorkbookWriter for Workbook {
+    type Sheet = Worksheet;
+    type Chart = Chart;
+
+    fn add_worksheet(&self, sheet_name: Option<&str>) -> Result<Worksheet, XlsxError> {
+        Workbook::add_worksheet(self, sheet_name)
+    }
+
+    fn add_chart(&self, chart_type: ChartType) -> Chart {
+        Workbook::add_chart(self, chart_type)
+    }
+
+    fn define_name(&self, name: &str, formula: &str) -> Result<(), XlsxError> {
+        Workbook::define_name(self, name, formula)
+    }
+
+    fn add_defined_name(&self, name: &DefinedName) -> Result<(), XlsxError> {
+        Workbook::add_defined_name(self, name)
+    }
+
+    fn insert_chart(
+        &self,
+        worksheet: &mut Worksheet,
+        row: WorksheetRow,
+        col: WorksheetCol,
+        chart: &Chart,
+    ) -> Result<(), XlsxError> {
+        worksheet.insert_chart(row, col, chart)
+    }
+}